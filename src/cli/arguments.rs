@@ -1,16 +1,152 @@
-use clap;
+use fillercore::engine::{
+	BotAdapter, BotKind, HillScoring, Interpreters, Latency, Pacing, RecencyScoring, TieBreak,
+};
+use fillercore::models::{OverlapRule, Player, StartLayout};
 use std::path;
+use std::time::Duration;
 
-const PLAYER_ARG: &'static str = "player";
-const JSON_ARG: &'static str = "json";
-const VERBOSE_ARG: &'static str = "verbose";
+const PLAYER_ARG: &str = "player";
+const JSON_ARG: &str = "json";
+const VERBOSE_ARG: &str = "verbose";
+const BLIND_START_ARG: &str = "blind-start";
+const MAP_ARG: &str = "map";
+const DRY_RUN_ARG: &str = "dry-run";
+const SELFCHECK_ARG: &str = "selfcheck";
+const ADAPTER_ARG: &str = "adapter";
+const START_ARG: &str = "start";
+const META_ARG: &str = "meta";
+const FOG_ARG: &str = "fog";
+const MIRROR_PIECES_ARG: &str = "mirror-pieces";
+const POSITION_ARG: &str = "position";
+const RULES_ARG: &str = "rules";
+const BOTS_ARG: &str = "bots";
+const INTERPRETER_ARG: &str = "interpreter";
+const OVERLAP_ARG: &str = "overlap";
+const TO_MOVE_ARG: &str = "to-move";
+const NO_NETWORK_ARG: &str = "no-network";
+const READ_ONLY_ARG: &str = "read-only";
+const BOT_CWD_ARG: &str = "bot-cwd";
+const CHECKPOINT_ARG: &str = "checkpoint";
+const CHECKPOINT_EVERY_ARG: &str = "checkpoint-every";
+const RESUME_ARG: &str = "resume";
+const REPORT_ARG: &str = "report";
+const HTML_STATUS_ARG: &str = "html-status";
+const HTML_EVERY_ARG: &str = "html-every";
+const JSON_LOG_ARG: &str = "json-log";
+const MIN_MOVE_INTERVAL_ARG: &str = "min-move-interval";
+const RECENCY_SCORING_ARG: &str = "recency-scoring";
+const HILL_VALUE_ARG: &str = "hill-value";
+const LATENCY_ARG: &str = "latency";
+const TIE_BREAK_ARG: &str = "tie-break";
+const COLOR_ARG: &str = "color";
+const OUTCOME_EXIT_CODE_ARG: &str = "outcome-exit-code";
 
-const CLAP_PLAYER_ERROR: &'static str = "Clap failed at handling of players";
+const FUZZBOT_CMD: &str = "fuzzbot";
+const BOT_ARG: &str = "bot";
+const ROUNDS_ARG: &str = "rounds";
+const SEED_ARG: &str = "seed";
+
+const MIRROR_CMD: &str = "mirror";
+
+const DOCTOR_CMD: &str = "doctor";
+
+const TOURNAMENT_CMD: &str = "tournament";
+const BOTH_COLORS_ARG: &str = "both-colors";
+const THREADS_ARG: &str = "threads";
+const STORE_ARG: &str = "store";
+
+const GENMAP_CMD: &str = "genmap";
+const WIDTH_ARG: &str = "width";
+const HEIGHT_ARG: &str = "height";
+const OBSTACLES_ARG: &str = "obstacles";
+
+const PLAYBACK_CMD: &str = "playback";
+const REPLAY_ARG: &str = "replay";
+const SPEED_ARG: &str = "speed";
+
+const REPLAY_CMD: &str = "replay";
+const CUT_CMD: &str = "cut";
+const VERIFY_CMD: &str = "verify";
+const CAST_CMD: &str = "cast";
+const AUDIT_CMD: &str = "audit";
+const ANIMATE_CMD: &str = "animate";
+const FPS_ARG: &str = "fps";
+const REALTIME_ARG: &str = "realtime";
+const SEAT_ARG: &str = "seat";
+const FROM_ARG: &str = "from";
+const TO_ARG: &str = "to";
+const OUTPUT_ARG: &str = "output";
+
+const CLAP_PLAYER_ERROR: &str = "Clap failed at handling of players";
 
 pub struct Arguments<'a> {
 	matches: clap::ArgMatches<'a>,
 }
 
+pub struct FuzzbotArguments {
+	pub bot: String,
+	pub rounds: usize,
+	pub seed: Option<u64>,
+}
+
+pub struct MirrorArguments {
+	pub bot: String,
+	pub map: Option<String>,
+	pub seed: Option<u64>,
+}
+
+pub struct TournamentArguments {
+	pub bots: Vec<String>,
+	pub maps: Vec<String>,
+	pub both_colors: bool,
+	pub seed: Option<u64>,
+	pub threads: usize,
+	pub store: Option<String>,
+	pub output: Option<String>,
+}
+
+pub struct DoctorArguments {
+	pub bots: Vec<String>,
+	pub replay_dir: String,
+}
+
+pub struct GenmapArguments {
+	pub width: usize,
+	pub height: usize,
+	pub obstacles: f64,
+	pub seed: Option<u64>,
+	pub output: Option<String>,
+}
+
+pub struct ReplayCutArguments {
+	pub replay: String,
+	pub from: usize,
+	pub to: usize,
+	pub output: Option<String>,
+}
+
+pub struct ReplayCastArguments {
+	pub replay: String,
+	pub speed: f64,
+	pub output: Option<String>,
+}
+
+pub struct ReplayAnimateArguments {
+	pub replay: String,
+	pub pacing: Pacing,
+}
+
+pub struct ReplayAuditArguments {
+	pub replay: String,
+	pub bot: String,
+	pub player: Player,
+}
+
+pub struct PlaybackArguments {
+	pub replay: String,
+	pub speed: f64,
+}
+
 impl<'a> Arguments<'a> {
 	pub fn new() -> Self {
 		let matches = clap::App::new("Filler_mk2")
@@ -20,6 +156,47 @@ impl<'a> Arguments<'a> {
 			.arg(player_arg())
 			.arg(json_arg())
 			.arg(verbose_arg())
+			.arg(blind_start_arg())
+			.arg(map_arg())
+			.arg(dry_run_arg())
+			.arg(selfcheck_arg())
+			.arg(adapter_arg())
+			.arg(start_arg())
+			.arg(meta_arg())
+			.arg(fog_arg())
+			.arg(mirror_pieces_arg())
+			.arg(position_arg())
+			.arg(seed_arg())
+			.arg(rules_arg())
+			.arg(bots_arg())
+			.arg(interpreter_arg())
+			.arg(overlap_arg())
+			.arg(to_move_arg())
+			.arg(no_network_arg())
+			.arg(read_only_arg())
+			.arg(bot_cwd_arg())
+			.arg(checkpoint_arg())
+			.arg(checkpoint_every_arg())
+			.arg(resume_arg())
+			.arg(report_arg())
+			.arg(html_status_arg())
+			.arg(html_every_arg())
+			.arg(json_log_arg())
+			.arg(min_move_interval_arg())
+			.arg(recency_scoring_arg())
+			.arg(hill_value_arg())
+			.arg(latency_arg())
+			.arg(tie_break_arg())
+			.arg(color_arg())
+			.arg(outcome_exit_code_arg())
+			.setting(clap::AppSettings::SubcommandsNegateReqs)
+			.subcommand(fuzzbot_cmd())
+			.subcommand(mirror_cmd())
+			.subcommand(genmap_cmd())
+			.subcommand(doctor_cmd())
+			.subcommand(tournament_cmd())
+			.subcommand(playback_cmd())
+			.subcommand(replay_cmd())
 			.get_matches();
 
 		Arguments { matches }
@@ -42,17 +219,315 @@ impl<'a> Arguments<'a> {
 	pub fn verbose(&self) -> bool {
 		self.matches.is_present(VERBOSE_ARG)
 	}
+
+	pub fn blind_start(&self) -> bool {
+		self.matches.is_present(BLIND_START_ARG)
+	}
+
+	pub fn map_path(&self) -> Option<&str> {
+		self.matches.value_of(MAP_ARG)
+	}
+
+	pub fn start_layout(&self) -> Option<StartLayout> {
+		self.matches.value_of(START_ARG).map(|s| s.parse().unwrap())
+	}
+
+	pub fn recency_scoring(&self) -> Option<RecencyScoring> {
+		self.matches.value_of(RECENCY_SCORING_ARG).map(|s| s.parse().unwrap())
+	}
+
+	pub fn hill_scoring(&self) -> Option<HillScoring> {
+		let value = self.matches.value_of(HILL_VALUE_ARG)?;
+		Some(HillScoring {
+			value: value.parse().unwrap(),
+		})
+	}
+
+	pub fn overlap_rule(&self) -> Option<OverlapRule> {
+		let rule = self.matches.value_of(OVERLAP_ARG)?;
+		Some(rule.parse().unwrap())
+	}
+
+	pub fn bots_path(&self) -> Option<&str> {
+		self.matches.value_of(BOTS_ARG)
+	}
+
+	pub fn interpreters(&self) -> Vec<(String, Vec<String>)> {
+		match self.matches.values_of(INTERPRETER_ARG) {
+			Some(values) => values
+				.map(|v| Interpreters::parse_mapping(v).unwrap())
+				.collect(),
+			None => vec![],
+		}
+	}
+
+	pub fn rules_path(&self) -> Option<&str> {
+		self.matches.value_of(RULES_ARG)
+	}
+
+	pub fn seed(&self) -> Option<u64> {
+		self.matches.value_of(SEED_ARG).map(|s| s.parse().unwrap())
+	}
+
+	pub fn position_path(&self) -> Option<&str> {
+		self.matches.value_of(POSITION_ARG)
+	}
+
+	pub fn to_move(&self) -> Option<Player> {
+		match self.matches.value_of(TO_MOVE_ARG)? {
+			"2" => Some(Player::Player2),
+			_ => Some(Player::Player1),
+		}
+	}
+
+	pub fn no_network(&self) -> bool {
+		self.matches.is_present(NO_NETWORK_ARG)
+	}
+
+	pub fn read_only(&self) -> bool {
+		self.matches.is_present(READ_ONLY_ARG)
+	}
+
+	pub fn bot_cwds(&self) -> Vec<&str> {
+		match self.matches.values_of(BOT_CWD_ARG) {
+			Some(values) => values.collect(),
+			None => vec![],
+		}
+	}
+
+	pub fn mirror_pieces(&self) -> bool {
+		self.matches.is_present(MIRROR_PIECES_ARG)
+	}
+
+	pub fn selfcheck(&self) -> bool {
+		self.matches.is_present(SELFCHECK_ARG)
+	}
+
+	pub fn dry_run(&self) -> bool {
+		self.matches.is_present(DRY_RUN_ARG)
+	}
+
+	pub fn report(&self) -> bool {
+		self.matches.is_present(REPORT_ARG)
+	}
+
+	pub fn fuzzbot(&self) -> Option<FuzzbotArguments> {
+		let matches = self.matches.subcommand_matches(FUZZBOT_CMD)?;
+		Some(FuzzbotArguments {
+			bot: String::from(matches.value_of(BOT_ARG).unwrap()),
+			rounds: matches.value_of(ROUNDS_ARG).unwrap().parse().unwrap(),
+			seed: matches.value_of(SEED_ARG).map(|s| s.parse().unwrap()),
+		})
+	}
+
+	pub fn checkpoint(&self) -> Option<(&str, usize)> {
+		let path = self.matches.value_of(CHECKPOINT_ARG)?;
+		let every = self.matches.value_of(CHECKPOINT_EVERY_ARG).unwrap();
+		Some((path, every.parse().unwrap()))
+	}
+
+	pub fn resume_path(&self) -> Option<&str> {
+		self.matches.value_of(RESUME_ARG)
+	}
+
+	pub fn html_status(&self) -> Option<(&str, usize)> {
+		let path = self.matches.value_of(HTML_STATUS_ARG)?;
+		let every = self.matches.value_of(HTML_EVERY_ARG).unwrap();
+		Some((path, every.parse().unwrap()))
+	}
+
+	pub fn json_log(&self) -> Option<&str> {
+		self.matches.value_of(JSON_LOG_ARG)
+	}
+
+	pub fn min_move_interval(&self) -> Option<Duration> {
+		let millis = self.matches.value_of(MIN_MOVE_INTERVAL_ARG)?;
+		Some(Duration::from_millis(millis.parse().unwrap()))
+	}
+
+	pub fn mirror(&self) -> Option<MirrorArguments> {
+		let matches = self.matches.subcommand_matches(MIRROR_CMD)?;
+		Some(MirrorArguments {
+			bot: String::from(matches.value_of(BOT_ARG).unwrap()),
+			map: matches.value_of(MAP_ARG).map(String::from),
+			seed: matches.value_of(SEED_ARG).map(|s| s.parse().unwrap()),
+		})
+	}
+
+	pub fn tournament(&self) -> Option<TournamentArguments> {
+		let matches = self.matches.subcommand_matches(TOURNAMENT_CMD)?;
+		Some(TournamentArguments {
+			bots: matches.values_of(BOT_ARG).unwrap().map(String::from).collect(),
+			maps: matches
+				.values_of(MAP_ARG)
+				.map(|maps| maps.map(String::from).collect())
+				.unwrap_or_default(),
+			both_colors: matches.is_present(BOTH_COLORS_ARG),
+			seed: matches.value_of(SEED_ARG).map(|s| s.parse().unwrap()),
+			threads: matches.value_of(THREADS_ARG).unwrap().parse().unwrap(),
+			store: matches.value_of(STORE_ARG).map(String::from),
+			output: matches.value_of(OUTPUT_ARG).map(String::from),
+		})
+	}
+
+	pub fn doctor(&self) -> Option<DoctorArguments> {
+		let matches = self.matches.subcommand_matches(DOCTOR_CMD)?;
+		Some(DoctorArguments {
+			bots: matches
+				.values_of(BOT_ARG)
+				.map(|bots| bots.map(String::from).collect())
+				.unwrap_or_default(),
+			replay_dir: matches.value_of(JSON_ARG).unwrap().to_string(),
+		})
+	}
+
+	pub fn genmap(&self) -> Option<GenmapArguments> {
+		let matches = self.matches.subcommand_matches(GENMAP_CMD)?;
+		Some(GenmapArguments {
+			width: matches.value_of(WIDTH_ARG).unwrap().parse().unwrap(),
+			height: matches.value_of(HEIGHT_ARG).unwrap().parse().unwrap(),
+			obstacles: matches.value_of(OBSTACLES_ARG).unwrap().parse().unwrap(),
+			seed: matches.value_of(SEED_ARG).map(|s| s.parse().unwrap()),
+			output: matches.value_of(OUTPUT_ARG).map(String::from),
+		})
+	}
+
+	pub fn playback(&self) -> Option<PlaybackArguments> {
+		let matches = self.matches.subcommand_matches(PLAYBACK_CMD)?;
+		Some(PlaybackArguments {
+			replay: matches.value_of(REPLAY_ARG).unwrap().to_string(),
+			speed: matches.value_of(SPEED_ARG).unwrap().parse().unwrap(),
+		})
+	}
+
+	pub fn replay_cut(&self) -> Option<ReplayCutArguments> {
+		let matches = self
+			.matches
+			.subcommand_matches(REPLAY_CMD)?
+			.subcommand_matches(CUT_CMD)?;
+		Some(ReplayCutArguments {
+			replay: matches.value_of(REPLAY_ARG).unwrap().to_string(),
+			from: matches.value_of(FROM_ARG).unwrap().parse().unwrap(),
+			to: matches.value_of(TO_ARG).unwrap().parse().unwrap(),
+			output: matches.value_of(OUTPUT_ARG).map(String::from),
+		})
+	}
+
+	pub fn replay_cast(&self) -> Option<ReplayCastArguments> {
+		let matches = self
+			.matches
+			.subcommand_matches(REPLAY_CMD)?
+			.subcommand_matches(CAST_CMD)?;
+		Some(ReplayCastArguments {
+			replay: matches.value_of(REPLAY_ARG).unwrap().to_string(),
+			speed: matches.value_of(SPEED_ARG).unwrap().parse().unwrap(),
+			output: matches.value_of(OUTPUT_ARG).map(String::from),
+		})
+	}
+
+	pub fn replay_animate(&self) -> Option<ReplayAnimateArguments> {
+		let matches = self
+			.matches
+			.subcommand_matches(REPLAY_CMD)?
+			.subcommand_matches(ANIMATE_CMD)?;
+		let pacing = match matches.is_present(REALTIME_ARG) {
+			true => Pacing::Realtime {
+				speed: matches.value_of(SPEED_ARG).map_or(1.0, |s| s.parse().unwrap()),
+			},
+			false => Pacing::Fps(matches.value_of(FPS_ARG).unwrap().parse().unwrap()),
+		};
+		Some(ReplayAnimateArguments {
+			replay: matches.value_of(REPLAY_ARG).unwrap().to_string(),
+			pacing,
+		})
+	}
+
+	pub fn replay_audit(&self) -> Option<ReplayAuditArguments> {
+		let matches = self
+			.matches
+			.subcommand_matches(REPLAY_CMD)?
+			.subcommand_matches(AUDIT_CMD)?;
+		Some(ReplayAuditArguments {
+			replay: matches.value_of(REPLAY_ARG).unwrap().to_string(),
+			bot: matches.value_of(BOT_ARG).unwrap().to_string(),
+			player: match matches.value_of(SEAT_ARG).unwrap() {
+				"2" => Player::Player2,
+				_ => Player::Player1,
+			},
+		})
+	}
+
+	pub fn replay_verify(&self) -> Option<&str> {
+		self.matches
+			.subcommand_matches(REPLAY_CMD)?
+			.subcommand_matches(VERIFY_CMD)?
+			.value_of(REPLAY_ARG)
+	}
+
+	pub fn adapters(&self) -> Vec<BotAdapter> {
+		match self.matches.values_of(ADAPTER_ARG) {
+			Some(values) => values.map(|v| v.parse().unwrap()).collect(),
+			None => vec![],
+		}
+	}
+
+	pub fn fog(&self) -> Vec<Option<usize>> {
+		match self.matches.values_of(FOG_ARG) {
+			Some(values) => values.map(|v| v.parse().ok()).collect(),
+			None => vec![],
+		}
+	}
+
+	pub fn latencies(&self) -> Vec<Latency> {
+		match self.matches.values_of(LATENCY_ARG) {
+			Some(values) => values.map(|v| v.parse().unwrap()).collect(),
+			None => vec![],
+		}
+	}
+
+	pub fn colors(&self) -> Vec<&str> {
+		match self.matches.values_of(COLOR_ARG) {
+			Some(values) => values.collect(),
+			None => vec![],
+		}
+	}
+
+	pub fn tie_breaks(&self) -> Vec<TieBreak> {
+		match self.matches.values_of(TIE_BREAK_ARG) {
+			Some(values) => values.map(|v| v.parse().unwrap()).collect(),
+			None => vec![],
+		}
+	}
+
+	pub fn outcome_exit_code(&self) -> bool {
+		self.matches.is_present(OUTCOME_EXIT_CODE_ARG)
+	}
+
+	pub fn metadata(&self) -> Vec<(&str, &str)> {
+		match self.matches.values_of(META_ARG) {
+			Some(values) => values.map(|v| split_meta(v).unwrap()).collect(),
+			None => vec![],
+		}
+	}
 }
 
 fn validate_player_path(path: String) -> Result<(), String> {
+	if !BotKind::of(&path).is_process() {
+		return Ok(());
+	}
 	let path = path::Path::new(&path);
+	// Looked up in the bot registry once it has been read
+	if !path.exists() && path.components().count() == 1 && path.extension().is_none() {
+		return Ok(());
+	}
 
 	match path.exists() {
 		false => Err(String::from("Path invalid: Path not found")),
 		true if path.is_dir() => Err(String::from("Path invalid: Path is directory")),
 		true => match path.extension() {
-			Some(ex) if ex == "filler" => Ok(()),
-			_ => Err(String::from("File invalid: '.filler' file required")),
+			// Scripts are checked once the interpreters are known
+			Some(_) => Ok(()),
+			None => Err(String::from("File invalid: '.filler' file or script required")),
 		},
 	}
 }
@@ -74,6 +549,7 @@ fn player_arg<'a>() -> clap::Arg<'a, 'a> {
 		.takes_value(true)
 		.multiple(true)
 		.value_name("PLAYER_PATH")
+		.help("player program or script (.py, .js and .jar run through their interpreter), a name from the bot registry, 'random' or 'greedy' for a built-in bot, or tcp:PORT to wait for a bot to connect (and reconnect within 10s if its connection drops). A lone player faces the random bot")
 		.required(true)
 		.max_values(2)
 		.validator(validate_player_path)
@@ -95,3 +571,659 @@ fn verbose_arg<'a>() -> clap::Arg<'a, 'a> {
 		.long(VERBOSE_ARG)
 		.help("displays each placement on the terminal")
 }
+
+fn blind_start_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(BLIND_START_ARG)
+		.long(BLIND_START_ARG)
+		.help("randomizes the start points and keeps them hidden from the players")
+}
+
+fn map_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(MAP_ARG)
+		.long(MAP_ARG)
+		.short("m")
+		.takes_value(true)
+		.value_name("MAP")
+		.help("map file or built-in map name (tiny, classic, huge, pillars, wall) to play on")
+}
+
+fn selfcheck_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(SELFCHECK_ARG)
+		.long(SELFCHECK_ARG)
+		.help("plays a short game between built-in bots and checks the engine before starting")
+}
+
+fn dry_run_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(DRY_RUN_ARG)
+		.long(DRY_RUN_ARG)
+		.help("checks the configuration and exits without running the players")
+}
+
+fn report_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(REPORT_ARG)
+		.long(REPORT_ARG)
+		.help("prints diagnostics about each bot once the game is over")
+}
+
+fn validate_adapter(adapter: String) -> Result<(), String> {
+	adapter.parse::<BotAdapter>().map(|_| ())
+}
+
+fn adapter_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(ADAPTER_ARG)
+		.long(ADAPTER_ARG)
+		.takes_value(true)
+		.multiple(true)
+		.number_of_values(1)
+		.value_name("OPTIONS")
+		.validator(validate_adapter)
+		.help("I/O adapter for an off-spec player, given in player order (legacy-board,swap-tokens,swap-xy,crlf)")
+}
+
+fn validate_recency_scoring(weights: String) -> Result<(), String> {
+	weights.parse::<RecencyScoring>().map(|_| ())
+}
+
+fn recency_scoring_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(RECENCY_SCORING_ARG)
+		.long(RECENCY_SCORING_ARG)
+		.takes_value(true)
+		.value_name("FULL,FLOOR,DECAY")
+		.validator(validate_recency_scoring)
+		.conflicts_with(HILL_VALUE_ARG)
+		.help("scores owned cells by age: FULL points when just claimed, DECAY fewer per move played since, at least FLOOR")
+}
+
+fn hill_value_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(HILL_VALUE_ARG)
+		.long(HILL_VALUE_ARG)
+		.takes_value(true)
+		.value_name("POINTS")
+		.validator(validate_number)
+		.conflicts_with(RECENCY_SCORING_ARG)
+		.help("scores owned cells instead of placed pieces, with each hill ('+' on the map) worth POINTS")
+}
+
+fn validate_start_layout(layout: String) -> Result<(), String> {
+	layout.parse::<StartLayout>().map(|_| ())
+}
+
+fn start_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(START_ARG)
+		.long(START_ARG)
+		.takes_value(true)
+		.value_name("LAYOUT")
+		.validator(validate_start_layout)
+		.conflicts_with_all(&[BLIND_START_ARG, POSITION_ARG])
+		.help("start points: corners, centered, mirrored:X,Y or custom:X,Y:X,Y")
+}
+
+fn validate_overlap(rule: String) -> Result<(), String> {
+	rule.parse::<OverlapRule>().map(|_| ())
+}
+
+fn overlap_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(OVERLAP_ARG)
+		.long(OVERLAP_ARG)
+		.takes_value(true)
+		.value_name("N|N+|N-M")
+		.validator(validate_overlap)
+		.help("own cells a piece has to cover: exactly N, at least N or between N and M (default 1)")
+}
+
+fn bots_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(BOTS_ARG)
+		.long(BOTS_ARG)
+		.takes_value(true)
+		.value_name("REGISTRY_PATH")
+		.help("TOML file mapping bot names to programs, ./bots.toml is used if it exists")
+}
+
+fn validate_interpreter(mapping: String) -> Result<(), String> {
+	Interpreters::parse_mapping(&mapping).map(|_| ())
+}
+
+fn interpreter_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(INTERPRETER_ARG)
+		.long(INTERPRETER_ARG)
+		.takes_value(true)
+		.multiple(true)
+		.number_of_values(1)
+		.value_name("EXT=COMMAND")
+		.validator(validate_interpreter)
+		.help("runs bots ending in EXT with COMMAND, e.g. py=pypy3; an empty COMMAND runs them directly")
+}
+
+fn rules_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(RULES_ARG)
+		.long(RULES_ARG)
+		.takes_value(true)
+		.value_name("RULES_PATH")
+		.help("JSON file with the variant rules to play under, other rule options are applied on top")
+}
+
+fn seed_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(SEED_ARG)
+		.long(SEED_ARG)
+		.takes_value(true)
+		.validator(validate_number)
+		.help("seed for the random pieces, as recorded in a replay's header, to deal the same pieces again")
+}
+
+fn position_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(POSITION_ARG)
+		.long(POSITION_ARG)
+		.takes_value(true)
+		.value_name("POSITION_PATH")
+		.conflicts_with_all(&[MAP_ARG, START_ARG, BLIND_START_ARG])
+		.help("starts from a position file (a map, VM board dump or run-length board, then pieces) or the beginning of a replay")
+}
+
+fn to_move_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(TO_MOVE_ARG)
+		.long(TO_MOVE_ARG)
+		.takes_value(true)
+		.possible_values(&["1", "2"])
+		.help("player that moves first")
+}
+
+fn no_network_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(NO_NETWORK_ARG)
+		.long(NO_NETWORK_ARG)
+		.help("starts the players without network access (Linux, needs unshare)")
+}
+
+fn read_only_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(READ_ONLY_ARG)
+		.long(READ_ONLY_ARG)
+		.help("gives the players a read-only view of the filesystem (Linux, needs unshare and setpriv)")
+}
+
+fn validate_latency(latency: String) -> Result<(), String> {
+	latency.parse::<Latency>().map(|_| ())
+}
+
+fn latency_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(LATENCY_ARG)
+		.long(LATENCY_ARG)
+		.takes_value(true)
+		.multiple(true)
+		.number_of_values(1)
+		.value_name("MS[~JITTER]")
+		.validator(validate_latency)
+		.help("delay added to every frame sent to each player, counted against its time, given in player order")
+}
+
+fn color_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(COLOR_ARG)
+		.long(COLOR_ARG)
+		.takes_value(true)
+		.multiple(true)
+		.number_of_values(1)
+		.value_name("CSS_COLOR")
+		.help("colour each player is drawn in, given in player order")
+}
+
+fn tie_break_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(TIE_BREAK_ARG)
+		.long(TIE_BREAK_ARG)
+		.takes_value(true)
+		.multiple(true)
+		.number_of_values(1)
+		.possible_values(&["errors", "clock"])
+		.help("decides a game with level scores, applied in the order given; without one it is a draw")
+}
+
+fn outcome_exit_code_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(OUTCOME_EXIT_CODE_ARG)
+		.long(OUTCOME_EXIT_CODE_ARG)
+		.help("exits with the winner's player number, 3 for a draw or 4 if neither bot placed a piece before errors ended the game")
+}
+
+fn bot_cwd_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(BOT_CWD_ARG)
+		.long(BOT_CWD_ARG)
+		.takes_value(true)
+		.multiple(true)
+		.number_of_values(1)
+		.value_name("DIR")
+		.help("working directory for each player, given in player order")
+}
+
+fn mirror_pieces_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(MIRROR_PIECES_ARG)
+		.long(MIRROR_PIECES_ARG)
+		.help("deals player 2 the previous turn's pieces, rotated to match its mirrored start")
+}
+
+fn validate_fog(fog: String) -> Result<(), String> {
+	match fog.as_str() {
+		"full" => Ok(()),
+		_ => validate_number(fog),
+	}
+}
+
+fn fog_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(FOG_ARG)
+		.long(FOG_ARG)
+		.takes_value(true)
+		.multiple(true)
+		.number_of_values(1)
+		.value_name("RADIUS")
+		.validator(validate_fog)
+		.help("limits how far around its own cells a player can see, given in player order ('full' for no limit)")
+}
+
+fn split_meta(meta: &str) -> Option<(&str, &str)> {
+	let mut parts = meta.splitn(2, '=');
+	match (parts.next(), parts.next()) {
+		(Some(key), Some(value)) if !key.is_empty() => Some((key, value)),
+		_ => None,
+	}
+}
+
+fn validate_meta(meta: String) -> Result<(), String> {
+	split_meta(&meta)
+		.map(|_| ())
+		.ok_or_else(|| String::from("Expected KEY=VALUE"))
+}
+
+fn meta_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(META_ARG)
+		.long(META_ARG)
+		.takes_value(true)
+		.multiple(true)
+		.number_of_values(1)
+		.value_name("KEY=VALUE")
+		.validator(validate_meta)
+		.help("metadata recorded in the replay, e.g. tournament=spring or round=3")
+}
+
+fn checkpoint_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(CHECKPOINT_ARG)
+		.long(CHECKPOINT_ARG)
+		.takes_value(true)
+		.value_name("STATE_PATH")
+		.help("periodically saves the game state to this file")
+}
+
+fn checkpoint_every_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(CHECKPOINT_EVERY_ARG)
+		.long(CHECKPOINT_EVERY_ARG)
+		.takes_value(true)
+		.value_name("MOVES")
+		.default_value("100")
+		.validator(validate_number)
+		.help("number of moves between checkpoints")
+}
+
+fn resume_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(RESUME_ARG)
+		.long(RESUME_ARG)
+		.takes_value(true)
+		.value_name("STATE_PATH")
+		.help("resumes the game saved in this checkpoint, with the same bots and rules")
+}
+
+fn html_status_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(HTML_STATUS_ARG)
+		.long(HTML_STATUS_ARG)
+		.takes_value(true)
+		.value_name("HTML_PATH")
+		.help("keeps a self-refreshing HTML page of the game in progress at this path")
+}
+
+fn json_log_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(JSON_LOG_ARG)
+		.long(JSON_LOG_ARG)
+		.takes_value(true)
+		.value_name("JSONL_PATH")
+		.help("writes one JSON object per move and a summary to this file, for analysis with jq")
+}
+
+fn html_every_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(HTML_EVERY_ARG)
+		.long(HTML_EVERY_ARG)
+		.takes_value(true)
+		.value_name("MOVES")
+		.default_value("1")
+		.validator(validate_number)
+		.help("number of moves between HTML status updates")
+}
+
+fn min_move_interval_arg<'a>() -> clap::Arg<'a, 'a> {
+	clap::Arg::with_name(MIN_MOVE_INTERVAL_ARG)
+		.long(MIN_MOVE_INTERVAL_ARG)
+		.takes_value(true)
+		.value_name("MS")
+		.validator(validate_number)
+		.help("slows the game to at most one move per MS milliseconds while it is being displayed")
+}
+
+fn validate_number(number: String) -> Result<(), String> {
+	number
+		.parse::<u64>()
+		.map(|_| ())
+		.map_err(|_| String::from("Not a positive number"))
+}
+
+fn fuzzbot_cmd<'a>() -> clap::App<'a, 'a> {
+	clap::SubCommand::with_name(FUZZBOT_CMD)
+		.about("sends random plateaus and pieces to a player and reports crashes, malformed replies and hangs")
+		.arg(
+			clap::Arg::with_name(BOT_ARG)
+				.required(true)
+				.value_name("PLAYER_PATH")
+				.validator(validate_player_path),
+		)
+		.arg(
+			clap::Arg::with_name(ROUNDS_ARG)
+				.long(ROUNDS_ARG)
+				.takes_value(true)
+				.default_value("100")
+				.validator(validate_number),
+		)
+		.arg(
+			clap::Arg::with_name(SEED_ARG)
+				.long(SEED_ARG)
+				.takes_value(true)
+				.validator(validate_number),
+		)
+}
+
+fn mirror_cmd<'a>() -> clap::App<'a, 'a> {
+	clap::SubCommand::with_name(MIRROR_CMD)
+		.about("plays a player against itself, rerun and with the seats exchanged, and reports where its moves differ")
+		.arg(
+			clap::Arg::with_name(BOT_ARG)
+				.required(true)
+				.value_name("PLAYER_PATH")
+				.validator(validate_player_path),
+		)
+		.arg(map_arg())
+		.arg(
+			clap::Arg::with_name(SEED_ARG)
+				.long(SEED_ARG)
+				.takes_value(true)
+				.validator(validate_number),
+		)
+}
+
+fn tournament_cmd<'a>() -> clap::App<'a, 'a> {
+	clap::SubCommand::with_name(TOURNAMENT_CMD)
+		.about("plays every pair of players against each other and ranks them")
+		.arg(
+			clap::Arg::with_name(BOT_ARG)
+				.required(true)
+				.min_values(2)
+				.value_name("PLAYER_PATH")
+				.validator(validate_player_path),
+		)
+		.arg(
+			map_arg()
+				.multiple(true)
+				.number_of_values(1)
+				.help("map file or built-in map name to play every pairing on, may be repeated"),
+		)
+		.arg(
+			clap::Arg::with_name(BOTH_COLORS_ARG)
+				.long(BOTH_COLORS_ARG)
+				.help("plays every pairing a second time with the seats exchanged"),
+		)
+		.arg(
+			clap::Arg::with_name(SEED_ARG)
+				.long(SEED_ARG)
+				.takes_value(true)
+				.validator(validate_number),
+		)
+		.arg(
+			clap::Arg::with_name(THREADS_ARG)
+				.long(THREADS_ARG)
+				.takes_value(true)
+				.value_name("N")
+				.default_value("1")
+				.validator(validate_number)
+				.help("number of games played at once"),
+		)
+		.arg(
+			clap::Arg::with_name(STORE_ARG)
+				.long(STORE_ARG)
+				.takes_value(true)
+				.value_name("STORAGE")
+				.help(
+					"where to save every game as it ends: a directory of JSON files, sqlite:PATH or \
+					 memory. Games already saved there are not played again with the same seed.",
+				),
+		)
+		.arg(
+			clap::Arg::with_name(OUTPUT_ARG)
+				.long(OUTPUT_ARG)
+				.short("o")
+				.takes_value(true)
+				.value_name("PATH")
+				.help("file to write the standings and games to as JSON"),
+		)
+}
+
+fn doctor_cmd<'a>() -> clap::App<'a, 'a> {
+	clap::SubCommand::with_name(DOCTOR_CMD)
+		.about("checks the bots, interpreters and replay directory for common setup problems and suggests fixes")
+		.arg(
+			clap::Arg::with_name(BOT_ARG)
+				.multiple(true)
+				.value_name("PLAYER_PATH")
+				.help("bots to check besides those in the bot registry"),
+		)
+		.arg(
+			clap::Arg::with_name(JSON_ARG)
+				.long(JSON_ARG)
+				.takes_value(true)
+				.value_name("OUTPUT_DIR")
+				.default_value(".")
+				.help("folder replays will be saved in"),
+		)
+}
+
+fn validate_obstacles(density: String) -> Result<(), String> {
+	match density.parse::<f64>() {
+		Ok(density) if (0.0..1.0).contains(&density) => Ok(()),
+		_ => Err(String::from("Not a number from 0 up to 1")),
+	}
+}
+
+fn genmap_cmd<'a>() -> clap::App<'a, 'a> {
+	clap::SubCommand::with_name(GENMAP_CMD)
+		.about("generates a map with mirrored starts and obstacles, previews it and checks that it is fair")
+		.arg(
+			clap::Arg::with_name(WIDTH_ARG)
+				.long(WIDTH_ARG)
+				.takes_value(true)
+				.required(true)
+				.validator(validate_number),
+		)
+		.arg(
+			clap::Arg::with_name(HEIGHT_ARG)
+				.long(HEIGHT_ARG)
+				.takes_value(true)
+				.required(true)
+				.validator(validate_number),
+		)
+		.arg(
+			clap::Arg::with_name(OBSTACLES_ARG)
+				.long(OBSTACLES_ARG)
+				.takes_value(true)
+				.default_value("0")
+				.validator(validate_obstacles)
+				.help("share of the empty cells to block"),
+		)
+		.arg(
+			clap::Arg::with_name(SEED_ARG)
+				.long(SEED_ARG)
+				.takes_value(true)
+				.validator(validate_number),
+		)
+		.arg(
+			clap::Arg::with_name(OUTPUT_ARG)
+				.long(OUTPUT_ARG)
+				.short("o")
+				.takes_value(true)
+				.value_name("PATH")
+				.help("file to write the map to, only previewed by default"),
+		)
+}
+
+fn validate_speed(speed: String) -> Result<(), String> {
+	match speed.parse::<f64>() {
+		Ok(speed) if speed >= 0.0 => Ok(()),
+		_ => Err(String::from("Not a positive number")),
+	}
+}
+
+fn validate_fps(fps: String) -> Result<(), String> {
+	match fps.parse::<f64>() {
+		Ok(fps) if fps > 0.0 => Ok(()),
+		_ => Err(String::from("Not a number above 0")),
+	}
+}
+
+fn playback_cmd<'a>() -> clap::App<'a, 'a> {
+	clap::SubCommand::with_name(PLAYBACK_CMD)
+		.about("replays a saved game at the pace it was originally played")
+		.arg(
+			clap::Arg::with_name(REPLAY_ARG)
+				.required(true)
+				.value_name("REPLAY_PATH"),
+		)
+		.arg(
+			clap::Arg::with_name(SPEED_ARG)
+				.long(SPEED_ARG)
+				.takes_value(true)
+				.default_value("1")
+				.validator(validate_speed)
+				.help("playback speed multiplier, 0 plays without pauses"),
+		)
+}
+
+fn replay_cmd<'a>() -> clap::App<'a, 'a> {
+	clap::SubCommand::with_name(REPLAY_CMD)
+		.about("tools for saved replays")
+		.setting(clap::AppSettings::SubcommandRequiredElseHelp)
+		.subcommand(
+			clap::SubCommand::with_name(CUT_CMD)
+				.about("extracts moves FROM..TO as a standalone replay starting from the board at FROM")
+				.arg(
+					clap::Arg::with_name(REPLAY_ARG)
+						.required(true)
+						.value_name("REPLAY_PATH"),
+				)
+				.arg(
+					clap::Arg::with_name(FROM_ARG)
+						.long(FROM_ARG)
+						.takes_value(true)
+						.required(true)
+						.validator(validate_number),
+				)
+				.arg(
+					clap::Arg::with_name(TO_ARG)
+						.long(TO_ARG)
+						.takes_value(true)
+						.required(true)
+						.validator(validate_number),
+				)
+				.arg(
+					clap::Arg::with_name(OUTPUT_ARG)
+						.long(OUTPUT_ARG)
+						.short("o")
+						.takes_value(true)
+						.value_name("PATH")
+						.help("file to write the slice to, stdout by default"),
+				),
+		)
+		.subcommand(
+			clap::SubCommand::with_name(CAST_CMD)
+				.about("converts a replay to an asciinema cast of the board in colour")
+				.arg(
+					clap::Arg::with_name(REPLAY_ARG)
+						.required(true)
+						.value_name("REPLAY_PATH"),
+				)
+				.arg(
+					clap::Arg::with_name(SPEED_ARG)
+						.long(SPEED_ARG)
+						.takes_value(true)
+						.default_value("1")
+						.validator(validate_speed)
+						.help("playback speed multiplier, 0 gives every move the same short pause"),
+				)
+				.arg(
+					clap::Arg::with_name(OUTPUT_ARG)
+						.long(OUTPUT_ARG)
+						.short("o")
+						.takes_value(true)
+						.value_name("PATH")
+						.help("file to write the cast to, stdout by default"),
+				),
+		)
+		.subcommand(
+			clap::SubCommand::with_name(ANIMATE_CMD)
+				.about("animates a replay in the terminal, redrawing the coloured board for every move")
+				.arg(
+					clap::Arg::with_name(REPLAY_ARG)
+						.required(true)
+						.value_name("REPLAY_PATH"),
+				)
+				.arg(
+					clap::Arg::with_name(FPS_ARG)
+						.long(FPS_ARG)
+						.takes_value(true)
+						.default_value("10")
+						.validator(validate_fps)
+						.help("moves shown per second"),
+				)
+				.arg(
+					clap::Arg::with_name(REALTIME_ARG)
+						.long(REALTIME_ARG)
+						.help("shows each move for the time it originally took instead of a fixed rate"),
+				)
+				.arg(
+					clap::Arg::with_name(SPEED_ARG)
+						.long(SPEED_ARG)
+						.takes_value(true)
+						.requires(REALTIME_ARG)
+						.validator(validate_fps)
+						.help("speed multiplier for --realtime (default 1)"),
+				),
+		)
+		.subcommand(
+			clap::SubCommand::with_name(AUDIT_CMD)
+				.about("replays a player's recorded turns to its bot and reports divergent answers and implausibly fast moves")
+				.arg(
+					clap::Arg::with_name(REPLAY_ARG)
+						.required(true)
+						.value_name("REPLAY_PATH"),
+				)
+				.arg(
+					clap::Arg::with_name(BOT_ARG)
+						.required(true)
+						.value_name("PLAYER_PATH")
+						.validator(validate_player_path),
+				)
+				.arg(
+					clap::Arg::with_name(SEAT_ARG)
+						.long(SEAT_ARG)
+						.takes_value(true)
+						.possible_values(&["1", "2"])
+						.default_value("1")
+						.help("seat the bot played in"),
+				),
+		)
+		.subcommand(
+			clap::SubCommand::with_name(VERIFY_CMD)
+				.about("replays a game and reports the first move whose board hash does not match")
+				.arg(
+					clap::Arg::with_name(REPLAY_ARG)
+						.required(true)
+						.value_name("REPLAY_PATH"),
+				),
+		)
+}
@@ -2,57 +2,596 @@ extern crate chrono;
 extern crate clap;
 extern crate fillercore;
 
-use engine::Engine;
+use engine::bot_registry::{self, BotEntry};
+use engine::{
+    animate, asciicast, audit, doctor, fuzz, mirror_match, native_bot, playback, storage, BotKind,
+    BotRegistry, Engine, GameState, HtmlStatus, Interpreters, JsonLog, Position, Replay, RuleSet,
+    Sandbox, Tournament,
+};
 use fillercore::engine;
+use fillercore::eval;
+use fillercore::models::piece::RANGE_DEFAULT;
+use fillercore::models::{maps, PieceBag, Plateau, Player};
 use std::path;
 
-use path::Path;
-use std::error::Error;
+use path::{Path, PathBuf};
+use std::convert::TryFrom;
+use std::fs;
 use std::fs::File;
 use std::io::prelude::*;
+use std::process;
 
 mod arguments;
-use arguments::Arguments;
+use arguments::{
+    Arguments, DoctorArguments, FuzzbotArguments, GenmapArguments, MirrorArguments,
+    PlaybackArguments, ReplayAnimateArguments, ReplayAuditArguments, ReplayCastArguments,
+    ReplayCutArguments, TournamentArguments,
+};
 
 fn main() {
     let args = Arguments::new();
 
+    if let Some(fuzz_args) = args.fuzzbot() {
+        fuzzbot(fuzz_args);
+        return;
+    }
+    if let Some(mirror_args) = args.mirror() {
+        mirror(mirror_args);
+        return;
+    }
+    if let Some(tournament_args) = args.tournament() {
+        tournament(tournament_args);
+        return;
+    }
+    if let Some(doctor_args) = args.doctor() {
+        doctor(doctor_args);
+        return;
+    }
+    if let Some(genmap_args) = args.genmap() {
+        genmap(genmap_args);
+        return;
+    }
+    if let Some(cut_args) = args.replay_cut() {
+        replay_cut(cut_args);
+        return;
+    }
+    if let Some(cast_args) = args.replay_cast() {
+        replay_cast(cast_args);
+        return;
+    }
+    if let Some(animate_args) = args.replay_animate() {
+        replay_animate(animate_args);
+        return;
+    }
+    if let Some(audit_args) = args.replay_audit() {
+        replay_audit(audit_args);
+        return;
+    }
+    if let Some(replay_path) = args.replay_verify() {
+        replay_verify(replay_path);
+        return;
+    }
+    if let Some(playback_args) = args.playback() {
+        playback(playback_args);
+        return;
+    }
+
+    if args.selfcheck() {
+        if let Err(e) = Engine::selfcheck() {
+            eprintln!("Self-check failed: {}", e);
+            process::exit(1);
+        }
+    }
+
     let (player1, player2) = args.player_paths();
+    let names = [player1, player2.unwrap_or(native_bot::RANDOM)];
 
-    let mut builder = Engine::builder(player1);
-    if let Some(player2_) = player2 {
-        builder.with_player2(player2_);
+    let mut problems = vec![];
+    let registry = load_registry(args.bots_path(), &mut problems);
+    let entries: Vec<Option<&BotEntry>> = names.iter().map(|name| registry.get(name)).collect();
+    let paths: Vec<&str> = names
+        .iter()
+        .zip(entries.iter())
+        .map(|(name, entry)| entry.map_or(*name, |entry| entry.path.as_str()))
+        .collect();
+    let mut interpreters = Interpreters::default();
+    registry.configure_interpreters(&mut interpreters);
+    for (extension, command) in args.interpreters() {
+        interpreters.set(&extension, command);
+    }
+    for (name, entry) in names.iter().zip(entries.iter()) {
+        let path = Path::new(name);
+        if entry.is_some() || !BotKind::of(name).is_process() {
+            continue;
+        }
+        if !path.exists() {
+            problems.push(format!(
+                "Bot {} is neither a file nor in the bot registry",
+                name
+            ));
+        } else if let Some(extension) = path.extension().filter(|ex| *ex != "filler") {
+            if interpreters.command_for(path).is_none() {
+                problems.push(format!(
+                    "Bot {}: no interpreter for .{} files, see --interpreter",
+                    name,
+                    extension.to_string_lossy()
+                ));
+            }
+        }
+    }
+
+    let mut builder = Engine::builder(paths[0]);
+    builder.with_player2(paths[1]);
+    if let Some(rules_path) = args.rules_path() {
+        match fs::read_to_string(rules_path)
+            .map_err(|e| e.to_string())
+            .and_then(|json| RuleSet::from_json(&json))
+        {
+            Ok(rules) => {
+                builder.with_rules(rules);
+            }
+            Err(e) => problems.push(format!("Rules {}: {}", rules_path, e)),
+        }
     }
 
     if args.verbose() {
         builder.verbose();
     }
+    let seats = [Player::Player1, Player::Player2];
+    for (player, color) in seats.iter().zip(args.colors()) {
+        builder.with_color(*player, color);
+    }
+    if let Some((path, every)) = args.html_status() {
+        let mut html_status = HtmlStatus::new(PathBuf::from(path), every);
+        for (player, color) in seats.iter().zip(args.colors()) {
+            html_status = html_status.with_color(*player, color);
+        }
+        builder.add_on_player_response(Box::new(html_status));
+    }
+    if let Some(path) = args.json_log() {
+        match JsonLog::create(Path::new(path)) {
+            Ok(json_log) => {
+                builder.add_on_player_response(Box::new(json_log));
+            }
+            Err(e) => problems.push(format!("JSON log {}: {}", path, e)),
+        }
+    }
+    // Clap refuses both at once, a game has a single scoring
+    if let Some(scoring) = args.recency_scoring() {
+        builder.with_scoring(Box::new(scoring));
+    } else if let Some(scoring) = args.hill_scoring() {
+        builder.with_scoring(Box::new(scoring));
+    }
+    if let Some(interval) = args.min_move_interval() {
+        builder.with_min_move_interval(interval);
+    }
 
-    let mut filler = builder.finish();
+    if let Some(rule) = args.overlap_rule() {
+        builder.with_overlap_rule(rule);
+    }
+
+    if args.blind_start() {
+        builder.blind_start();
+    }
 
-    filler.run();
+    if args.mirror_pieces() {
+        builder.mirror_pieces();
+    }
 
+    for (player, adapter) in seats.iter().zip(args.adapters()) {
+        builder.with_adapter(*player, adapter);
+    }
+
+    builder.with_sandbox(Sandbox {
+        isolate_network: args.no_network(),
+        read_only: args.read_only(),
+        interpreters,
+        ..Sandbox::default()
+    });
+    for ((player, name), entry) in seats.iter().zip(names.iter()).zip(entries.iter()) {
+        if let Some(entry) = entry {
+            entry.configure(builder.sandbox_mut(*player));
+            builder.with_display_name(*player, name);
+        }
+    }
+    for (player, cwd) in seats.iter().zip(args.bot_cwds()) {
+        builder.sandbox_mut(*player).cwd = Some(PathBuf::from(cwd));
+    }
+
+    if let Some((path, every)) = args.checkpoint() {
+        builder.with_checkpoint(PathBuf::from(path), every);
+    }
+
+    for (key, value) in args.metadata() {
+        builder.with_metadata(key, value);
+    }
+
+    let tie_breaks = args.tie_breaks();
+    if !tie_breaks.is_empty() {
+        builder.with_tie_breaks(tie_breaks);
+    }
+    for (player, latency) in seats.iter().zip(args.latencies()) {
+        builder.with_latency(*player, latency);
+    }
+    for (player, fog) in seats.iter().zip(args.fog()) {
+        if let Some(radius) = fog {
+            builder.with_fog(*player, radius);
+        }
+    }
+
+    if let Some(map_path) = args.map_path() {
+        match load_map(map_path) {
+            Ok(plateau) => {
+                builder.with_plateau(plateau);
+            }
+            Err(e) => problems.push(format!("Map {}: {}", map_path, e)),
+        }
+    }
+    // The layout moves the map's start points, a position brings its own
+    if let Some(layout) = args.start_layout() {
+        if let Err(e) = builder.with_start_layout(&layout) {
+            problems.push(format!("Start layout: {}", e));
+        }
+    }
+    if let Some(seed) = args.seed() {
+        builder.with_piecebag(PieceBag::with_seed(seed, RANGE_DEFAULT, RANGE_DEFAULT));
+    }
+    if let Some(position_path) = args.position_path() {
+        match load_position(position_path) {
+            Ok(position) => {
+                builder.with_position(&position);
+            }
+            Err(e) => problems.push(format!("Position {}: {}", position_path, e)),
+        }
+    }
+    if let Some(player) = args.to_move() {
+        builder.first_to_move(player);
+    }
+    if let Some(state_path) = args.resume_path() {
+        match GameState::load(Path::new(state_path)) {
+            Ok(state) => {
+                builder.resume(state);
+            }
+            Err(e) => problems.push(format!("Checkpoint {}", e)),
+        }
+    }
+
+    if args.dry_run() {
+        problems.extend(builder.preflight());
+        for problem in problems.iter() {
+            eprintln!("{}", problem);
+        }
+        match problems.is_empty() {
+            true => println!("Configuration OK"),
+            false => process::exit(1),
+        }
+        return;
+    }
+    if let Some(problem) = problems.first() {
+        eprintln!("{}", problem);
+        process::exit(1);
+    }
+
+    let mut filler = builder.finish().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    });
+
+    let result = filler.run();
+
+    if args.report() {
+        for report in filler.bot_reports() {
+            print!("{}", report);
+        }
+    }
     if let Some(json_dir) = args.json_path() {
         write_replay(json_dir, &filler);
     }
+    if args.outcome_exit_code() {
+        process::exit(result.outcome.exit_code());
+    }
 }
 
 use chrono::prelude::*;
 
+fn fuzzbot(args: FuzzbotArguments) {
+    let seed = args.seed.unwrap_or_else(|| Utc::now().timestamp() as u64);
+    println!("Fuzzing {} with seed {}", args.bot, seed);
+
+    let report = match fuzz::fuzz_bot(&args.bot, args.rounds, seed) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    };
+    print!("{}", report);
+    if !report.is_clean() {
+        process::exit(1);
+    }
+}
+
+fn mirror(args: MirrorArguments) {
+    let seed = args.seed.unwrap_or_else(|| Utc::now().timestamp() as u64);
+    let plateau = match args.map.as_deref().map(load_map) {
+        Some(Ok(plateau)) => plateau,
+        Some(Err(e)) => {
+            eprintln!("Map {}: {}", args.map.unwrap(), e);
+            process::exit(1);
+        }
+        None => Plateau::default(),
+    };
+    println!("Mirror match of {} with seed {}", args.bot, seed);
+
+    let report = mirror_match::mirror_match(&args.bot, &plateau, seed).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    });
+    print!("{}", report);
+    if !report.is_clean() {
+        process::exit(1);
+    }
+}
+
+fn tournament(args: TournamentArguments) {
+    let mut boards = vec![];
+    for map in args.maps.iter() {
+        match load_map(map) {
+            Ok(plateau) => boards.push(plateau),
+            Err(e) => {
+                eprintln!("Map {}: {}", map, e);
+                process::exit(1);
+            }
+        }
+    }
+    let mut tournament = Tournament::new(args.bots);
+    if !boards.is_empty() {
+        tournament.with_boards(boards);
+    }
+    if args.both_colors {
+        tournament.both_colors();
+    }
+    if let Some(seed) = args.seed {
+        tournament.with_seed(seed);
+    }
+    tournament.with_threads(args.threads);
+    println!("Tournament with seed {}", tournament.seed());
+
+    let store = args.store.as_deref().map(storage::open).transpose();
+    let standings = store.and_then(|store| match store {
+        Some(mut store) => tournament.run_into(&mut *store),
+        None => Ok(tournament.run()),
+    });
+    let standings = standings.unwrap_or_else(|e| {
+        eprintln!("Storage: {}", e);
+        process::exit(1);
+    });
+    print!("{}", standings);
+    if let Some(path) = args.output {
+        if let Err(e) = fs::write(&path, standings.to_json()) {
+            eprintln!("{}: {}", path, e);
+            process::exit(1);
+        }
+    }
+}
+
+fn doctor(args: DoctorArguments) {
+    let mut problems = vec![];
+    let registry = load_registry(None, &mut problems);
+    let mut findings: Vec<doctor::Finding> = problems
+        .into_iter()
+        .map(|problem| doctor::Finding {
+            problem,
+            fix: format!("fix or remove {}", bot_registry::DEFAULT_REGISTRY),
+        })
+        .collect();
+    let mut interpreters = Interpreters::default();
+    registry.configure_interpreters(&mut interpreters);
+
+    let mut paths: Vec<&str> = registry
+        .names()
+        .map(|name| registry.get(name).unwrap().path.as_str())
+        .collect();
+    paths.extend(args.bots.iter().map(|name| match registry.get(name) {
+        Some(entry) => entry.path.as_str(),
+        None => name.as_str(),
+    }));
+    findings.extend(
+        paths
+            .iter()
+            .filter_map(|path| doctor::check_bot(path, &interpreters)),
+    );
+    findings.extend(doctor::check_replay_dir(Path::new(&args.replay_dir)));
+
+    println!(
+        "Checked {} bots and replay directory {}",
+        paths.len(),
+        args.replay_dir
+    );
+    if findings.is_empty() {
+        println!("No problems found");
+        return;
+    }
+    for finding in findings.iter() {
+        println!("{}", finding);
+    }
+    process::exit(1);
+}
+
+fn genmap(args: GenmapArguments) {
+    let seed = args.seed.unwrap_or_else(|| Utc::now().timestamp() as u64);
+    let plateau = match maps::generate(args.width, args.height, args.obstacles, seed) {
+        Ok(plateau) => plateau,
+        Err(e) => {
+            eprintln!("Map generation failed: {}", e);
+            process::exit(1);
+        }
+    };
+    println!("Map with seed {}", seed);
+    print!("{}", plateau.render_human());
+
+    if let Err(e) = eval::check_fairness(plateau.view()) {
+        eprintln!("Unfair map: {}", e);
+        process::exit(1);
+    }
+    if let Some(path) = args.output {
+        let mut map = plateau.to_map_rows().join("\n");
+        map.push('\n');
+        if let Err(e) = fs::write(&path, map) {
+            eprintln!("{}: {}", path, e);
+            process::exit(1);
+        }
+    }
+}
+
+fn playback(args: PlaybackArguments) {
+    let result = fs::read_to_string(&args.replay)
+        .map_err(|e| e.to_string())
+        .and_then(|replay| playback::play_back(&replay, args.speed, &mut std::io::stdout()));
+    if let Err(e) = result {
+        eprintln!("{}: {}", args.replay, e);
+        process::exit(1);
+    }
+}
+
+fn replay_cut(args: ReplayCutArguments) {
+    let slice = fs::read_to_string(&args.replay)
+        .map_err(|e| e.to_string())
+        .and_then(|replay| Replay::from_json(&replay))
+        .and_then(|replay| replay.slice(args.from..args.to));
+    let slice = match slice {
+        Ok(slice) => slice.to_json(),
+        Err(e) => {
+            eprintln!("{}: {}", args.replay, e);
+            process::exit(1);
+        }
+    };
+    match args.output {
+        Some(path) => {
+            if let Err(e) = fs::write(&path, slice) {
+                eprintln!("{}: {}", path, e);
+                process::exit(1);
+            }
+        }
+        None => println!("{}", slice),
+    }
+}
+
+fn replay_cast(args: ReplayCastArguments) {
+    let cast = fs::read_to_string(&args.replay)
+        .map_err(|e| e.to_string())
+        .and_then(|replay| Replay::from_json(&replay))
+        .and_then(|replay| asciicast::to_cast(replay, args.speed));
+    let cast = match cast {
+        Ok(cast) => cast,
+        Err(e) => {
+            eprintln!("{}: {}", args.replay, e);
+            process::exit(1);
+        }
+    };
+    match args.output {
+        Some(path) => {
+            if let Err(e) = fs::write(&path, cast) {
+                eprintln!("{}: {}", path, e);
+                process::exit(1);
+            }
+        }
+        None => print!("{}", cast),
+    }
+}
+
+fn replay_animate(args: ReplayAnimateArguments) {
+    let result = fs::read_to_string(&args.replay)
+        .map_err(|e| e.to_string())
+        .and_then(|replay| Replay::from_json(&replay))
+        .and_then(|replay| animate::animate(replay, args.pacing, &mut std::io::stdout()));
+    if let Err(e) = result {
+        eprintln!("{}: {}", args.replay, e);
+        process::exit(1);
+    }
+}
+
+fn replay_audit(args: ReplayAuditArguments) {
+    let report = fs::read_to_string(&args.replay)
+        .map_err(|e| e.to_string())
+        .and_then(|replay| Replay::from_json(&replay))
+        .and_then(|replay| audit::audit_bot(&args.bot, &replay, args.player));
+    let report = match report {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("{}: {}", args.replay, e);
+            process::exit(1);
+        }
+    };
+    print!("{}", report);
+    if !report.is_clean() {
+        process::exit(1);
+    }
+}
+
+fn replay_verify(path: &str) {
+    let divergence = fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|replay| Replay::from_json(&replay))
+        .and_then(|replay| replay.first_divergence());
+    match divergence {
+        Ok(None) => println!("{}: OK", path),
+        Ok(Some(move_number)) => {
+            println!("{}: diverges at move {}", path, move_number);
+            process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("{}: {}", path, e);
+            process::exit(1);
+        }
+    }
+}
+
+fn load_position(path: &str) -> Result<Position, String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    match text.trim_start().starts_with('{') {
+        true => Position::from_replay(&Replay::from_json(&text)?),
+        false => Position::parse(&text),
+    }
+}
+
+/// The registry at `path`, or at the default path if there is a file there
+fn load_registry(path: Option<&str>, problems: &mut Vec<String>) -> BotRegistry {
+    let path = match path {
+        Some(path) => Path::new(path),
+        None if Path::new(bot_registry::DEFAULT_REGISTRY).is_file() => {
+            Path::new(bot_registry::DEFAULT_REGISTRY)
+        }
+        None => return BotRegistry::default(),
+    };
+    BotRegistry::load(path).unwrap_or_else(|e| {
+        problems.push(format!("Bot registry {}: {}", path.display(), e));
+        BotRegistry::default()
+    })
+}
+
+fn load_map(path: &str) -> Result<Plateau, String> {
+    if let Some(plateau) = maps::get(path) {
+        return Ok(plateau);
+    }
+    let map = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    Plateau::try_from(map)
+}
+
 fn write_replay(file_dir: &str, filler_engine: &Engine) {
     let player_names = filler_engine.player_names();
     let mut filename = format!("{}_", Utc::now().timestamp());
 
-    filename = filename + player_names[0].split('.').next().unwrap();
+    filename += player_names[0].split('.').next().unwrap();
     if let Some(player_name) = player_names.get(1) {
-        filename = filename + "_vs_";
-        filename = filename + player_name.split('.').next().unwrap();
+        filename += "_vs_";
+        filename += player_name.split('.').next().unwrap();
     }
 
     let path = Path::new(file_dir).join(filename).with_extension("json");
     let mut file = match File::create(&path) {
         //TODO: Better error handling
-        Err(why) => panic!("couldn't open {}: {}", path.display(), why.description()),
+        Err(why) => panic!("couldn't open {}: {}", path.display(), why),
         Ok(file) => file,
     };
 
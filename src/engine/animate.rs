@@ -0,0 +1,85 @@
+use super::asciicast::status;
+use super::render::Frame;
+use super::{Replay, ReplayCursor};
+use std::io::Write;
+use std::thread;
+use std::time::Duration;
+
+/// How long each move of an animation stays on screen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pacing {
+    /// A fixed number of moves per second
+    Fps(f64),
+    /// The time each move originally took, divided by `speed`
+    Realtime { speed: f64 },
+}
+
+/// Animates a replay in a terminal, redrawing the coloured board in place
+/// for every move
+pub fn animate<W: Write>(replay: Replay, pacing: Pacing, out: &mut W) -> Result<(), String> {
+    let mut cursor = ReplayCursor::new(replay)?;
+    let frame = Frame::draw(cursor.plateau().view());
+    draw(out, &frame.to_screen("Start"))?;
+
+    let mut previous = cursor.replay().started_at;
+    while cursor.step_forward()?.is_some() {
+        let move_number = cursor.position() - 1;
+        let response = cursor.last_move().unwrap();
+        let pause = match pacing {
+            Pacing::Fps(fps) => Duration::from_secs_f64(1.0 / fps),
+            Pacing::Realtime { .. } if response.timestamp == 0 => Duration::default(),
+            Pacing::Realtime { speed } => {
+                let elapsed = (response.timestamp - previous).max(0) as u64;
+                Duration::from_millis(elapsed).div_f64(speed)
+            }
+        };
+        previous = response.timestamp.max(previous);
+        thread::sleep(pause);
+
+        let frame = Frame::draw(cursor.plateau().view());
+        draw(out, &frame.to_screen(&status(move_number, response)))?;
+    }
+
+    let replay = cursor.replay();
+    if let Some(termination) = replay.termination {
+        writeln!(out, "Game over: {}", termination).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn draw<W: Write>(out: &mut W, frame: &str) -> Result<(), String> {
+    out.write_all(frame.as_bytes())
+        .and_then(|_| out.flush())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redraws_the_board_for_every_move() {
+        let replay = Replay::from_json(
+            r#"{
+            "started_at": 1000,
+            "players": ["a", "b"],
+            "plateau": {"width": 3, "height": 2,
+                "player1_start": {"x": 0, "y": 0}, "player2_start": {"x": 2, "y": 1}},
+            "history": [
+                {"player": "Player1", "piece": {"width": 2, "height": 1, "cells": [1, 1]},
+                 "raw_response": "0 0\n", "placement": {"x": 0, "y": 0}, "placement_count": 1,
+                 "error": null, "timestamp": 1010}
+            ],
+            "termination": "ErrorThreshold"
+        }"#,
+        )
+        .unwrap();
+        let mut out = vec![];
+        animate(replay, Pacing::Realtime { speed: 10.0 }, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert_eq!(out.matches("\x1b[H\x1b[2J").count(), 2);
+        assert!(out.contains("Move 0: <O>"));
+        assert!(out.ends_with("Game over: error threshold reached\n"));
+    }
+}
@@ -0,0 +1,96 @@
+use super::render::Frame;
+use super::{PlayerResponse, Replay, ReplayCursor};
+use serde_json::json;
+
+/// Pause before moves without a recorded time, in seconds
+const DEFAULT_PAUSE: f64 = 0.1;
+/// Longest pause kept in a cast, so a slow bot does not stall the recording
+const MAX_PAUSE: f64 = 2.0;
+
+/// Converts a replay to an asciinema v2 cast of the board drawn in colour,
+/// one frame per move. Pauses follow the recorded move times divided by
+/// `speed`; with a `speed` of 0 every move gets the same short pause.
+pub fn to_cast(replay: Replay, speed: f64) -> Result<String, String> {
+    let mut cursor = ReplayCursor::new(replay)?;
+    let frame = Frame::draw(cursor.plateau().view());
+    let board = frame.to_text();
+    let width = board.lines().map(str::len).max().unwrap_or(0).max(40);
+    let height = board.lines().count() + 1;
+
+    let header = json!({
+        "version": 2,
+        "width": width,
+        "height": height,
+        "timestamp": cursor.replay().started_at / 1000,
+        "title": cursor.replay().players.join(" vs "),
+    });
+    let mut cast = format!("{}\n", header);
+    let mut time = 0.0;
+    cast += &event(time, &frame.to_screen("Start"));
+
+    let mut previous = cursor.replay().started_at;
+    while cursor.step_forward()?.is_some() {
+        let move_number = cursor.position() - 1;
+        let response = cursor.last_move().unwrap();
+        let pause = match (response.timestamp, speed > 0.0) {
+            (0, _) | (_, false) => DEFAULT_PAUSE,
+            (timestamp, true) => (timestamp - previous).max(0) as f64 / 1000.0 / speed,
+        };
+        previous = response.timestamp.max(previous);
+        time += pause.min(MAX_PAUSE);
+
+        let frame = Frame::draw(cursor.plateau().view());
+        cast += &event(time, &frame.to_screen(&status(move_number, response)));
+    }
+    Ok(cast)
+}
+
+fn event(time: f64, output: &str) -> String {
+    format!(
+        "{}\n",
+        json!([(time * 1000.0).round() / 1000.0, "o", output])
+    )
+}
+
+/// The line shown over the board after a move
+pub(crate) fn status(move_number: usize, response: &PlayerResponse) -> String {
+    match &response.error {
+        Some(error) => format!("Move {}: <{}> {}", move_number, response.player, error),
+        None => format!("Move {}: <{}>", move_number, response.player),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_a_header_and_a_frame_per_move() {
+        let replay = Replay::from_json(
+            r#"{
+            "started_at": 1000,
+            "players": ["a", "b"],
+            "plateau": {"width": 3, "height": 2,
+                "player1_start": {"x": 0, "y": 0}, "player2_start": {"x": 2, "y": 1}},
+            "history": [
+                {"player": "Player1", "piece": {"width": 2, "height": 1, "cells": [1, 1]},
+                 "raw_response": "0 0\n", "placement": {"x": 0, "y": 0}, "placement_count": 1,
+                 "error": null, "timestamp": 1500}
+            ],
+            "termination": null
+        }"#,
+        )
+        .unwrap();
+        let cast = to_cast(replay, 1.0).unwrap();
+        let lines: Vec<serde_json::Value> = cast
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["version"], 2);
+        assert_eq!(lines[0]["title"], "a vs b");
+        assert_eq!(lines[2][0], 0.5);
+        assert!(lines[2][2].as_str().unwrap().contains("\x1b[91;1mo\x1b[0m"));
+    }
+}
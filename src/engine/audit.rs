@@ -0,0 +1,117 @@
+use super::{prelude, Bot, Prelude, Replay, Sandbox};
+use crate::models::Player;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Time the audited bot gets for each move
+const AUDIT_TIMEOUT: Duration = Duration::from_secs(2);
+/// A recorded move this many times faster than the rerun is flagged
+const TIMING_RATIO: i64 = 10;
+/// Reruns quicker than this, in milliseconds, are too noisy to compare
+const MIN_COMPARABLE_MS: i64 = 5;
+/// Number of findings described in a report
+const MAX_SAMPLES: usize = 10;
+
+/// Outcome of replaying a player's recorded turns to a fresh instance of
+/// its bot
+#[derive(Debug, Default)]
+pub struct AuditReport {
+    /// Turns of the audited player that were replayed
+    pub moves: usize,
+    /// Moves the rerun answered differently
+    pub divergent: Vec<usize>,
+    /// Moves recorded much faster than the rerun could answer them
+    pub implausible_timing: Vec<usize>,
+    pub samples: Vec<String>,
+}
+
+impl AuditReport {
+    pub fn is_clean(&self) -> bool {
+        self.divergent.is_empty() && self.implausible_timing.is_empty()
+    }
+
+    fn sample(&mut self, description: String) {
+        if self.samples.len() < MAX_SAMPLES {
+            self.samples.push(description);
+        }
+    }
+}
+
+impl fmt::Display for AuditReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Moves replayed:     {}", self.moves)?;
+        writeln!(f, "Divergent answers:  {}", self.divergent.len())?;
+        writeln!(f, "Implausible timing: {}", self.implausible_timing.len())?;
+        for sample in self.samples.iter() {
+            writeln!(f, "  {}", sample)?;
+        }
+        Ok(())
+    }
+}
+
+/// Sends the bot at `path` every frame `player` was sent in `replay` and
+/// compares its answers with the recorded ones, so a deterministic bot
+/// should answer identically. Moves recorded in a fraction of the time the
+/// rerun needs are flagged too, as they hint at answers sent before the
+/// board was read. A bot that crashes is not restarted, as it was not in
+/// the original game either. Fog and adapters of the original game are not
+/// applied.
+pub fn audit_bot(path: &str, replay: &Replay, player: Player) -> Result<AuditReport, String> {
+    let mut report = AuditReport::default();
+    let name = prelude::default_name(path);
+    let opponent = replay
+        .players
+        .get(1 - player as usize)
+        .cloned()
+        .unwrap_or_default();
+    let prelude = Prelude::default().render(player, path, &name, &opponent);
+    let mut bot = Bot::new(
+        path,
+        name,
+        AUDIT_TIMEOUT,
+        player,
+        prelude,
+        Sandbox::default(),
+    )?;
+
+    let mut plateau = replay.initial_plateau()?;
+    let mut previous = replay.started_at;
+    for (move_number, recorded) in replay.history.iter().enumerate() {
+        let recorded_ms = match (recorded.timestamp, previous) {
+            (0, _) | (_, 0) => None,
+            (timestamp, previous) => Some(timestamp - previous),
+        };
+        previous = recorded.timestamp;
+
+        if recorded.player == player {
+            report.moves += 1;
+            let mut pieces = vec![recorded.piece.clone()];
+            pieces.extend(recorded.batch.iter().cloned());
+
+            let started = Instant::now();
+            let rerun = bot.request_placement(&mut plateau.clone(), &pieces, move_number, &mut []);
+            let rerun_ms = started.elapsed().as_millis() as i64;
+
+            if rerun.raw_response != recorded.raw_response {
+                report.divergent.push(move_number);
+                report.sample(format!(
+                    "move {}: answered {:?}, recorded {:?}",
+                    move_number, rerun.raw_response, recorded.raw_response
+                ));
+            }
+            if let Some(recorded_ms) = recorded_ms {
+                if rerun_ms >= MIN_COMPARABLE_MS && recorded_ms * TIMING_RATIO < rerun_ms {
+                    report.implausible_timing.push(move_number);
+                    report.sample(format!(
+                        "move {}: recorded in {}ms, rerun took {}ms",
+                        move_number, recorded_ms, rerun_ms
+                    ));
+                }
+            }
+        }
+        replay.apply(&mut plateau, move_number, recorded)?;
+    }
+    bot.end_game(&[], false);
+
+    Ok(report)
+}
@@ -1,164 +1,937 @@
 use std::convert::TryFrom;
-use std::io::{BufRead, BufReader, Write};
-use std::path::Path;
-use std::process::{Command, Stdio};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::mem;
+use std::net::{Shutdown, TcpStream};
+use std::process::{Child, Stdio};
+use std::sync::atomic::Ordering;
 use std::sync::mpsc;
-use std::sync::mpsc::{Receiver, Sender};
-use std::thread;
-use std::time::Duration;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
-use super::PlayerResponse;
-use crate::models::{Piece, Plateau, Player, Point};
+use super::bot_report::ProcessStats;
+use super::native_bot::{self, BotKind, NativeBot};
+use super::tcp_bot;
+use super::{
+    json_frame, resources, BotAdapter, BotReport, Latency, PlayerResponse, Referee, Rejection,
+    Sandbox, Verdict,
+};
+use crate::models::{OverlapRule, Piece, Plateau, Player, Point};
 
 pub type ComError = String;
+/// How long a bot may take to exit on its own after its stdin is closed
+const SHUTDOWN_GRACE: Duration = Duration::from_millis(500);
+
+/// The engine's ends of a bot's I/O thread
+type Channels = (
+    Sender<Frame>,
+    Receiver<String>,
+    JoinHandle<()>,
+    Arc<ProcessStats>,
+    Link,
+);
+
+/// What the engine can cut to wake a bot's I/O thread that is stuck waiting
+/// for a bot that stopped answering
+enum Link {
+    /// The bot's process, once the I/O thread has started it
+    Process(Arc<Mutex<Option<Child>>>),
+    /// The bot's current connection, replaced when it reconnects and taken
+    /// once cut so the bot is not waited on any longer
+    Socket(Arc<Mutex<Option<TcpStream>>>),
+    Builtin,
+}
+
+impl Link {
+    fn cut(&self) {
+        match self {
+            Link::Process(child) => {
+                if let Some(child) = child.lock().unwrap().as_mut() {
+                    let _ = child.kill();
+                }
+            }
+            Link::Socket(stream) => {
+                if let Some(stream) = stream.lock().unwrap().take() {
+                    let _ = stream.shutdown(Shutdown::Both);
+                }
+            }
+            Link::Builtin => {}
+        }
+    }
+}
+
+/// The I/O thread's end of a bot connected over TCP
+struct Remote {
+    path: String,
+    address: String,
+    prelude: String,
+    link: Arc<Mutex<Option<TcpStream>>>,
+    stats: Arc<ProcessStats>,
+}
+
+impl Remote {
+    /// Plays the bot over `stream`. A bot whose connection drops may connect
+    /// again within `tcp_bot::RECONNECT_WINDOW`, up to
+    /// `tcp_bot::MAX_RECONNECTS` times, and is sent its prelude and the frame
+    /// the engine is waiting on again. Otherwise the bot is gone for good.
+    fn run(self, mut stream: TcpStream, frames: Receiver<Frame>, replies: Sender<String>) {
+        let mut unanswered = None;
+        let mut reconnects = 0;
+        'connection: loop {
+            let outcome = match stream.try_clone() {
+                Ok(bot_out) if stream.write_all(self.prelude.as_bytes()).is_ok() => {
+                    let mut bot_out = BufReader::new(bot_out);
+                    let resend = unanswered.take();
+                    Bot::exchange(&mut stream, &mut bot_out, &frames, &replies, resend)
+                        .map_err(Some)
+                }
+                _ => Err(unanswered.take()),
+            };
+            let _ = stream.shutdown(Shutdown::Both);
+            let mut waiting = match outcome {
+                Ok(()) => break,
+                Err(waiting) => waiting,
+            };
+            if reconnects == tcp_bot::MAX_RECONNECTS {
+                eprintln!("Bot {}: disconnected", self.path);
+                break;
+            }
+
+            eprintln!(
+                "Bot {}: disconnected, waiting for it to reconnect",
+                self.path
+            );
+            // The engine cuts the link once it no longer waits on the bot
+            let abandoned = || self.link.lock().unwrap().is_none();
+            stream = match tcp_bot::reconnect(&self.address, abandoned) {
+                Ok(stream) => stream,
+                Err(e) => {
+                    eprintln!("Bot {}: {}", self.path, e);
+                    break;
+                }
+            };
+            match self.link.lock().unwrap().as_mut() {
+                Some(link) => match stream.try_clone() {
+                    Ok(clone) => *link = clone,
+                    Err(_) => break,
+                },
+                None => break,
+            }
+            reconnects += 1;
+            self.stats.reconnects.fetch_add(1, Ordering::Relaxed);
+            eprintln!("Bot {}: reconnected", self.path);
+
+            // Frames sent while the bot was away have timed out, only the
+            // latest is still waited on
+            loop {
+                match frames.try_recv() {
+                    Ok(Frame::Message(message, expected_lines)) => {
+                        waiting = Some((message, expected_lines, 0))
+                    }
+                    Ok(Frame::Rules(_)) => {}
+                    Ok(Frame::Close(message)) => {
+                        let _ = stream.write_all(message.as_bytes());
+                        break 'connection;
+                    }
+                    Err(TryRecvError::Disconnected) => break 'connection,
+                    Err(TryRecvError::Empty) => break,
+                }
+            }
+            unanswered = waiting;
+        }
+        let _ = stream.shutdown(Shutdown::Both);
+        *self.stats.exit_status.lock().unwrap() = Some(String::from("disconnected"));
+    }
+}
+
+enum Frame {
+    /// A message for the bot and the number of lines expected in reply
+    Message(String, usize),
+    /// A final message, after which the bot's stdin is closed
+    Close(String),
+    /// The game's overlap rule, only built-in bots are told it
+    Rules(OverlapRule),
+}
+
+/// A frame the bot went away before answering: the message, the number of
+/// lines expected in reply and how many of those were already passed on
+type Unanswered = (String, usize, usize);
 
 pub struct Bot<'a> {
     path: &'a str,
+    name: String,
     player: Player,
     placement_count: usize,
-    sender: Sender<std::string::String>,
-    receiver: Receiver<std::string::String>,
-    timeout: usize,
+    sender: Sender<Frame>,
+    receiver: Receiver<String>,
+    handle: Option<JoinHandle<()>>,
+    timeout: Duration,
+    /// Remaining thinking time over the whole game, if the bot is on a clock
+    clock: Option<Duration>,
+    total_clock: Option<Duration>,
+    clock_header: bool,
+    prelude: String,
+    crashed: bool,
+    /// Lines still owed for frames that timed out, dropped when they arrive
+    /// so they are not read as answers to a later frame
+    stale_lines: usize,
+    adapter: BotAdapter,
+    /// How far around its own cells the bot can see, if it is handicapped
+    fog: Option<usize>,
+    /// Turns are sent as JSON lines, see `json_frame`
+    json_frames: bool,
+    sandbox: Sandbox,
+    /// Delay added before every frame, see `Latency`
+    latency: Option<Latency>,
+    overlap: OverlapRule,
+    report: BotReport,
+    process_stats: Arc<ProcessStats>,
+    link: Link,
 }
 
 impl<'a> Bot<'a> {
-    pub fn new(path: &str, timeout: usize, player: Player) -> Result<Bot, ComError> {
-        let (sender, receiver) = Bot::spawn_player(path, player)?;
+    /// Spawns the bot under `sandbox` and sends it `prelude` as its first line
+    pub fn new(
+        path: &'a str,
+        name: String,
+        timeout: Duration,
+        player: Player,
+        prelude: String,
+        sandbox: Sandbox,
+    ) -> Result<Bot<'a>, ComError> {
+        let (sender, receiver, handle, process_stats, link) =
+            Bot::spawn_player(path, player, prelude.clone(), &sandbox)?;
+        let report = BotReport::new(player, name.clone());
         Ok(Bot {
             path,
+            name,
             player,
             sender,
             receiver,
+            handle: Some(handle),
             timeout,
             placement_count: 0,
+            clock: None,
+            total_clock: None,
+            clock_header: false,
+            prelude,
+            crashed: false,
+            stale_lines: 0,
+            adapter: BotAdapter::default(),
+            fog: None,
+            json_frames: false,
+            sandbox,
+            latency: None,
+            overlap: OverlapRule::default(),
+            report,
+            process_stats,
+            link,
         })
     }
 
+    /// Puts the bot on a game clock that every turn's thinking time is
+    /// deducted from
+    pub fn set_total_clock(&mut self, clock: Duration) {
+        self.clock = Some(clock);
+        self.total_clock = Some(clock);
+    }
+
+    pub fn set_fog(&mut self, radius: Option<usize>) {
+        self.fog = radius;
+    }
+
+    pub fn set_latency(&mut self, latency: Option<Latency>) {
+        self.latency = latency;
+    }
+
+    /// Tells a built-in bot which placements the engine accepts
+    pub fn set_overlap_rule(&mut self, rule: OverlapRule) {
+        self.overlap = rule;
+        let _ = self.sender.send(Frame::Rules(rule));
+    }
+
+    pub fn set_adapter(&mut self, adapter: BotAdapter) {
+        self.adapter = adapter;
+        self.json_frames = adapter.json_frames;
+    }
+
+    /// Prepares the bot for a new game. A persistent bot keeps its process and
+    /// is sent a `$$$ reset` line followed by its prelude, otherwise (or if the
+    /// bot has crashed) the process is replaced by a fresh one.
+    pub fn reset(&mut self, persistent: bool) -> Result<(), ComError> {
+        self.placement_count = 0;
+        self.clock = self.total_clock;
+        self.report = BotReport::new(self.player, self.name.clone());
+
+        let frame = format!("$$$ reset\n{}", self.prelude);
+        if persistent && !self.crashed && self.send(frame, 0).is_ok() {
+            self.process_stats.stderr_bytes.store(0, Ordering::Relaxed);
+            self.process_stats.reconnects.store(0, Ordering::Relaxed);
+            return Ok(());
+        }
+
+        let (sender, receiver, handle, process_stats, link) =
+            Bot::spawn_player(self.path, self.player, self.prelude.clone(), &self.sandbox)?;
+        self.sender = sender;
+        self.receiver = receiver;
+        self.process_stats = process_stats;
+        // The previous bot may have hung mid-turn, it is killed rather than
+        // left running with its I/O thread stuck waiting on it
+        mem::replace(&mut self.link, link).cut();
+        if let Some(handle) = self.handle.replace(handle) {
+            let _ = handle.join();
+        }
+        self.crashed = false;
+        self.stale_lines = 0;
+        self.json_frames = self.adapter.json_frames;
+        self.set_overlap_rule(self.overlap);
+        Ok(())
+    }
+
+    /// Prefixes each frame with a `$$$ clock <move_ms> <total_ms>` line. The
+    /// total is `-` when the bot is not on a game clock.
+    pub fn set_clock_header(&mut self, enabled: bool) {
+        self.clock_header = enabled;
+    }
+
+    /// Time the bot may spend on the current move
+    fn move_budget(&self) -> Duration {
+        match self.clock {
+            Some(clock) if clock < self.timeout => clock,
+            _ => self.timeout,
+        }
+    }
+
+    fn clock_line(&self, budget: Duration) -> String {
+        let total = match self.clock {
+            Some(clock) => clock.as_millis().to_string(),
+            None => String::from("-"),
+        };
+        format!("$$$ clock {} {}\n", budget.as_millis(), total)
+    }
+
     pub fn name(&self) -> String {
-        Path::new(self.path)
-            .file_name()
-            .unwrap()
-            .to_string_lossy()
-            .to_owned()
-            .to_string()
+        self.name.clone()
+    }
+
+    /// Sends the plateau followed by every piece of the turn and expects one
+    /// placement per piece, in order. The placements are validated as a batch:
+    /// if any of them is rejected none are applied.
+    pub fn request_placement(
+        &mut self,
+        plateau: &mut Plateau,
+        pieces: &[Piece],
+        move_number: usize,
+        referees: &mut [Box<dyn Referee>],
+    ) -> PlayerResponse {
+        let started = Instant::now();
+        let response = self.play_turn(plateau, pieces, referees);
+        self.report.record(started.elapsed(), response.rejection);
+        let usage = self
+            .process_stats
+            .pid()
+            .and_then(|pid| resources::sample(pid, move_number));
+        self.report.resources.extend(usage);
+        response
     }
 
-    pub fn request_placement(&mut self, plateau: &mut Plateau, piece: &Piece) -> PlayerResponse {
+    fn play_turn(
+        &mut self,
+        plateau: &mut Plateau,
+        pieces: &[Piece],
+        referees: &mut [Box<dyn Referee>],
+    ) -> PlayerResponse {
         let mut player_response = PlayerResponse {
             player: self.player,
-            piece: piece.clone(),
+            piece: pieces[0].clone(),
+            batch: pieces[1..].to_vec(),
             raw_response: None,
+            placement: None,
+            batch_placements: vec![],
             placement_count: self.placement_count,
             error: None,
+            rejection: None,
+            timestamp: 0,
+            board_hash: None,
+            name: Some(self.name.clone()),
+            notes: vec![],
         };
 
-        let msg = format!("{}{}", plateau, piece);
-        if let Err(error_message) = self.send(msg) {
-            player_response.error = Some(error_message);
+        let budget = self.move_budget();
+        let fogged = self.fog.map(|radius| plateau.fogged(self.player, radius));
+        let seen = fogged.as_ref().unwrap_or(plateau);
+        let msg = match self.json_frames {
+            true => json_frame::render(seen, pieces, self.player, budget, self.clock),
+            false => self.ascii_frame(seen, pieces, budget),
+        };
+        let msg = self.adapter.outgoing(msg);
+        let started = Instant::now();
+        if let Some(latency) = self.latency {
+            thread::sleep(latency.sample());
+        }
+        if let Err(error_message) = self.send(msg, pieces.len()) {
+            self.crashed = true;
+            player_response.reject(Rejection::BotCrashed, error_message);
             return player_response;
         }
 
-        let raw_response = self.receive();
-        if let Err(error_message) = raw_response {
-            player_response.error = Some(error_message);
+        let remaining = budget.saturating_sub(started.elapsed());
+        let raw_response = self.receive(pieces.len(), remaining);
+        if let Some(clock) = self.clock.as_mut() {
+            *clock = clock.checked_sub(started.elapsed()).unwrap_or_default();
+        }
+        if let Err((rejection, error_message)) = raw_response {
+            self.crashed |= rejection == Rejection::BotCrashed;
+            player_response.reject(rejection, error_message);
             return player_response;
         }
         let raw_response = raw_response.unwrap();
+        player_response.raw_response = Some(raw_response.concat());
 
-        let placement = Point::try_from(&raw_response);
-        player_response.raw_response = Some(raw_response);
-        if let Err(error_message) = placement {
-            player_response.error = Some(error_message);
-            return player_response;
-        }
+        let mut next_plateau = plateau.clone();
+        for (piece, line) in pieces.iter().zip(raw_response.iter()) {
+            let placement = match Point::try_from(line.as_str()) {
+                Ok(placement) => placement,
+                Err(error_message) => {
+                    player_response.reject(Rejection::Malformed, error_message);
+                    return player_response;
+                }
+            };
+            match player_response.placement {
+                None => player_response.placement = Some(placement),
+                Some(_) => player_response.batch_placements.push(placement),
+            }
 
-        if let Err(error_message) = plateau.place_piece(piece, &placement.unwrap(), self.player) {
-            player_response.error = Some(error_message);
-            return player_response;
+            if let Err(error) = next_plateau.place_piece(piece, &placement, self.player) {
+                player_response.reject(error.into(), error.to_string());
+                return player_response;
+            }
+        }
+        for referee in referees.iter_mut() {
+            match referee.review(plateau.view(), next_plateau.view(), &player_response) {
+                Verdict::Allow => {}
+                Verdict::Annotate(note) => player_response.notes.push(note),
+                Verdict::Veto(reason) => {
+                    player_response.reject(Rejection::Vetoed, reason);
+                    return player_response;
+                }
+            }
         }
+        *plateau = next_plateau;
 
-        self.placement_count += 1;
+        self.placement_count += pieces.len();
         player_response.placement_count = self.placement_count;
 
         player_response
     }
 
-    fn send(&self, message: String) -> Result<(), String> {
-        match self.sender.send(message) {
+    fn ascii_frame(&self, plateau: &Plateau, pieces: &[Piece], budget: Duration) -> String {
+        let mut msg = match self.clock_header {
+            true => self.clock_line(budget),
+            false => String::new(),
+        };
+        msg += &self.adapter.render_plateau(plateau);
+        if pieces.len() > 1 {
+            msg += &format!("Pieces {}:\n", pieces.len());
+        }
+        for piece in pieces {
+            msg += &format!("{}", piece);
+        }
+        msg
+    }
+
+    /// Tells the bot the game is over with a `$$$ end` line carrying every
+    /// player's score. Unless `keep_alive` is set the bot's stdin is then
+    /// closed and it is given a moment to exit before being killed, whether
+    /// or not it is still answering.
+    pub fn end_game(&mut self, scores: &[(Player, usize)], keep_alive: bool) {
+        let mut line = String::from("$$$ end");
+        for (player, score) in scores {
+            line += &format!(" {}:{}", player, score);
+        }
+        line.push('\n');
+
+        if keep_alive {
+            let _ = self.send(line, 0);
+            return;
+        }
+        let _ = self.sender.send(Frame::Close(line));
+        self.disconnect();
+    }
+
+    /// Waits for the bot's I/O thread to finish. A thread still waiting on
+    /// the bot after `SHUTDOWN_GRACE`, because the bot hung, has its bot
+    /// killed or its connection shut down.
+    fn disconnect(&mut self) {
+        let handle = match self.handle.take() {
+            Some(handle) => handle,
+            None => return,
+        };
+        let closed = Instant::now();
+        while !handle.is_finished() && closed.elapsed() < SHUTDOWN_GRACE {
+            thread::sleep(Duration::from_millis(10));
+        }
+        if !handle.is_finished() {
+            self.link.cut();
+        }
+        let _ = handle.join();
+    }
+
+    fn send(&self, message: String, expected_lines: usize) -> Result<(), String> {
+        match self.sender.send(Frame::Message(message, expected_lines)) {
             Ok(_) => Ok(()),
             Err(_) => Err(String::from("Error while sending message")),
         }
     }
 
-    fn receive(&self) -> Result<String, String> {
-        let s = self
-            .receiver
-            .recv_timeout(Duration::from_secs(self.timeout as u64));
-        match s {
-            Ok(s) => Ok(s),
-            Err(_) => Err(String::from("Timed out")),
+    /// Collects `lines` lines from the bot, all of which must arrive within
+    /// `budget`. A bot asking for JSON frames is switched over from the next
+    /// turn on, the request does not count as one of the lines. Lines the bot
+    /// still owed for an earlier frame are skipped first.
+    fn receive(
+        &mut self,
+        lines: usize,
+        budget: Duration,
+    ) -> Result<Vec<String>, (Rejection, String)> {
+        let deadline = Instant::now() + budget;
+        let mut received = Vec::with_capacity(lines);
+
+        while received.len() < lines {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match self.receiver.recv_timeout(remaining) {
+                Ok(s) if s.trim_end() == json_frame::REQUEST_LINE => self.json_frames = true,
+                Ok(_) if self.stale_lines > 0 => self.stale_lines -= 1,
+                Ok(s) => received.push(self.adapter.incoming(s)),
+                Err(RecvTimeoutError::Timeout) => {
+                    self.stale_lines += lines - received.len();
+                    return Err((Rejection::Timeout, String::from("Timed out")));
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err((
+                        Rejection::BotCrashed,
+                        format!("{} stopped responding", self.path),
+                    ))
+                }
+            }
         }
+        Ok(received)
     }
 
     fn spawn_player(
         path: &str,
-        player_num: Player,
-    ) -> Result<(Sender<std::string::String>, Receiver<std::string::String>), ComError> {
-        let (sender, receiver_internal) = mpsc::channel();
+        player: Player,
+        prelude: String,
+        sandbox: &Sandbox,
+    ) -> Result<Channels, ComError> {
+        let (sender, receiver_internal) = mpsc::channel::<Frame>();
         let (sender_internal, receiver) = mpsc::channel();
+        let stats = Arc::new(ProcessStats::default());
+
+        if let BotKind::Builtin(strategy) = BotKind::of(path) {
+            // There is no process behind a built-in bot
+            *stats.exit_status.lock().unwrap() = Some(String::from("built-in"));
+            let bot = strategy.create(player);
+            let handle =
+                thread::spawn(move || Bot::run_native(bot, receiver_internal, sender_internal));
+            return Ok((sender, receiver, handle, stats, Link::Builtin));
+        }
 
+        if let BotKind::Remote(address) = BotKind::of(path) {
+            // Remote bots run wherever they like, outside of the sandbox
+            let stream = tcp_bot::accept(&address, tcp_bot::CONNECT_TIMEOUT)
+                .map_err(|e| format!("Bot {}: {}", path, e))?;
+            let link = stream
+                .try_clone()
+                .map_err(|e| format!("Bot {}: {}", path, e))?;
+            let link = Arc::new(Mutex::new(Some(link)));
+            let remote = Remote {
+                path: String::from(path),
+                address,
+                prelude,
+                link: Arc::clone(&link),
+                stats: Arc::clone(&stats),
+            };
+            let handle =
+                thread::spawn(move || remote.run(stream, receiver_internal, sender_internal));
+            return Ok((sender, receiver, handle, stats, Link::Socket(link)));
+        }
+
+        let mut command = sandbox.command(path);
         let path = String::from(path);
-        thread::spawn(move || {
-            let mut child_process = Command::new(&path)
+        let thread_stats = Arc::clone(&stats);
+        let child = Arc::new(Mutex::new(None));
+        let thread_child = Arc::clone(&child);
+        let handle = thread::spawn(move || {
+            let stats = thread_stats;
+            let mut child_process = command
                 .stdin(Stdio::piped())
                 .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
                 .spawn()
                 .unwrap_or_else(|_| panic!("Could not initialize player: {}", path));
+            stats.pid.store(child_process.id(), Ordering::Relaxed);
+
+            if let Some(child_err) = child_process.stderr.take() {
+                let stats = Arc::clone(&stats);
+                thread::spawn(move || Bot::forward_stderr(child_err, &stats));
+            }
 
-            let child_in = child_process
+            let mut child_in = child_process
                 .stdin
-                .as_mut()
+                .take()
                 .unwrap_or_else(|| panic!("Could not retrieve stdin for: {}", path));
 
             let mut child_out = BufReader::new(
                 child_process
                     .stdout
-                    .as_mut()
+                    .take()
                     .unwrap_or_else(|| panic!("Could not retrieve stdout for: {}", path)),
             );
+            *thread_child.lock().unwrap() = Some(child_process);
+
+            child_in
+                .write_all(prelude.as_bytes())
+                .unwrap_or_else(|_| panic!("Error initializing player: {}", path));
 
-            match player_num {
-                Player::Player1 => write!(child_in, "$$$ exec p1 : {}\n", path)
-                    .expect("Error initializing player 2"),
-                Player::Player2 => write!(child_in, "$$$ exec p2 : {}\n", path)
-                    .expect("Error initializing player 2"),
+            let _ = Bot::exchange(
+                &mut child_in,
+                &mut child_out,
+                &receiver_internal,
+                &sender_internal,
+                None,
+            );
+
+            drop(child_out);
+            drop(child_in);
+            let closed = Instant::now();
+            let status = loop {
+                let mut child = thread_child.lock().unwrap();
+                let child_process = child.as_mut().unwrap();
+                if let Ok(Some(exited)) = child_process.try_wait() {
+                    break exited.to_string();
+                }
+                if closed.elapsed() >= SHUTDOWN_GRACE {
+                    let _ = child_process.kill();
+                    break match child_process.wait() {
+                        Ok(exited) => format!("killed ({})", exited),
+                        Err(_) => String::from("killed"),
+                    };
+                }
+                drop(child);
+                thread::sleep(Duration::from_millis(10));
             };
+            *stats.exit_status.lock().unwrap() = Some(status);
+        });
 
-            loop {
-                let receive: String = receiver_internal
-                    .recv()
-                    .unwrap_or_else(|_| String::from(""));
-                child_in
-                    .write(receive.as_bytes())
-                    .unwrap_or_else(|_| panic!("Error writing to: {}", path));
+        Ok((sender, receiver, handle, stats, Link::Process(child)))
+    }
+
+    /// Sends the bot its frames, starting with `resend` if there is one, and
+    /// passes its replies on. Runs until the engine closes the bot or drops
+    /// its sender. If the bot goes away first the frame it left unanswered
+    /// is returned as the error; unless the bot reconnects the engine sees
+    /// this as a crash.
+    fn exchange(
+        bot_in: &mut impl Write,
+        bot_out: &mut impl BufRead,
+        frames: &Receiver<Frame>,
+        replies: &Sender<String>,
+        mut resend: Option<Unanswered>,
+    ) -> Result<(), Unanswered> {
+        loop {
+            let (message, expected_lines, passed_on) = match resend.take() {
+                Some(unanswered) => unanswered,
+                None => match frames.recv() {
+                    Ok(Frame::Message(message, expected_lines)) => (message, expected_lines, 0),
+                    Ok(Frame::Close(message)) => {
+                        let _ = bot_in.write_all(message.as_bytes());
+                        return Ok(());
+                    }
+                    Ok(Frame::Rules(_)) => continue,
+                    Err(_) => return Ok(()),
+                },
+            };
+            if bot_in.write_all(message.as_bytes()).is_err() {
+                return Err((message, expected_lines, passed_on));
+            }
+            let mut read = 0;
+            while read < expected_lines {
                 let mut line = String::new();
-                child_out
-                    .read_line(&mut line)
-                    .unwrap_or_else(|_| panic!("Error while reading line from: {}", path));
-                sender_internal
-                    .send(line)
-                    .unwrap_or_else(|_| panic!("Error while sending message from: {}", path));
+                match bot_out.read_line(&mut line) {
+                    Ok(0) | Err(_) => {
+                        let passed_on = passed_on.max(read);
+                        return Err((message, expected_lines, passed_on));
+                    }
+                    Ok(_) => {}
+                }
+                let request = line.trim_end() == json_frame::REQUEST_LINE;
+                // Lines of a resent frame the engine already has are dropped
+                if !request && read < passed_on {
+                    read += 1;
+                    continue;
+                }
+                if !request {
+                    read += 1;
+                }
+                if replies.send(line).is_err() {
+                    return Ok(());
+                }
             }
-        });
+        }
+    }
+
+    /// Passes the bot's stderr through to ours, counting the bytes written
+    fn forward_stderr(mut child_err: impl Read, stats: &ProcessStats) {
+        let mut buffer = [0; 4096];
+        while let Ok(read) = child_err.read(&mut buffer) {
+            if read == 0 {
+                break;
+            }
+            stats.stderr_bytes.fetch_add(read, Ordering::Relaxed);
+            let _ = io::stderr().write_all(&buffer[..read]);
+        }
+    }
+
+    /// Feeds frames to a built-in bot on the bot's I/O thread
+    fn run_native(mut bot: Box<dyn NativeBot>, frames: Receiver<Frame>, replies: Sender<String>) {
+        while let Ok(frame) = frames.recv() {
+            let message = match frame {
+                Frame::Message(message, _) => message,
+                Frame::Rules(rule) => {
+                    bot.set_overlap_rule(rule);
+                    continue;
+                }
+                Frame::Close(_) => return,
+            };
+            for line in native_bot::respond(bot.as_mut(), &message) {
+                if replies.send(line).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Diagnostics for the current game, see `BotReport`
+    pub fn report(&self) -> BotReport {
+        let mut report = self.report.clone();
+        self.process_stats.fill(&mut report);
+        report
+    }
+
+    pub fn adapter(&self) -> BotAdapter {
+        self.adapter
+    }
+
+    pub fn fog(&self) -> Option<usize> {
+        self.fog
+    }
+
+    pub fn latency(&self) -> Option<Latency> {
+        self.latency
+    }
 
-        Ok((sender, receiver))
+    pub fn clock_header(&self) -> bool {
+        self.clock_header
     }
 
     pub fn placement_count(&self) -> usize {
         self.placement_count
     }
 
+    /// Game time left, if the bot is on a clock
+    pub fn clock(&self) -> Option<Duration> {
+        self.clock
+    }
+
+    /// Carries over the placements and game time of a resumed game
+    pub fn resume(&mut self, placement_count: usize, clock: Option<Duration>) {
+        self.placement_count = placement_count;
+        if clock.is_some() {
+            self.clock = clock;
+        }
+    }
+
     pub fn player(&self) -> Player {
         self.player
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::net::TcpListener;
+
+    fn hang_then_end(path: &str) {
+        let prelude = String::from("$$$ exec p1 : [hung]\n");
+        let mut bot = Bot::new(
+            path,
+            String::from("hung"),
+            Duration::from_millis(100),
+            Player::Player1,
+            prelude,
+            Sandbox::default(),
+        )
+        .unwrap();
+        let mut plateau = Plateau::new(3, 2, &Point::new(0, 0), &Point::new(2, 1)).unwrap();
+        let piece = Piece::new(1, 1, vec![true]);
+        let response = bot.request_placement(&mut plateau, &[piece], 0, &mut []);
+        assert_eq!(response.rejection, Some(Rejection::Timeout));
+
+        let started = Instant::now();
+        bot.end_game(&[], false);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn hung_bots_are_cut_off_at_the_end_of_the_game() {
+        if cfg!(unix) {
+            let script = env_dir().join("hung.sh");
+            fs::write(&script, "#!/bin/sh\nexec sleep 600\n").unwrap();
+            set_executable(&script);
+            hang_then_end(&script.to_string_lossy());
+            fs::remove_file(&script).unwrap();
+        }
+
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let address = format!("127.0.0.1:{}", port);
+        let remote = thread::spawn(move || loop {
+            // Connects, then never answers
+            if let Ok(stream) = TcpStream::connect(&address) {
+                let mut silent = BufReader::new(stream);
+                let mut line = String::new();
+                while matches!(silent.read_line(&mut line), Ok(read) if read > 0) {}
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        });
+        hang_then_end(&format!("tcp:127.0.0.1:{}", port));
+        remote.join().unwrap();
+    }
+
+    #[test]
+    fn a_bad_placement_rejects_the_whole_batch() {
+        if !cfg!(unix) {
+            return;
+        }
+        let script = env_dir().join("batch.sh");
+        let answer =
+            "while read line; do case \"$line\" in Pieces*) printf '0 0\\n9 9\\n';; esac; done";
+        fs::write(&script, format!("#!/bin/sh\n{}\n", answer)).unwrap();
+        set_executable(&script);
+        let path = script.to_string_lossy();
+        let mut bot = Bot::new(
+            &path,
+            String::from("batch"),
+            Duration::from_secs(5),
+            Player::Player1,
+            String::from("$$$ exec p1 : [batch]\n"),
+            Sandbox::default(),
+        )
+        .unwrap();
+        let mut plateau = Plateau::new(3, 2, &Point::new(0, 0), &Point::new(2, 1)).unwrap();
+        let piece = Piece::new(1, 1, vec![true]);
+        let response = bot.request_placement(&mut plateau, &[piece.clone(), piece], 0, &mut []);
+        bot.end_game(&[], false);
+        fs::remove_file(&script).unwrap();
+
+        assert_eq!(response.rejection, Some(Rejection::OutOfBounds));
+        assert_eq!(response.placement, Some(Point::new(0, 0)));
+        assert_eq!(response.batch_placements, vec![Point::new(9, 9)]);
+        assert!(response.placements().is_empty());
+        assert_eq!(plateau.to_map_rows(), vec!["O..", "..X"]);
+        assert_eq!(bot.placement_count(), 0);
+        assert_eq!(bot.report().moves, 1);
+    }
+
+    #[test]
+    fn late_lines_of_a_batch_are_not_read_as_the_next_answer() {
+        if !cfg!(unix) {
+            return;
+        }
+        // Answers the first frame's second piece late and out of bounds
+        let script = env_dir().join("late.sh");
+        let answer = "frames=0; while read line; do case \"$line\" in Plateau*) \
+            frames=$((frames + 1)); echo '0 0'; \
+            if [ $frames -eq 1 ]; then sleep 0.6; echo '9 9'; fi;; esac; done";
+        fs::write(&script, format!("#!/bin/sh\n{}\n", answer)).unwrap();
+        set_executable(&script);
+        let path = script.to_string_lossy();
+        let mut bot = Bot::new(
+            &path,
+            String::from("late"),
+            Duration::from_millis(400),
+            Player::Player1,
+            String::from("$$$ exec p1 : [late]\n"),
+            Sandbox::default(),
+        )
+        .unwrap();
+        let mut plateau = Plateau::new(3, 2, &Point::new(0, 0), &Point::new(2, 1)).unwrap();
+        let piece = Piece::new(1, 1, vec![true]);
+
+        let batch = [piece.clone(), piece.clone()];
+        let first = bot.request_placement(&mut plateau, &batch, 0, &mut []);
+        assert_eq!(first.rejection, Some(Rejection::Timeout));
+        let second = bot.request_placement(&mut plateau, &[piece], 1, &mut []);
+        bot.end_game(&[], false);
+        fs::remove_file(&script).unwrap();
+
+        assert_eq!(second.error, None);
+        assert_eq!(second.placement, Some(Point::new(0, 0)));
+    }
+
+    #[test]
+    fn thinking_time_is_deducted_from_the_clock() {
+        if !cfg!(unix) {
+            return;
+        }
+        let script = env_dir().join("slow.sh");
+        let answer =
+            "while read line; do case \"$line\" in Piece*) sleep 0.3; echo '0 0';; esac; done";
+        fs::write(&script, format!("#!/bin/sh\n{}\n", answer)).unwrap();
+        set_executable(&script);
+        let path = script.to_string_lossy();
+        let mut bot = Bot::new(
+            &path,
+            String::from("slow"),
+            Duration::from_secs(5),
+            Player::Player1,
+            String::from("$$$ exec p1 : [slow]\n"),
+            Sandbox::default(),
+        )
+        .unwrap();
+        bot.set_total_clock(Duration::from_millis(500));
+        let mut plateau = Plateau::new(3, 2, &Point::new(0, 0), &Point::new(2, 1)).unwrap();
+        let pieces = [Piece::new(1, 1, vec![true])];
+
+        let first = bot.request_placement(&mut plateau, &pieces, 0, &mut []);
+        assert_eq!(first.error, None);
+        assert!(bot.clock().unwrap() <= Duration::from_millis(200));
+        // The rest of the clock is less than the bot needs
+        let second = bot.request_placement(&mut plateau, &pieces, 1, &mut []);
+        assert_eq!(second.rejection, Some(Rejection::Timeout));
+        assert_eq!(bot.clock(), Some(Duration::ZERO));
+        bot.end_game(&[], false);
+        fs::remove_file(&script).unwrap();
+    }
+
+    fn env_dir() -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("filler_bot_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[cfg(unix)]
+    fn set_executable(path: &std::path::Path) {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[cfg(not(unix))]
+    fn set_executable(_: &std::path::Path) {}
+}
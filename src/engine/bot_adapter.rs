@@ -0,0 +1,125 @@
+use crate::models::constants::*;
+use crate::models::Plateau;
+use std::fmt;
+use std::str::FromStr;
+
+/// I/O tweaks for bots that are slightly off the protocol. Parsed from a comma
+/// separated list of options, eg. `legacy-board,swap-xy`:
+///
+/// - `legacy-board`: send the plateau with column and row numbers, as the
+///   original VM did
+/// - `swap-tokens`: exchange the O and X characters on the plateau
+/// - `swap-xy`: the bot answers `x y` instead of `y x`
+/// - `crlf`: send lines terminated by `\r\n`
+/// - `json-frames`: send every turn as a JSON line from the start, rather
+///   than waiting for the bot to ask for it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BotAdapter {
+    pub legacy_board: bool,
+    pub swap_tokens: bool,
+    pub swap_xy: bool,
+    pub crlf: bool,
+    pub json_frames: bool,
+}
+
+impl BotAdapter {
+    pub fn render_plateau(&self, plateau: &Plateau) -> String {
+        match self.legacy_board {
+            true => plateau.render_human(),
+            false => plateau.render_protocol(),
+        }
+    }
+
+    /// Applies the adapter to a frame about to be sent to the bot
+    pub fn outgoing(&self, message: String) -> String {
+        let mut message = message;
+        if self.swap_tokens {
+            message = message
+                .chars()
+                .map(|c| match c {
+                    PLAYER1 => PLAYER2,
+                    PLAYER2 => PLAYER1,
+                    PLAYER1_NEW => PLAYER2_NEW,
+                    PLAYER2_NEW => PLAYER1_NEW,
+                    c => c,
+                })
+                .collect();
+        }
+        if self.crlf {
+            message = message.replace('\n', "\r\n");
+        }
+        message
+    }
+
+    /// Rewrites a line received from the bot into a `y x` placement
+    pub fn incoming(&self, line: String) -> String {
+        if !self.swap_xy {
+            return line;
+        }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            [x, y] => format!("{} {}\n", y, x),
+            _ => line,
+        }
+    }
+}
+
+/// Writes the options back as the comma separated list `FromStr` reads
+impl fmt::Display for BotAdapter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let options = [
+            (self.legacy_board, "legacy-board"),
+            (self.swap_tokens, "swap-tokens"),
+            (self.swap_xy, "swap-xy"),
+            (self.crlf, "crlf"),
+            (self.json_frames, "json-frames"),
+        ];
+        let enabled: Vec<&str> = options
+            .iter()
+            .filter(|(on, _)| *on)
+            .map(|(_, name)| *name)
+            .collect();
+        write!(f, "{}", enabled.join(","))
+    }
+}
+
+impl FromStr for BotAdapter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let mut adapter = BotAdapter::default();
+        for option in s.split(',').map(str::trim).filter(|o| !o.is_empty()) {
+            match option {
+                "legacy-board" => adapter.legacy_board = true,
+                "swap-tokens" => adapter.swap_tokens = true,
+                "swap-xy" => adapter.swap_xy = true,
+                "crlf" => adapter.crlf = true,
+                "json-frames" => adapter.json_frames = true,
+                _ => return Err(format!("Unknown adapter option: {}", option)),
+            }
+        }
+        Ok(adapter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_options() {
+        let adapter: BotAdapter = "swap-xy, crlf".parse().unwrap();
+        assert!(adapter.swap_xy && adapter.crlf);
+        assert!(!adapter.legacy_board && !adapter.swap_tokens);
+        assert!("swap-yx".parse::<BotAdapter>().is_err());
+        assert_eq!(adapter.to_string(), "swap-xy,crlf");
+    }
+
+    #[test]
+    fn rewrites_io() {
+        let adapter: BotAdapter = "swap-tokens,swap-xy,crlf".parse().unwrap();
+        assert_eq!(adapter.outgoing(String::from("Oo.\nXx\n")), "Xx.\r\nOo\r\n");
+        assert_eq!(adapter.incoming(String::from("3 7\r\n")), "7 3\n");
+        assert_eq!(adapter.incoming(String::from("garbage")), "garbage");
+    }
+}
@@ -0,0 +1,129 @@
+use super::{Interpreters, Sandbox};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Registry the CLI reads when none is given and the file exists
+pub const DEFAULT_REGISTRY: &str = "bots.toml";
+
+/// How to start one registered bot. Containerised bots are registered with
+/// the container runtime as `path` and the image in `args`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BotEntry {
+    pub path: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Directory the bot is started in, see `Sandbox::cwd`
+    #[serde(default)]
+    pub cwd: Option<PathBuf>,
+}
+
+impl BotEntry {
+    /// Adds the entry's arguments and directory to `sandbox`
+    pub fn configure(&self, sandbox: &mut Sandbox) {
+        sandbox.args = self.args.clone();
+        if self.cwd.is_some() {
+            sandbox.cwd = self.cwd.clone();
+        }
+    }
+}
+
+/// Friendly names for bots, read from a TOML file of `[bots.NAME]` tables:
+///
+/// ```toml
+/// [bots.greedy]
+/// path = "bots/greedy.filler"
+/// args = ["--depth", "2"]
+///
+/// [interpreters]
+/// py = ["pypy3"]
+/// ```
+///
+/// Relative paths are taken from the registry file's directory, so a
+/// registry can be moved together with its bots.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BotRegistry {
+    #[serde(default)]
+    bots: BTreeMap<String, BotEntry>,
+    /// Interpreter commands by file extension, see `Interpreters`
+    #[serde(default)]
+    interpreters: BTreeMap<String, Vec<String>>,
+}
+
+impl BotRegistry {
+    pub fn from_toml(text: &str) -> Result<BotRegistry, String> {
+        toml::from_str(text).map_err(|e| e.to_string())
+    }
+
+    pub fn load(path: &Path) -> Result<BotRegistry, String> {
+        let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let mut registry = BotRegistry::from_toml(&text)?;
+        let base = path.parent().unwrap_or_else(|| Path::new(""));
+        for entry in registry.bots.values_mut() {
+            // Bare names such as `python3` are looked up on the PATH
+            if entry.path.contains('/') && Path::new(&entry.path).is_relative() {
+                entry.path = base.join(&entry.path).to_string_lossy().into_owned();
+            }
+            if let Some(cwd) = entry.cwd.as_mut().filter(|cwd| cwd.is_relative()) {
+                *cwd = base.join(&cwd);
+            }
+        }
+        Ok(registry)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&BotEntry> {
+        self.bots.get(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.bots.keys().map(String::as_str)
+    }
+
+    /// Adds the registry's interpreters to `interpreters`
+    pub fn configure_interpreters(&self, interpreters: &mut Interpreters) {
+        for (extension, command) in self.interpreters.iter() {
+            interpreters.set(extension, command.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn resolves_paths_against_the_registry() {
+        let dir = env::temp_dir().join(format!("filler_registry_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(DEFAULT_REGISTRY);
+        fs::write(
+            &path,
+            "[bots.greedy]\npath = \"bots/greedy.filler\"\nargs = [\"-d\", \"2\"]\n\n\
+             [bots.docker]\npath = \"/usr/bin/docker\"\nargs = [\"run\", \"-i\", \"bot\"]\n\n\
+             [interpreters]\nrb = [\"ruby\"]\n",
+        )
+        .unwrap();
+        let registry = BotRegistry::load(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let greedy = registry.get("greedy").unwrap();
+        assert_eq!(Path::new(&greedy.path), dir.join("bots/greedy.filler"));
+        assert_eq!(greedy.args, vec!["-d", "2"]);
+        assert_eq!(registry.get("docker").unwrap().path, "/usr/bin/docker");
+        assert_eq!(
+            registry.names().collect::<Vec<_>>(),
+            vec!["docker", "greedy"]
+        );
+        let mut interpreters = Interpreters::none();
+        registry.configure_interpreters(&mut interpreters);
+        assert_eq!(
+            interpreters.command_for(Path::new("bot.rb")).unwrap(),
+            ["ruby"]
+        );
+        assert!(BotRegistry::from_toml("[bots.x]\nargs = []\n").is_err());
+    }
+}
@@ -0,0 +1,145 @@
+use super::{Rejection, ResourceSample};
+use crate::models::Player;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Diagnostics gathered about one bot over a game
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BotReport {
+    pub player: Player,
+    pub name: String,
+    /// Turns the bot was asked to play
+    pub moves: usize,
+    /// Rejected moves keyed by the reason they were rejected
+    pub invalid_moves: BTreeMap<String, usize>,
+    pub timeouts: usize,
+    pub total_think_ms: u64,
+    pub max_think_ms: u64,
+    /// Bytes the bot wrote to stderr
+    pub stderr_bytes: usize,
+    /// How the bot's process exited, `None` while it is still running
+    pub exit_status: Option<String>,
+    /// Times a remote bot connected again after its connection dropped
+    pub reconnects: usize,
+    /// Memory and CPU time of the bot's process after each of its moves,
+    /// empty without procfs or for built-in bots
+    pub resources: Vec<ResourceSample>,
+}
+
+impl BotReport {
+    pub fn new(player: Player, name: String) -> BotReport {
+        BotReport {
+            player,
+            name,
+            moves: 0,
+            invalid_moves: BTreeMap::new(),
+            timeouts: 0,
+            total_think_ms: 0,
+            max_think_ms: 0,
+            stderr_bytes: 0,
+            exit_status: None,
+            reconnects: 0,
+            resources: vec![],
+        }
+    }
+
+    /// Records one turn that took `think_time` and ended with `rejection`, if any
+    pub fn record(&mut self, think_time: Duration, rejection: Option<Rejection>) {
+        let think_ms = think_time.as_millis() as u64;
+        self.moves += 1;
+        self.total_think_ms += think_ms;
+        self.max_think_ms = self.max_think_ms.max(think_ms);
+        match rejection {
+            Some(Rejection::Timeout) => self.timeouts += 1,
+            Some(rejection) => {
+                *self
+                    .invalid_moves
+                    .entry(format!("{:?}", rejection))
+                    .or_insert(0) += 1
+            }
+            None => {}
+        }
+    }
+
+    pub fn peak_rss_kb(&self) -> Option<u64> {
+        self.resources.iter().map(|sample| sample.rss_kb).max()
+    }
+
+    pub fn average_think_ms(&self) -> u64 {
+        match self.moves {
+            0 => 0,
+            moves => self.total_think_ms / moves as u64,
+        }
+    }
+}
+
+impl fmt::Display for BotReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} ({})", self.name, self.player)?;
+        writeln!(f, "  Moves:          {}", self.moves)?;
+        writeln!(f, "  Timeouts:       {}", self.timeouts)?;
+        for (reason, count) in self.invalid_moves.iter() {
+            writeln!(f, "  Invalid ({}): {}", reason, count)?;
+        }
+        writeln!(f, "  Think time avg: {}ms", self.average_think_ms())?;
+        writeln!(f, "  Think time max: {}ms", self.max_think_ms)?;
+        writeln!(f, "  Stderr bytes:   {}", self.stderr_bytes)?;
+        if let (Some(peak), Some(last)) = (self.peak_rss_kb(), self.resources.last()) {
+            writeln!(f, "  Peak memory:    {} kB", peak)?;
+            writeln!(f, "  CPU time:       {}ms", last.cpu_ms)?;
+        }
+        if self.reconnects > 0 {
+            writeln!(f, "  Reconnects:     {}", self.reconnects)?;
+        }
+        let exit_status = self.exit_status.as_deref().unwrap_or("running");
+        writeln!(f, "  Exit status:    {}", exit_status)
+    }
+}
+
+/// What a bot's I/O threads observe about its process
+#[derive(Debug, Default)]
+pub(crate) struct ProcessStats {
+    /// Id of the bot's process once started, 0 before that
+    pub pid: AtomicU32,
+    pub stderr_bytes: AtomicUsize,
+    pub exit_status: Mutex<Option<String>>,
+    pub reconnects: AtomicUsize,
+}
+
+impl ProcessStats {
+    pub fn pid(&self) -> Option<u32> {
+        match self.pid.load(Ordering::Relaxed) {
+            0 => None,
+            pid => Some(pid),
+        }
+    }
+
+    pub fn fill(&self, report: &mut BotReport) {
+        report.stderr_bytes = self.stderr_bytes.load(Ordering::Relaxed);
+        report.exit_status = self.exit_status.lock().unwrap().clone();
+        report.reconnects = self.reconnects.load(Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_tallies_rejections_and_think_time() {
+        let mut report = BotReport::new(Player::Player1, String::from("bot"));
+        report.record(Duration::from_millis(10), None);
+        report.record(Duration::from_millis(30), Some(Rejection::Timeout));
+        report.record(Duration::from_millis(20), Some(Rejection::NoOverlap));
+
+        assert_eq!(report.moves, 3);
+        assert_eq!(report.timeouts, 1);
+        assert_eq!(report.invalid_moves.get("NoOverlap"), Some(&1));
+        assert_eq!(report.average_think_ms(), 20);
+        assert_eq!(report.max_think_ms, 30);
+    }
+}
@@ -0,0 +1,146 @@
+use super::{preflight, BotKind, Interpreters};
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// A setup problem and what to do about it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub problem: String,
+    pub fix: String,
+}
+
+impl Finding {
+    fn new(problem: String, fix: String) -> Finding {
+        Finding { problem, fix }
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}\n  fix: {}", self.problem, self.fix)
+    }
+}
+
+/// Checks that the bot at `path` can be started: that it exists, and is
+/// either executable or has an installed interpreter
+pub fn check_bot(path: &str, interpreters: &Interpreters) -> Option<Finding> {
+    if !BotKind::of(path).is_process() {
+        return None;
+    }
+    let file = Path::new(path);
+    // Bare names such as `python3` are looked up on the PATH when started
+    if !path.contains('/') && !file.exists() {
+        return match is_installed(path) {
+            true => None,
+            false => Some(Finding::new(
+                format!("Bot {}: not found", path),
+                String::from("check the path, or register the bot in bots.toml"),
+            )),
+        };
+    }
+    let metadata = match fs::metadata(file) {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            return Some(Finding::new(
+                format!("Bot {}: {}", path, e),
+                String::from("check the path, or register the bot in bots.toml"),
+            ))
+        }
+    };
+    if !metadata.is_file() {
+        return Some(Finding::new(
+            format!("Bot {}: not a file", path),
+            String::from("point at the bot's executable instead of its directory"),
+        ));
+    }
+
+    let extension = file
+        .extension()
+        .map(|extension| extension.to_string_lossy().into_owned());
+    match (interpreters.command_for(file), extension) {
+        (Some(command), Some(extension)) if !is_installed(&command[0]) => Some(Finding::new(
+            format!("Bot {}: interpreter {} is not installed", path, command[0]),
+            format!(
+                "install {}, or pick another with --interpreter {}=COMMAND",
+                command[0], extension
+            ),
+        )),
+        (Some(_), _) => None,
+        (None, _) if preflight::is_executable(&metadata) => None,
+        (None, Some(extension)) if extension != "filler" => Some(Finding::new(
+            format!("Bot {}: no interpreter for .{} files", path, extension),
+            format!(
+                "add one with --interpreter {}=COMMAND, or make the file executable",
+                extension
+            ),
+        )),
+        (None, _) => Some(Finding::new(
+            format!("Bot {}: not executable", path),
+            format!("run chmod +x {}", path),
+        )),
+    }
+}
+
+/// Checks that replays can be saved in `dir` by writing a scratch file
+pub fn check_replay_dir(dir: &Path) -> Option<Finding> {
+    if !dir.is_dir() {
+        return Some(Finding::new(
+            format!("Replay directory {}: does not exist", dir.display()),
+            format!("create it with mkdir -p {}", dir.display()),
+        ));
+    }
+    let scratch = dir.join(format!(".filler_doctor_{}", std::process::id()));
+    match fs::write(&scratch, "").and_then(|_| fs::remove_file(&scratch)) {
+        Ok(()) => None,
+        Err(e) => Some(Finding::new(
+            format!("Replay directory {}: {}", dir.display(), e),
+            String::from("fix its permissions, or save replays elsewhere with --json DIR"),
+        )),
+    }
+}
+
+/// Whether `program` can be started, either as a path or from the PATH
+fn is_installed(program: &str) -> bool {
+    if program.contains('/') {
+        return Path::new(program).is_file();
+    }
+    env::var_os("PATH")
+        .map(|paths| env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::native_bot;
+
+    #[test]
+    fn suggests_fixes_for_unstartable_bots() {
+        let dir = env::temp_dir().join(format!("filler_doctor_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let bot = dir.join("bot.filler");
+        let script = dir.join("bot.rb");
+        fs::write(&bot, "").unwrap();
+        fs::write(&script, "").unwrap();
+        let (bot, script) = (bot.to_string_lossy(), script.to_string_lossy());
+
+        let mut interpreters = Interpreters::none();
+        assert!(check_bot(native_bot::RANDOM, &interpreters).is_none());
+        assert!(check_bot("no_such_bot_anywhere", &interpreters).is_some());
+        if cfg!(unix) {
+            let finding = check_bot(&bot, &interpreters).unwrap();
+            assert_eq!(finding.fix, format!("run chmod +x {}", bot));
+            let finding = check_bot(&script, &interpreters).unwrap();
+            assert!(finding.fix.contains("--interpreter rb=COMMAND"));
+        }
+        interpreters.set("rb", vec![String::from("no-such-ruby")]);
+        let finding = check_bot(&script, &interpreters).unwrap();
+        assert!(finding.problem.contains("no-such-ruby is not installed"));
+
+        assert!(check_replay_dir(&dir).is_none());
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(check_replay_dir(&dir).is_some());
+    }
+}
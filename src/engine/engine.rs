@@ -1,20 +1,72 @@
-use super::{Bot, PlayerResponse};
-use crate::models::{PieceBag, Plateau, Player};
+use super::game_id::new_game_id;
+use super::game_state::CompactedHistory;
+use super::prelude::{self, Prelude};
+use super::subscription::Fanout;
+use super::{
+    native_bot, preflight, Bot, BotAdapter, BotReport, GameResult, GameState, Growth, Latency,
+    Outcome, PieceRange, Placement, PlayerResponse, Position, Replay, RuleSet, Sandbox, Scoring,
+    SpectatorDelay, Termination, TieBreak,
+};
+use crate::eval;
+use crate::models::{
+    maps, Identity, OverlapRule, Piece, PieceBag, Plateau, PlateauView, Player, StartLayout,
+};
+use chrono::Utc;
 use serde_json::json;
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
 
 /// Number of errors that may occure in a row before game ends
 const ERROR_THRESHOLD: usize = 6;
-/// Time in seconds that a player will be granted before timing out
-const DEFAULT_TIMEOUT: usize = 2;
+/// Time that a player will be granted before timing out
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(2);
+/// Moves played by `Engine::selfcheck`
+const SELFCHECK_MOVES: usize = 10;
 
 pub struct Engine<'a> {
     players: Vec<Bot<'a>>,
+    identities: Vec<Identity>,
     plateau: Plateau,
     piece_bag: PieceBag,
     move_count: usize,
     player_count: usize,
     history: Vec<PlayerResponse>,
     on_player_response: Box<dyn OnPlayerResponse>,
+    rules: RuleSet,
+    scoring: Option<Box<dyn Scoring>>,
+    piece_filter: Option<Box<dyn PieceFilter>>,
+    referees: Vec<Box<dyn Referee>>,
+    termination: Option<Termination>,
+    initial_plateau: Plateau,
+    persistent_bots: bool,
+    game_id: String,
+    metadata: BTreeMap<String, String>,
+    /// Wall-clock time in milliseconds since the epoch at which `run` started
+    started_at: i64,
+    checkpoint: Option<(PathBuf, usize)>,
+    /// Pieces dealt on the previous turn
+    last_dealt: Vec<Piece>,
+    /// Board the current game started on
+    game_start: Plateau,
+    /// Seat of the player who moves first
+    first_seat: usize,
+    /// Shortest time between two moves reaching the observer
+    min_move_interval: Option<Duration>,
+    /// Errors in a row after which each player's next error ends the game
+    error_thresholds: [usize; 2],
+    /// Bags of the players dealt pieces of their own size
+    player_bags: [Option<PieceBag>; 2],
+    /// Moves held in memory before they are compacted, see
+    /// `EngineBuilder::with_history_cap`
+    history_cap: Option<usize>,
+    history_spill: Option<BufWriter<File>>,
+    compacted: CompactedHistory,
+    /// Board before the first move still in `history`
+    history_start: Plateau,
 }
 
 pub struct EngineBuilder<'a> {
@@ -22,6 +74,30 @@ pub struct EngineBuilder<'a> {
     plateau: Option<Plateau>,
     piece_bag: Option<PieceBag>,
     on_player_response: Option<Box<dyn OnPlayerResponse>>,
+    rules: RuleSet,
+    scoring: Option<Box<dyn Scoring>>,
+    piece_filter: Option<Box<dyn PieceFilter>>,
+    referees: Vec<Box<dyn Referee>>,
+    total_clock: Option<Duration>,
+    clock_header: bool,
+    display_names: [Option<String>; 2],
+    colors: [Option<String>; 2],
+    prelude: Prelude,
+    persistent_bots: bool,
+    adapters: [BotAdapter; 2],
+    spectator_delay: Option<usize>,
+    fog: [Option<usize>; 2],
+    metadata: BTreeMap<String, String>,
+    checkpoint: Option<(PathBuf, usize)>,
+    first_seat: usize,
+    sandboxes: [Sandbox; 2],
+    latency: [Option<Latency>; 2],
+    min_move_interval: Option<Duration>,
+    timeouts: [Duration; 2],
+    error_thresholds: [usize; 2],
+    history_cap: Option<usize>,
+    history_spill: Option<BufWriter<File>>,
+    resume: Option<GameState>,
 }
 
 impl<'a> EngineBuilder<'a> {
@@ -35,99 +111,829 @@ impl<'a> EngineBuilder<'a> {
         self
     }
 
+    /// Plays on the bundled map with the given name
+    pub fn with_map(&mut self, name: &str) -> Result<&Self, String> {
+        let plateau = maps::get(name).ok_or_else(|| format!("Unknown map '{}'", name))?;
+        self.plateau = Some(plateau);
+        Ok(self)
+    }
+
+    /// Moves the plateau's start points to the given layout, keeping the
+    /// rest of the map. Fails if a start point lands on an obstacle.
+    pub fn with_start_layout(&mut self, layout: &StartLayout) -> Result<&Self, String> {
+        let current = self.plateau.clone().unwrap_or_default();
+        let (player1, player2) = layout.points(current.width(), current.height())?;
+        self.plateau = Some(current.with_starts_at(&player1, &player2)?);
+        Ok(self)
+    }
+
+    /// Attaches a key/value pair, such as a tournament name or round, that is
+    /// carried into the replay
+    pub fn with_metadata(&mut self, key: &str, value: &str) -> &Self {
+        self.metadata.insert(String::from(key), String::from(value));
+        self
+    }
+
+    /// Lets `player` make the first move
+    pub fn first_to_move(&mut self, player: Player) -> &Self {
+        self.first_seat = player as usize;
+        self
+    }
+
+    /// Starts from the position's board with its side to move, dealing its
+    /// pieces if it has any
+    pub fn with_position(&mut self, position: &Position) -> &Self {
+        self.plateau = Some(position.plateau.clone());
+        self.first_seat = position.to_move as usize;
+        if !position.pieces.is_empty() {
+            self.piece_bag = Some(PieceBag::finite(position.pieces.clone()));
+        }
+        self
+    }
+
     pub fn with_piecebag(&mut self, piece_bag: PieceBag) -> &Self {
         self.piece_bag = Some(piece_bag);
         self
     }
 
-    pub fn verbose(&mut self) -> &Self {
-        self.on_player_response
-            .replace(Box::new(PrintOnPlayerResponse {}));
+    /// Replaces every variant rule at once, the individual setters below
+    /// change one rule of the set
+    pub fn with_rules(&mut self, rules: RuleSet) -> &Self {
+        assert!(
+            rules.pieces_per_turn > 0,
+            "At least one piece has to be dealt per turn"
+        );
+        self.rules = rules;
         self
     }
 
-    pub fn finish(&mut self) -> Engine {
-        let mut players =
-            vec![Bot::new(self.players[0], DEFAULT_TIMEOUT, Player::Player1).unwrap()];
+    /// Number of own cells a piece has to cover, exactly one by default
+    pub fn with_overlap_rule(&mut self, rule: OverlapRule) -> &Self {
+        self.rules.overlap = rule;
+        self
+    }
+
+    /// Enables the erosion rule: after every move, cells that are neither part
+    /// of nor adjacent to the last `window` placements revert to empty
+    pub fn with_decay(&mut self, window: usize) -> &Self {
+        self.rules.decay_window = Some(window);
+        self
+    }
 
-        if let Some(player_path) = self.players.get(1) {
-            let player2 = Bot::new(*player_path, DEFAULT_TIMEOUT, Player::Player2).unwrap();
-            players.push(player2);
+    /// Grows the board by a ring of empty cells every `every` moves, up to
+    /// `max_rings` rings, see `Plateau::grow_ring`
+    pub fn with_growth(&mut self, every: usize, max_rings: usize) -> &Self {
+        self.rules.growth = Some(Growth { every, max_rings });
+        self
+    }
+
+    /// Scores the game with `scoring` instead of counting placed pieces
+    pub fn with_scoring(&mut self, scoring: Box<dyn Scoring>) -> &Self {
+        self.scoring = Some(scoring);
+        self
+    }
+
+    /// Rules applied in order to decide a game with level scores, which is
+    /// otherwise a draw
+    pub fn with_tie_breaks(&mut self, tie_breaks: Vec<TieBreak>) -> &Self {
+        self.rules.tie_breaks = tie_breaks;
+        self
+    }
+
+    /// Replaces the plateau's start points with fair, randomly chosen ones on
+    /// its empty cells, see `Plateau::with_starts_moved`. Bots are not told
+    /// where they start and have to find their origin on the first plateau
+    /// they receive.
+    pub fn blind_start(&mut self) -> &Self {
+        self.rules.blind_start = true;
+        self
+    }
+
+    /// Deals `player` random pieces with sides in `width_range` and
+    /// `height_range` instead of the bag's, as a handicap. The pieces come
+    /// from a bag of their own, seeded from the shared one. Turns that mirror
+    /// the opponent's pieces are dealt those regardless. Panics if a range is
+    /// empty or starts below 3.
+    pub fn with_player_piece_range(
+        &mut self,
+        player: Player,
+        width_range: [usize; 2],
+        height_range: [usize; 2],
+    ) -> &Self {
+        let range = PieceRange {
+            width: width_range,
+            height: height_range,
+        };
+        if let Err(e) = range.validate() {
+            panic!("{}", e);
         }
+        self.rules.piece_ranges[player as usize] = Some(range);
+        self
+    }
+
+    /// Deals `count` pieces every turn. The bot answers with one placement per
+    /// piece and the whole batch is rejected if any placement is invalid.
+    pub fn with_pieces_per_turn(&mut self, count: usize) -> &Self {
+        assert!(count > 0, "At least one piece has to be dealt per turn");
+        self.rules.pieces_per_turn = count;
+        self
+    }
+
+    /// Passes every piece through `filter` before it is dealt
+    pub fn with_piece_filter(&mut self, filter: Box<dyn PieceFilter>) -> &Self {
+        self.piece_filter = Some(filter);
+        self
+    }
+
+    /// Gives each player a game clock that all of their thinking time is
+    /// deducted from, on top of the per-move timeout
+    pub fn with_total_clock(&mut self, clock: Duration) -> &Self {
+        self.total_clock = Some(clock);
+        self
+    }
 
-        let plateau = match self.plateau.take() {
-            Some(set_plateau) => set_plateau,
-            None => Plateau::default(),
+    /// Opts in to sending each bot its remaining move and game time in a
+    /// header line before every plateau
+    pub fn with_clock_header(&mut self) -> &Self {
+        self.clock_header = true;
+        self
+    }
+
+    pub fn with_on_player_response(&mut self, observer: Box<dyn OnPlayerResponse>) -> &Self {
+        self.on_player_response = Some(observer);
+        self
+    }
+
+    /// Attaches `observer` alongside any already attached. Wrap it in a
+    /// `Subscription` to limit the moves it sees.
+    pub fn add_on_player_response(&mut self, observer: Box<dyn OnPlayerResponse>) -> &Self {
+        let observer = match self.on_player_response.take() {
+            Some(existing) => Box::new(Fanout(vec![existing, observer])),
+            None => observer,
         };
+        self.on_player_response = Some(observer);
+        self
+    }
+
+    /// Has `referee` review every legal move before it is committed, after
+    /// any referees already added
+    pub fn add_referee(&mut self, referee: Box<dyn Referee>) -> &Self {
+        self.referees.push(referee);
+        self
+    }
+
+    /// Holds every move back until at least `interval` has passed since the
+    /// previous one so live games can be followed. Only applies when an
+    /// observer is attached, headless runs keep full speed.
+    pub fn with_min_move_interval(&mut self, interval: Duration) -> &Self {
+        self.min_move_interval = Some(interval);
+        self
+    }
+
+    /// Name shown for `player` and passed to the bots' preludes, defaults to
+    /// the executable's file name
+    pub fn with_display_name(&mut self, player: Player, name: &str) -> &Self {
+        self.display_names[player as usize] = Some(String::from(name));
+        self
+    }
+
+    /// Colour `player` is drawn in, see `Identity`
+    pub fn with_color(&mut self, player: Player, color: &str) -> &Self {
+        self.colors[player as usize] = Some(String::from(color));
+        self
+    }
+
+    /// Replaces the line sent to bots on startup, see `Prelude` for the
+    /// supported placeholders
+    pub fn with_prelude(&mut self, template: &str) -> &Self {
+        self.prelude = Prelude::new(template);
+        self
+    }
+
+    /// Ends the game as stalled once every player has gone `turns`
+    /// consecutive turns without a successful placement. The error threshold
+    /// is raised as far as needed for the watchdog to fire before it.
+    pub fn with_watchdog(&mut self, turns: usize) -> &Self {
+        self.rules.watchdog = Some(turns);
+        self
+    }
+
+    /// Ends the game as stagnated once `moves` moves in a row have left the
+    /// territory balance, Player1's cells less Player2's, where it was a
+    /// round earlier, as when both players only fill their own pockets
+    pub fn with_stagnation_limit(&mut self, moves: usize) -> &Self {
+        self.rules.stagnation_limit = Some(moves);
+        self
+    }
+
+    /// Saves the game state to `path` every `moves` moves
+    pub fn with_checkpoint(&mut self, path: PathBuf, moves: usize) -> &Self {
+        self.checkpoint = Some((path, moves.max(1)));
+        self
+    }
+
+    /// Picks up the game where `state`, a checkpoint, left off. The rest of
+    /// the configuration, such as bots and rules, should match the game's.
+    /// Counters kept by `Engine::run`, such as errors in a row, start over.
+    pub fn resume(&mut self, state: GameState) -> &Self {
+        self.resume = Some(state);
+        self
+    }
+
+    /// Deals Player2 the pieces Player1 got on the turn before, turned half
+    /// a turn to match its mirrored start, so neither player is luckier with
+    /// piece orientation
+    pub fn mirror_pieces(&mut self) -> &Self {
+        self.rules.mirror_pieces = true;
+        self
+    }
+
+    /// Restrictions every bot process is started under
+    pub fn with_sandbox(&mut self, sandbox: Sandbox) -> &Self {
+        self.sandboxes = [sandbox.clone(), sandbox];
+        self
+    }
+
+    pub fn with_player_sandbox(&mut self, player: Player, sandbox: Sandbox) -> &Self {
+        self.sandboxes[player as usize] = sandbox;
+        self
+    }
+
+    /// Mutable access to a player's sandbox, for changing single settings
+    pub fn sandbox_mut(&mut self, player: Player) -> &mut Sandbox {
+        &mut self.sandboxes[player as usize]
+    }
+
+    /// Keeps bot processes alive between games started with
+    /// `Engine::next_game`, sending them a reset frame instead of respawning
+    pub fn persistent_bots(&mut self) -> &Self {
+        self.persistent_bots = true;
+        self
+    }
+
+    /// Adapts the I/O of `player`'s bot when it does not follow the protocol
+    pub fn with_adapter(&mut self, player: Player, adapter: BotAdapter) -> &Self {
+        self.adapters[player as usize] = adapter;
+        self
+    }
+
+    /// Handicaps `player` so that it only sees opponent cells within `radius`
+    /// cells of its own territory
+    pub fn with_fog(&mut self, player: Player, radius: usize) -> &Self {
+        self.fog[player as usize] = Some(radius);
+        self
+    }
+
+    /// Keeps at most `moves` moves of history in memory, for long games on
+    /// servers running many at once. Once the history reaches the cap its
+    /// moves are dropped, leaving only counts behind, and the replay starts
+    /// from the board they led to, like a slice.
+    pub fn with_history_cap(&mut self, moves: usize) -> &Self {
+        self.history_cap = Some(moves.max(1));
+        self
+    }
+
+    /// Writes the moves dropped by `with_history_cap` to `path`, one JSON
+    /// object per line, so the full history can be pieced together
+    pub fn with_history_spill(&mut self, path: &Path) -> Result<&Self, String> {
+        let file = File::create(path).map_err(|e| e.to_string())?;
+        self.history_spill = Some(BufWriter::new(file));
+        Ok(self)
+    }
+
+    /// Time every bot may think about a move before it times out, 2 seconds
+    /// by default
+    pub fn with_timeout(&mut self, timeout: Duration) -> &Self {
+        self.timeouts = [timeout, timeout];
+        self
+    }
+
+    pub fn with_player_timeout(&mut self, player: Player, timeout: Duration) -> &Self {
+        self.timeouts[player as usize] = timeout;
+        self
+    }
+
+    /// Number of errors that may occur in a row, by either player, before
+    /// the next error ends the game. Defaults to 6.
+    pub fn with_error_threshold(&mut self, errors: usize) -> &Self {
+        self.error_thresholds = [errors, errors];
+        self
+    }
+
+    /// Error threshold applied when `player` is the one who errs, see
+    /// `with_error_threshold`
+    pub fn with_player_error_threshold(&mut self, player: Player, errors: usize) -> &Self {
+        self.error_thresholds[player as usize] = errors;
+        self
+    }
+
+    /// Delays every frame sent to `player`, see `Latency`
+    pub fn with_latency(&mut self, player: Player, latency: Latency) -> &Self {
+        self.latency[player as usize] = Some(latency);
+        self
+    }
+
+    /// Holds moves back from the observer for `moves` moves, see
+    /// `SpectatorDelay`
+    pub fn with_spectator_delay(&mut self, moves: usize) -> &Self {
+        self.spectator_delay = Some(moves);
+        self
+    }
 
-        let piece_bag = match self.piece_bag.take() {
-            Some(set_piece_bag) => set_piece_bag,
-            None => PieceBag::default(),
+    pub fn verbose(&mut self) -> &Self {
+        self.add_on_player_response(Box::new(PrintOnPlayerResponse {}))
+    }
+
+    /// Checks the configuration without spawning any bots: bot executables,
+    /// start points and piece sizes. Returns every problem found.
+    pub fn preflight(&self) -> Vec<String> {
+        let default_plateau;
+        let plateau = match &self.plateau {
+            Some(plateau) => plateau,
+            None => {
+                default_plateau = Plateau::default();
+                &default_plateau
+            }
+        };
+        let bag_state = match &self.piece_bag {
+            Some(piece_bag) => piece_bag.state(),
+            None => PieceBag::default().state(),
         };
 
+        let mut problems: Vec<String> = self
+            .players
+            .iter()
+            .zip(self.sandboxes.iter())
+            .filter_map(|(path, sandbox)| preflight::check_bot(path, &sandbox.interpreters))
+            .collect();
+        problems.extend(self.sandboxes.iter().filter_map(Sandbox::check));
+        match self.rules.blind_start {
+            true => problems.extend(plateau.with_starts_moved().err()),
+            false => problems.extend(preflight::check_plateau(plateau)),
+        }
+        if !self.piece_bag.as_ref().is_some_and(PieceBag::is_fixed) {
+            problems.extend(preflight::check_piece_bag(&bag_state, plateau));
+        }
+        for range in self.rules.piece_ranges.iter().flatten() {
+            let bag_state = PieceBag::new(range.width, range.height).state();
+            problems.extend(preflight::check_piece_bag(&bag_state, plateau));
+        }
+        problems
+    }
+
+    /// Starts the bots and sets up the game. Fails if the rules cannot be
+    /// played under, if a bot cannot be started, or if `blind_start` finds no
+    /// fair start points on the map.
+    pub fn finish(&mut self) -> Result<Engine<'a>, String> {
+        self.rules.validate()?;
+        let mut plateau = self.plateau.take().unwrap_or_default();
+        if self.rules.blind_start {
+            plateau = plateau.with_starts_moved()?;
+        }
+        self.rules.prepare(&mut plateau);
+
+        let names: Vec<String> = self
+            .players
+            .iter()
+            .zip(self.display_names.iter())
+            .map(|(path, name)| name.clone().unwrap_or_else(|| prelude::default_name(path)))
+            .collect();
+
+        let identities: Vec<Identity> = names
+            .iter()
+            .zip([Player::Player1, Player::Player2].iter())
+            .zip(self.colors.iter())
+            .map(|((name, seat), color)| {
+                let mut identity = Identity::new(*seat, name);
+                if let Some(color) = color {
+                    identity.color = color.clone();
+                }
+                identity
+            })
+            .collect();
+
+        let mut players = vec![];
+        for (i, (path, player)) in self
+            .players
+            .iter()
+            .zip([Player::Player1, Player::Player2].iter())
+            .enumerate()
+        {
+            let opponent = names.get(1 - i).map(String::as_str).unwrap_or("");
+            let prelude = self.prelude.render(*player, path, &names[i], opponent);
+            let mut bot = Bot::new(
+                path,
+                names[i].clone(),
+                self.timeouts[i],
+                *player,
+                prelude,
+                self.sandboxes[i].clone(),
+            )?;
+            bot.set_adapter(self.adapters[i]);
+            bot.set_overlap_rule(self.rules.overlap);
+            bot.set_fog(self.fog[i]);
+            bot.set_latency(self.latency[i]);
+            players.push(bot);
+        }
+
+        for bot in players.iter_mut() {
+            if let Some(clock) = self.total_clock {
+                bot.set_total_clock(clock);
+            }
+            bot.set_clock_header(self.clock_header);
+        }
+
+        let piece_bag = self.piece_bag.take().unwrap_or_default();
+        let player_bags = self.rules.player_bags(piece_bag.seed());
+
+        // The watchdog fires after `turns` failed turns from every player,
+        // that many errors in a row
+        let mut error_thresholds = self.error_thresholds;
+        if let Some(turns) = self.rules.watchdog {
+            let needed = (turns * players.len()).saturating_sub(1);
+            for threshold in error_thresholds.iter_mut() {
+                *threshold = (*threshold).max(needed);
+            }
+        }
+
+        let min_move_interval = self
+            .min_move_interval
+            .filter(|_| self.on_player_response.is_some());
         let on_player_response = self
             .on_player_response
             .take()
-            .unwrap_or(Box::new(DefaultOnPlayerResponse {}));
+            .unwrap_or_else(|| Box::new(DefaultOnPlayerResponse {}));
+        let on_player_response: Box<dyn OnPlayerResponse> = match self.spectator_delay {
+            Some(delay) => Box::new(SpectatorDelay::new(on_player_response, delay)),
+            None => on_player_response,
+        };
 
-        Engine {
+        let mut engine = Engine {
             player_count: players.len(),
             players,
-            plateau,
+            identities,
             piece_bag,
             move_count: 0,
             history: vec![],
             on_player_response,
+            rules: self.rules.clone(),
+            scoring: self.scoring.take(),
+            piece_filter: self.piece_filter.take(),
+            referees: std::mem::take(&mut self.referees),
+            termination: None,
+            initial_plateau: plateau.clone(),
+            persistent_bots: self.persistent_bots,
+            game_id: new_game_id(),
+            metadata: self.metadata.clone(),
+            started_at: 0,
+            checkpoint: self.checkpoint.take(),
+            last_dealt: vec![],
+            game_start: plateau.clone(),
+            first_seat: self.first_seat,
+            min_move_interval,
+            error_thresholds,
+            player_bags,
+            history_cap: self.history_cap,
+            history_spill: self.history_spill.take(),
+            compacted: CompactedHistory::default(),
+            history_start: plateau.clone(),
+            plateau,
+        };
+        if let Some(state) = self.resume.take() {
+            engine.restore(state);
         }
+        Ok(engine)
     }
 }
 
 impl<'a> Engine<'a> {
-    pub fn builder<'b>(player_path: &'b str) -> EngineBuilder {
+    pub fn builder(player_path: &str) -> EngineBuilder<'_> {
         EngineBuilder {
             players: vec![player_path],
             plateau: None,
             piece_bag: None,
             on_player_response: None,
+            rules: RuleSet::default(),
+            scoring: None,
+            piece_filter: None,
+            referees: vec![],
+            total_clock: None,
+            clock_header: false,
+            display_names: [None, None],
+            colors: [None, None],
+            prelude: Prelude::default(),
+            persistent_bots: false,
+            adapters: [BotAdapter::default(); 2],
+            spectator_delay: None,
+            fog: [None, None],
+            metadata: BTreeMap::new(),
+            checkpoint: None,
+            first_seat: 0,
+            sandboxes: [Sandbox::default(), Sandbox::default()],
+            latency: [None, None],
+            min_move_interval: None,
+            timeouts: [DEFAULT_TIMEOUT; 2],
+            error_thresholds: [ERROR_THRESHOLD; 2],
+            history_cap: None,
+            history_spill: None,
+            resume: None,
         }
     }
 
-    pub fn run(&mut self) {
+    /// Plays the game to its end, printing the players and the final score,
+    /// and returns the result
+    pub fn run(&mut self) -> GameResult {
+        let run_started = Instant::now();
         let mut errors: usize = 0;
+        let mut failed_turns = vec![0; self.player_count];
+        // Whether each player's last turn failed with a piece that fit nowhere
+        let mut stuck = vec![false; self.player_count];
+        // Territory balance after each move of the last round, oldest first
+        let mut balances = VecDeque::with_capacity(self.player_count + 1);
+        let mut stagnant_moves = 0;
+        if self.started_at == 0 {
+            self.started_at = Utc::now().timestamp_millis();
+        }
+        let mut last_move = Instant::now();
 
         for bot in self.players.iter() {
             println!("Player {}: {}", bot.player(), bot.name())
         }
 
-        loop {
-            let response = self.next_move();
-            &self.on_player_response.on_player_move(self, &response);
+        while let Some(response) = self.next_move() {
+            if let Some(interval) = self.min_move_interval {
+                let elapsed = last_move.elapsed();
+                if elapsed < interval {
+                    thread::sleep(interval - elapsed);
+                }
+                last_move = Instant::now();
+            }
+            self.on_player_response
+                .on_player_move(self.plateau.view(), &response);
+
+            let seat = response.player as usize;
+            match &response.error {
+                None => failed_turns[seat] = 0,
+                Some(_) => failed_turns[seat] += 1,
+            }
+            if let Some(turns) = self.rules.watchdog {
+                if failed_turns.iter().all(|failed| *failed >= turns) {
+                    self.termination = Some(Termination::Stalled);
+                }
+            }
 
             match &response.error {
                 None => errors = 0,
-                Some(_) if errors >= ERROR_THRESHOLD => break,
+                Some(_) if errors >= self.error_thresholds[seat] => {
+                    self.termination.get_or_insert(Termination::ErrorThreshold);
+                }
                 Some(_) => errors += 1,
             }
+            stuck[seat] = response.error.is_some()
+                && !self.plateau.can_place(&response.piece, response.player);
             self.history.push(response);
+            self.save_checkpoint();
+            self.compact_history();
+
+            if self.termination.is_some() {
+                break;
+            }
+            if stuck.iter().all(|stuck| *stuck) {
+                self.termination = Some(Termination::NoMovesLeft);
+                break;
+            }
+
+            let balance = eval::territory_difference(self.plateau.view(), Player::Player1);
+            balances.push_back(balance);
+            if balances.len() > self.player_count {
+                match balances.pop_front() {
+                    Some(round_ago) if round_ago == balance => stagnant_moves += 1,
+                    _ => stagnant_moves = 0,
+                }
+            }
+            if let Some(limit) = self.rules.stagnation_limit {
+                if stagnant_moves >= limit {
+                    self.termination = Some(Termination::Stagnation);
+                    break;
+                }
+            }
         }
 
-        let placements = self.placement_counts();
+        self.on_player_response.on_game_end(self.plateau.view());
+        if let Some(Err(e)) = self.history_spill.as_mut().map(BufWriter::flush) {
+            eprintln!("History spill: {}", e);
+        }
+
+        let scores = self.scores();
+        for bot in self.players.iter_mut() {
+            bot.end_game(&scores, self.persistent_bots);
+        }
+        if let Some(termination) = self.termination {
+            println!("Game over: {}", termination);
+        }
         println!("Final Score: ");
-        for (player, count) in placements {
+        for (player, count) in scores {
             println!("<{}> -> {}", player, count);
         }
+        let outcome = self.outcome();
+        if self.player_count == 2 {
+            println!("{}", outcome);
+        }
+
+        GameResult {
+            game_id: self.game_id.clone(),
+            metadata: self.metadata.clone(),
+            outcome,
+            scores: self.scores(),
+            placement_counts: self.placement_counts(),
+            moves: self.compacted.moves + self.history.len(),
+            termination: self.termination,
+            duration: run_started.elapsed(),
+            reports: self.bot_reports(),
+        }
     }
 
-    pub fn next_move(&mut self) -> PlayerResponse {
-        let player_com = &mut self.players[self.move_count % self.player_count];
+    /// Result of the game from the scores and, if they are level, the
+    /// configured tie-breaks. A game errors ended before anyone placed a
+    /// piece is no contest.
+    pub fn outcome(&self) -> Outcome {
+        let broken_off = matches!(
+            self.termination,
+            Some(Termination::ErrorThreshold) | Some(Termination::Stalled)
+        );
+        if broken_off && self.players.iter().all(|bot| bot.placement_count() == 0) {
+            return Outcome::NoContest;
+        }
+        Outcome::decide(
+            &self.scores(),
+            &self.rules.tie_breaks,
+            |player, tie_break| match tie_break {
+                TieBreak::FewestErrors => {
+                    let errors = self
+                        .history
+                        .iter()
+                        .filter(|response| response.player == player && response.error.is_some())
+                        .count();
+                    (self.compacted.errors[player as usize] + errors) as u64
+                }
+                TieBreak::FasterClock => self
+                    .players
+                    .iter()
+                    .find(|bot| bot.player() == player)
+                    .map_or(0, |bot| bot.report().total_think_ms),
+            },
+        )
+    }
+
+    /// Plays the next turn. Returns `None` without playing it, and ends the
+    /// game as `PiecesExhausted`, once the bags cannot deal a full turn.
+    pub fn next_move(&mut self) -> Option<PlayerResponse> {
+        let pieces: Vec<Piece> = match self.mirrors_turn() {
+            true => self
+                .last_dealt
+                .iter()
+                .map(Piece::rotated_half_turn)
+                .collect(),
+            false => match self.draw_pieces() {
+                Some(pieces) => pieces,
+                None => {
+                    self.termination = Some(Termination::PiecesExhausted);
+                    return None;
+                }
+            },
+        };
+        self.last_dealt = pieces.clone();
+
+        let seat = (self.move_count + self.first_seat) % self.player_count;
+        self.plateau.set_move_number(self.move_count);
+        let player_com = &mut self.players[seat];
+        let mut response = player_com.request_placement(
+            &mut self.plateau,
+            &pieces,
+            self.move_count,
+            &mut self.referees,
+        );
         self.move_count += 1;
+        response.timestamp = Utc::now().timestamp_millis();
+        self.after_move();
+        response.board_hash = Some(hash_hex(&self.plateau));
+        Some(response)
+    }
+
+    /// Deals the pieces of a turn, `None` once either bag has run out
+    fn draw_pieces(&mut self) -> Option<Vec<Piece>> {
+        let piece_bag = &mut self.piece_bag;
+        let move_count = self.move_count;
+        let seat = (move_count + self.first_seat) % self.player_count;
+        let mut player_bag = self.player_bags[self.players[seat].player() as usize].as_mut();
+        let pieces = (0..self.rules.pieces_per_turn)
+            .map(|_| {
+                // The shared bag counts the draw either way, so its limit
+                // holds for both players alike
+                let piece = piece_bag.next()?;
+                match player_bag.as_mut() {
+                    Some(bag) => bag.next(),
+                    None => Some(piece),
+                }
+            })
+            .collect::<Option<Vec<Piece>>>()?;
+        let pieces = match self.piece_filter.as_mut() {
+            Some(filter) => pieces
+                .into_iter()
+                .map(|piece| filter.filter(piece, move_count))
+                .collect(),
+            None => pieces,
+        };
+        Some(pieces)
+    }
+
+    /// Whether this turn replays the previous turn's pieces for Player2
+    fn mirrors_turn(&self) -> bool {
+        self.rules.mirror_pieces && self.player_count == 2 && self.move_count % 2 == 1
+    }
+
+    /// Drops the history once it reaches the cap, spilling it if asked to,
+    /// and starts the replay over from the current board
+    fn compact_history(&mut self) {
+        if self.history_cap.is_none_or(|cap| self.history.len() < cap) {
+            return;
+        }
+        for response in self.history.drain(..) {
+            if let Some(spill) = self.history_spill.as_mut() {
+                let written = serde_json::to_writer(&mut *spill, &response)
+                    .map_err(|e| e.to_string())
+                    .and_then(|_| writeln!(spill).map_err(|e| e.to_string()));
+                if let Err(e) = written {
+                    eprintln!("History spill: {}", e);
+                }
+            }
+            self.compacted.moves += 1;
+            if response.error.is_some() {
+                self.compacted.errors[response.player as usize] += 1;
+            }
+            self.compacted.last_timestamp = response.timestamp;
+        }
+        self.history_start = self.plateau.clone();
+    }
+
+    fn save_checkpoint(&self) {
+        if let Some((path, moves)) = &self.checkpoint {
+            if self.move_count.is_multiple_of(*moves) {
+                if let Err(e) = self.state().save(path) {
+                    eprintln!("Checkpoint {}: {}", path.display(), e);
+                }
+            }
+        }
+    }
+
+    /// Rule hooks applied once a move has been resolved
+    fn after_move(&mut self) {
+        self.rules.after_move(&mut self.plateau, self.move_count);
+    }
+
+    /// Resets the board, piece bag and history so the same bots can play
+    /// another game in a series. Fails if `blind_start` finds no fair start
+    /// points on the map.
+    pub fn next_game(&mut self) -> Result<(), String> {
+        self.plateau = match self.rules.blind_start {
+            true => self.initial_plateau.with_starts_moved()?,
+            false => self.initial_plateau.clone(),
+        };
+        self.rules.prepare(&mut self.plateau);
+        self.piece_bag.restart();
+        self.player_bags = self.rules.player_bags(self.piece_bag.seed());
+        self.move_count = 0;
+        self.last_dealt.clear();
+        self.game_start = self.plateau.clone();
+        self.history.clear();
+        self.compacted = CompactedHistory::default();
+        self.history_start = self.plateau.clone();
+        self.termination = None;
+        self.game_id = new_game_id();
+        self.started_at = 0;
+
+        for bot in self.players.iter_mut() {
+            bot.reset(self.persistent_bots)?;
+        }
+        Ok(())
+    }
+
+    /// Why the game ended, `None` while it is still running
+    pub fn termination(&self) -> Option<Termination> {
+        self.termination
+    }
+
+    /// Unique id of the current game, renewed by `next_game`
+    pub fn game_id(&self) -> &str {
+        &self.game_id
+    }
 
-        let piece = self.piece_bag.next();
-        player_com.request_placement(&mut self.plateau, &piece)
+    pub fn metadata(&self) -> &BTreeMap<String, String> {
+        &self.metadata
     }
 
-    pub fn plateau(&self) -> &Plateau {
-        &self.plateau
+    pub fn plateau(&self) -> PlateauView<'_> {
+        self.plateau.view()
     }
 
     pub fn placement_counts(&self) -> Vec<(Player, usize)> {
@@ -137,39 +943,289 @@ impl<'a> Engine<'a> {
             .collect()
     }
 
+    /// Each player's score under the configured `Scoring`, by default the
+    /// number of pieces it placed
+    pub fn scores(&self) -> Vec<(Player, usize)> {
+        match &self.scoring {
+            Some(scoring) => self
+                .players
+                .iter()
+                .map(|bot| {
+                    (
+                        bot.player(),
+                        scoring.score(self.plateau.view(), bot.player()),
+                    )
+                })
+                .collect(),
+            None => self.placement_counts(),
+        }
+    }
+
+    pub fn placement_count(&self, player: Player) -> usize {
+        self.players
+            .iter()
+            .find(|bot| bot.player() == player)
+            .map_or(0, Bot::placement_count)
+    }
+
+    /// Successful placements of the given player, in the order they were
+    /// made. Moves compacted out of the history are left out.
+    pub fn moves(&self, player: Player) -> Vec<Placement> {
+        self.history
+            .iter()
+            .enumerate()
+            .filter(|(_, response)| response.player == player && response.error.is_none())
+            .flat_map(|(move_number, response)| {
+                let move_number = self.compacted.moves + move_number;
+                response
+                    .placements()
+                    .into_iter()
+                    .map(move |(_, point)| Placement {
+                        player,
+                        move_number,
+                        point,
+                    })
+            })
+            .collect()
+    }
+
+    /// Per-bot diagnostics for the current game
+    pub fn bot_reports(&self) -> Vec<BotReport> {
+        self.players.iter().map(Bot::report).collect()
+    }
+
+    /// Moves still held in memory, see `EngineBuilder::with_history_cap`
+    pub fn history(&self) -> &[PlayerResponse] {
+        &self.history
+    }
+
+    /// Moves dropped from the history by `EngineBuilder::with_history_cap`
+    pub fn compacted_moves(&self) -> usize {
+        self.compacted.moves
+    }
+
+    pub fn state(&self) -> GameState {
+        GameState {
+            game_id: self.game_id.clone(),
+            move_count: self.move_count,
+            first_seat: self.first_seat,
+            plateau: self.plateau.clone(),
+            placement_counts: self.players.iter().map(Bot::placement_count).collect(),
+            clocks: self.players.iter().map(Bot::clock).collect(),
+            piece_bag: self.piece_bag.state(),
+            player_bags: [
+                self.player_bags[0].as_ref().map(PieceBag::state),
+                self.player_bags[1].as_ref().map(PieceBag::state),
+            ],
+            last_dealt: self.last_dealt.clone(),
+            started_at: self.started_at,
+            game_start: self.game_start.clone(),
+            history: self.history.clone(),
+            history_start: self.history_start.clone(),
+            compacted: self.compacted,
+        }
+    }
+
+    /// Puts the game back where `state` left it, see `EngineBuilder::resume`
+    fn restore(&mut self, state: GameState) {
+        for (i, bot) in self.players.iter_mut().enumerate() {
+            let placement_count = state.placement_counts.get(i).copied().unwrap_or(0);
+            let clock = state.clocks.get(i).copied().flatten();
+            bot.resume(placement_count, clock);
+        }
+        self.game_id = state.game_id;
+        self.move_count = state.move_count;
+        self.first_seat = state.first_seat;
+        self.plateau = state.plateau;
+        self.piece_bag = PieceBag::from_state(&state.piece_bag);
+        self.player_bags = [
+            state.player_bags[0].as_ref().map(PieceBag::from_state),
+            state.player_bags[1].as_ref().map(PieceBag::from_state),
+        ];
+        self.last_dealt = state.last_dealt;
+        self.started_at = state.started_at;
+        self.game_start = state.game_start;
+        self.history = state.history;
+        self.history_start = state.history_start;
+        self.compacted = state.compacted;
+    }
+
     pub fn player_names(&self) -> Vec<String> {
         self.players.iter().map(|bot| bot.name()).collect()
     }
 
+    /// The variant rules this game is played under
+    pub fn rules(&self) -> &RuleSet {
+        &self.rules
+    }
+
+    /// Seat, display name and colour of every player
+    pub fn identities(&self) -> &[Identity] {
+        &self.identities
+    }
+
+    /// Engine version and everything that changes how a game plays out, so a
+    /// replay can be checked against the rules it was played under
+    fn replay_header(&self) -> serde_json::Value {
+        let protocol: Vec<_> = self
+            .players
+            .iter()
+            .map(|bot| {
+                json!({
+                    "adapter": bot.adapter().to_string(),
+                    "clock_header": bot.clock_header(),
+                    "latency": bot.latency().map(|latency| latency.to_string()),
+                })
+            })
+            .collect();
+        let fog: Vec<Option<usize>> = self.players.iter().map(Bot::fog).collect();
+        let bag = self.piece_bag.state();
+        let mut rules = serde_json::to_value(&self.rules).unwrap();
+        rules["scoring"] = json!(self.scoring.as_ref().map(|scoring| scoring.rules()));
+        rules["fog"] = json!(fog);
+        json!({
+            "engine_version": env!("CARGO_PKG_VERSION"),
+            "protocol": protocol,
+            "rules": rules,
+            "piece_bag": {
+                "seed": bag.seed,
+                "width_range": bag.width_range,
+                "height_range": bag.height_range,
+                "limit": bag.limit,
+                "fixed": self.piece_bag.is_fixed(),
+            },
+            "map_hash": hash_hex(&self.game_start),
+        })
+    }
+
+    /// The game as JSON. Once the history has been compacted the replay
+    /// starts after the compacted moves, the way a slice does.
     pub fn replay(&self) -> String {
+        let mut metadata = self.metadata.clone();
+        let mut started_at = self.started_at;
+        if self.compacted.moves > 0 {
+            metadata.insert(String::from("first_move"), self.compacted.moves.to_string());
+            started_at = self.compacted.last_timestamp;
+        }
         json!({
+        "header": self.replay_header(),
+        "game_id": self.game_id,
+        "metadata": metadata,
+        "started_at": started_at,
         "players": &self.player_names(),
+        "identities": self.identities,
         "plateau": json!({
-            "width": self.plateau.width(),
-            "height": self.plateau.height(),
-            "player1_start": self.plateau.player_start(Player::Player1),
-            "player2_start": self.plateau.player_start(Player::Player2),
+            "width": self.history_start.width(),
+            "height": self.history_start.height(),
+            "player1_start": self.history_start.player_start(Player::Player1),
+            "player2_start": self.history_start.player_start(Player::Player2),
+            "rows": self.history_start.to_map_rows(),
         }),
-        "history": self.history
+        "history": self.history,
+        "termination": self.termination,
+        "outcome": self.termination.map(|_| self.outcome()),
         })
         .to_string()
     }
+
+    /// Plays a short game between two built-in bots on a tiny board and
+    /// checks that the placement counts, the board and the replay agree, to catch a
+    /// broken build or environment before a long run
+    pub fn selfcheck() -> Result<(), String> {
+        let mut builder = Engine::builder(native_bot::RANDOM);
+        builder.with_player2(native_bot::RANDOM);
+        builder.with_map("tiny")?;
+        let mut engine = builder.finish()?;
+        for _ in 0..SELFCHECK_MOVES {
+            let response = engine.next_move().ok_or("Piece bag exhausted")?;
+            engine.history.push(response);
+        }
+
+        let plateau = engine.plateau();
+        for (player, count) in engine.placement_counts() {
+            let placed = engine
+                .history
+                .iter()
+                .filter(|response| response.player == player && response.error.is_none())
+                .count();
+            if placed != count {
+                return Err(format!(
+                    "{} has {} placements but {} successful moves",
+                    player, count, placed
+                ));
+            }
+            let held = plateau
+                .cells()
+                .filter(|(_, owner)| *owner == Some(player))
+                .count();
+            if held != plateau.territory(player) {
+                return Err(format!(
+                    "{} holds {} cells but has {} territory",
+                    player,
+                    held,
+                    plateau.territory(player)
+                ));
+            }
+        }
+
+        let replay = Replay::from_json(&engine.replay())?;
+        if let Some(move_number) = replay.first_divergence()? {
+            return Err(format!("Replay diverges at move {}", move_number));
+        }
+        let replayed = replay.plateau_at(replay.history.len())?;
+        if replayed.to_string() != engine.plateau.to_string() {
+            return Err(String::from("Replay does not rebuild the final board"));
+        }
+        Ok(())
+    }
 }
 
-trait OnPlayerResponse {
-    fn on_player_move(&self, engine: &Engine, player_response: &PlayerResponse);
+/// Hook to replace or alter pieces before they are dealt to a player
+pub trait PieceFilter {
+    fn filter(&mut self, piece: Piece, move_no: usize) -> Piece;
+}
+
+/// What a `Referee` rules on a move
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Allow,
+    /// Commits the move with a note kept in its `PlayerResponse`
+    Annotate(String),
+    /// Rejects the move for the given reason, as if it were illegal
+    Veto(String),
+}
+
+/// Plugin that reviews every legal move before it is committed, to enforce
+/// house rules or flag suspicious play. `before` is the plateau the move was
+/// made on and `after` the plateau it would leave. Referees are asked in the
+/// order they were added until one vetoes.
+pub trait Referee {
+    fn review(
+        &mut self,
+        before: PlateauView,
+        after: PlateauView,
+        player_response: &PlayerResponse,
+    ) -> Verdict;
+}
+
+/// Observer notified after every move with a read-only view of the plateau
+pub trait OnPlayerResponse {
+    fn on_player_move(&self, plateau: PlateauView, player_response: &PlayerResponse);
+
+    /// Called once the game is over with the final plateau
+    fn on_game_end(&self, _plateau: PlateauView) {}
 }
 
 struct DefaultOnPlayerResponse;
 
 impl OnPlayerResponse for DefaultOnPlayerResponse {
-    fn on_player_move(&self, _: &Engine, _: &PlayerResponse) {}
+    fn on_player_move(&self, _: PlateauView, _: &PlayerResponse) {}
 }
 
 struct PrintOnPlayerResponse;
 
 impl OnPlayerResponse for PrintOnPlayerResponse {
-    fn on_player_move(&self, engine: &Engine, player_response: &PlayerResponse) {
+    fn on_player_move(&self, plateau: PlateauView, player_response: &PlayerResponse) {
         match &player_response.error {
             None => {
                 print!(
@@ -178,8 +1234,7 @@ impl OnPlayerResponse for PrintOnPlayerResponse {
                     player_response.raw_response.as_ref().unwrap()
                 );
                 print!("{}", player_response.piece);
-                print!("{}", engine.plateau());
-                ()
+                print!("{}", plateau);
             }
             Some(e) => {
                 println!("{}: {}", player_response.player, e);
@@ -187,3 +1242,354 @@ impl OnPlayerResponse for PrintOnPlayerResponse {
         }
     }
 }
+
+pub(crate) fn hash_hex(plateau: &Plateau) -> String {
+    format!("{:016x}", plateau.content_hash())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Rejection;
+    use crate::models::Point;
+    use std::convert::TryFrom;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn selfcheck_passes() {
+        assert_eq!(Engine::selfcheck(), Ok(()));
+    }
+
+    #[test]
+    fn game_ends_once_no_player_can_place() {
+        let mut builder = Engine::builder(native_bot::RANDOM);
+        builder.with_player2(native_bot::RANDOM);
+        builder.with_plateau(Plateau::try_from(String::from("O...X\n")).unwrap());
+        builder.with_piecebag(PieceBag::with_seed(1, [3, 5], [3, 5]));
+        let mut engine = builder.finish().unwrap();
+        let result = engine.run();
+
+        assert_eq!(result.termination, Some(Termination::NoMovesLeft));
+        assert_eq!(result.moves, 2);
+        assert_eq!(result.winner(), None);
+        assert_eq!(engine.history().len(), 2);
+    }
+
+    /// Vetoes Player2's moves and notes how many cells Player1's claim
+    struct OnlyPlayer1;
+
+    impl Referee for OnlyPlayer1 {
+        fn review(
+            &mut self,
+            before: PlateauView,
+            after: PlateauView,
+            response: &PlayerResponse,
+        ) -> Verdict {
+            match response.player {
+                Player::Player1 => {
+                    let claimed =
+                        after.territory(Player::Player1) - before.territory(Player::Player1);
+                    Verdict::Annotate(format!("claimed {}", claimed))
+                }
+                Player::Player2 => Verdict::Veto(String::from("Player2 may not move")),
+            }
+        }
+    }
+
+    #[test]
+    fn referees_veto_and_annotate_moves() {
+        let mut builder = Engine::builder(native_bot::RANDOM);
+        builder.with_player2(native_bot::RANDOM);
+        builder.with_map("tiny").unwrap();
+        builder.add_referee(Box::new(OnlyPlayer1));
+        let mut engine = builder.finish().unwrap();
+        let start = engine.plateau().territory(Player::Player2);
+
+        let first = engine.next_move().unwrap();
+        let second = engine.next_move().unwrap();
+        assert_eq!(first.notes.len(), 1);
+        assert_eq!(second.rejection, Some(Rejection::Vetoed));
+        assert_eq!(engine.plateau().territory(Player::Player2), start);
+    }
+
+    #[test]
+    fn builtin_bots_play_by_the_overlap_rule() {
+        let map = "OO......\nOO......\n........\n........\n......XX\n......XX\n";
+        let mut builder = Engine::builder(native_bot::GREEDY);
+        builder.with_player2(native_bot::RANDOM);
+        builder.with_plateau(Plateau::try_from(String::from(map)).unwrap());
+        builder.with_piecebag(PieceBag::with_seed(3, [3, 4], [3, 4]));
+        builder.with_overlap_rule(OverlapRule::Exactly(2));
+        let mut engine = builder.finish().unwrap();
+
+        let first = engine.next_move().unwrap();
+        assert_eq!(first.error, None);
+        assert_eq!(engine.placement_count(Player::Player1), 1);
+    }
+
+    #[test]
+    fn decay_counts_failed_turns_as_moves() {
+        let mut builder = Engine::builder(native_bot::RANDOM);
+        builder.with_player2(native_bot::RANDOM);
+        builder.with_map("tiny").unwrap();
+        builder.add_referee(Box::new(OnlyPlayer1));
+        builder.with_decay(1);
+        let mut engine = builder.finish().unwrap();
+
+        assert_eq!(engine.next_move().unwrap().error, None);
+        assert!(engine.plateau().territory(Player::Player1) > 0);
+        // Player2's vetoed turn is a move too, after which nothing is fresh
+        assert!(engine.next_move().unwrap().error.is_some());
+        assert_eq!(engine.plateau().territory(Player::Player1), 0);
+    }
+
+    #[test]
+    fn unplayable_piece_ranges_are_refused() {
+        let mut rules = RuleSet::default();
+        rules.piece_ranges[1] = Some(PieceRange {
+            width: [5, 5],
+            height: [3, 6],
+        });
+        let mut builder = Engine::builder(native_bot::RANDOM);
+        builder.with_rules(rules);
+        assert!(builder.finish().is_err());
+    }
+
+    #[test]
+    fn next_move_stops_once_the_bag_is_empty() {
+        let mut builder = Engine::builder(native_bot::RANDOM);
+        builder.with_player2(native_bot::RANDOM);
+        builder.with_map("tiny").unwrap();
+        builder.with_piecebag(PieceBag::finite(vec![Piece::new(1, 1, vec![true])]));
+        let mut engine = builder.finish().unwrap();
+
+        assert!(engine.next_move().is_some());
+        assert!(engine.next_move().is_none());
+        assert_eq!(engine.termination(), Some(Termination::PiecesExhausted));
+    }
+
+    #[test]
+    fn blind_starts_keep_the_map() {
+        let map = "O.#....\n.....#.\n.#.....\n....#.X\n";
+        let mut builder = Engine::builder(native_bot::RANDOM);
+        builder.with_player2(native_bot::RANDOM);
+        builder.with_plateau(Plateau::try_from(String::from(map)).unwrap());
+        builder.blind_start();
+        let mut engine = builder.finish().unwrap();
+        let blocked = |engine: &Engine| -> Vec<Point> {
+            let view = engine.plateau();
+            let cells = view.cells().map(|(point, _)| point);
+            cells.filter(|point| view.is_blocked(point)).collect()
+        };
+        let obstacles = vec![
+            Point::new(2, 0),
+            Point::new(5, 1),
+            Point::new(1, 2),
+            Point::new(4, 3),
+        ];
+        assert_eq!(blocked(&engine), obstacles);
+        engine.next_game().unwrap();
+        assert_eq!(blocked(&engine), obstacles);
+
+        let mut builder = Engine::builder(native_bot::RANDOM);
+        builder.with_plateau(Plateau::try_from(String::from("O..#\n#X.#\n")).unwrap());
+        builder.blind_start();
+        let problems = builder.preflight();
+        assert!(problems
+            .iter()
+            .any(|problem| problem.contains("fair start points")));
+        assert!(builder.finish().is_err());
+    }
+
+    #[test]
+    fn player_error_threshold_ends_the_game() {
+        let mut builder = Engine::builder(native_bot::RANDOM);
+        builder.with_player2(native_bot::RANDOM);
+        builder.with_map("tiny").unwrap();
+        builder.add_referee(Box::new(OnlyPlayer1));
+        builder.with_timeout(Duration::from_millis(500));
+        builder.with_player_error_threshold(Player::Player2, 0);
+        builder.with_metadata("round", "3");
+        let mut engine = builder.finish().unwrap();
+        let result = engine.run();
+
+        assert_eq!(result.termination, Some(Termination::ErrorThreshold));
+        // The vetoed move that ended the game is counted and recorded
+        assert_eq!(result.moves, 2);
+        assert_eq!(engine.history()[1].rejection, Some(Rejection::Vetoed));
+        assert_eq!(result.game_id, engine.game_id());
+        assert_eq!(result.metadata["round"], "3");
+        assert_eq!(result.reports.len(), 2);
+        assert_eq!(result.reports[1].invalid_moves["Vetoed"], 1);
+    }
+
+    /// Notes every piece it is shown and deals a single cell instead
+    struct SingleCells(Arc<Mutex<Vec<(Piece, usize)>>>);
+
+    impl PieceFilter for SingleCells {
+        fn filter(&mut self, piece: Piece, move_no: usize) -> Piece {
+            self.0.lock().unwrap().push((piece, move_no));
+            Piece::new(1, 1, vec![true])
+        }
+    }
+
+    #[test]
+    fn piece_filter_replaces_every_dealt_piece() {
+        let seen = Arc::new(Mutex::new(vec![]));
+        let mut builder = Engine::builder(native_bot::RANDOM);
+        builder.with_player2(native_bot::RANDOM);
+        builder.with_map("tiny").unwrap();
+        builder.with_piecebag(PieceBag::with_seed(5, [3, 4], [3, 4]));
+        builder.with_piece_filter(Box::new(SingleCells(Arc::clone(&seen))));
+        let mut engine = builder.finish().unwrap();
+        for _ in 0..4 {
+            engine.next_move();
+        }
+
+        let mut bag = PieceBag::with_seed(5, [3, 4], [3, 4]);
+        let dealt: Vec<(Piece, usize)> = (0..4).map(|i| (bag.next().unwrap(), i)).collect();
+        assert_eq!(*seen.lock().unwrap(), dealt);
+        for response in engine.history() {
+            assert_eq!(response.piece, Piece::new(1, 1, vec![true]));
+        }
+    }
+
+    /// Vetoes every move
+    struct VetoAll;
+
+    impl Referee for VetoAll {
+        fn review(&mut self, _: PlateauView, _: PlateauView, _: &PlayerResponse) -> Verdict {
+            Verdict::Veto(String::from("no moves today"))
+        }
+    }
+
+    #[test]
+    fn watchdog_ends_a_game_nobody_places_in() {
+        let mut builder = Engine::builder(native_bot::RANDOM);
+        builder.with_player2(native_bot::RANDOM);
+        builder.with_map("tiny").unwrap();
+        builder.add_referee(Box::new(VetoAll));
+        builder.with_watchdog(5);
+        let mut engine = builder.finish().unwrap();
+        let result = engine.run();
+
+        assert_eq!(result.termination, Some(Termination::Stalled));
+        assert_eq!(result.moves, 10);
+    }
+
+    #[test]
+    fn a_game_nobody_could_play_is_no_contest() {
+        let mut builder = Engine::builder(native_bot::RANDOM);
+        builder.with_player2(native_bot::RANDOM);
+        builder.with_map("tiny").unwrap();
+        builder.add_referee(Box::new(VetoAll));
+        let mut engine = builder.finish().unwrap();
+        let result = engine.run();
+
+        assert_eq!(result.termination, Some(Termination::ErrorThreshold));
+        assert_eq!(result.outcome, Outcome::NoContest);
+        assert_eq!(result.outcome.exit_code(), 4);
+        assert_eq!(result.winner(), None);
+    }
+
+    #[test]
+    fn handicapped_player_gets_pieces_of_its_range() {
+        let mut builder = Engine::builder(native_bot::RANDOM);
+        builder.with_player2(native_bot::RANDOM);
+        builder.with_map("tiny").unwrap();
+        builder.with_piecebag(PieceBag::with_seed(3, [4, 6], [4, 6]).with_limit(10));
+        builder.with_player_piece_range(Player::Player2, [3, 4], [3, 4]);
+        let mut engine = builder.finish().unwrap();
+        engine.run();
+
+        assert_eq!(engine.history().len(), 10);
+        for response in engine.history() {
+            let side = response.piece.width().min(response.piece.height());
+            match response.player {
+                Player::Player1 => assert!(side >= 4),
+                Player::Player2 => {
+                    assert_eq!((response.piece.width(), response.piece.height()), (3, 3))
+                }
+            }
+        }
+        let replay = Replay::from_json(&engine.replay()).unwrap();
+        assert!(replay.rules().unwrap().piece_ranges[Player::Player2 as usize].is_some());
+    }
+
+    #[test]
+    fn capped_history_spills_and_replays_from_the_cut() {
+        let spill = std::env::temp_dir().join(format!("filler_spill_{}.jsonl", std::process::id()));
+        let mut builder = Engine::builder(native_bot::RANDOM);
+        builder.with_player2(native_bot::RANDOM);
+        builder.with_map("tiny").unwrap();
+        builder.with_piecebag(PieceBag::with_seed(5, [3, 4], [3, 4]).with_limit(10));
+        builder.with_history_cap(4);
+        builder.with_history_spill(&spill).unwrap();
+        let mut engine = builder.finish().unwrap();
+        let result = engine.run();
+        let spilled = std::fs::read_to_string(&spill).unwrap();
+        std::fs::remove_file(&spill).unwrap();
+
+        assert_eq!(result.moves, 10);
+        assert_eq!(engine.compacted_moves(), 8);
+        assert_eq!(engine.history().len(), 2);
+        assert_eq!(spilled.lines().count(), 8);
+        let replay = Replay::from_json(&engine.replay()).unwrap();
+        assert_eq!(replay.first_divergence(), Ok(None));
+        assert_eq!(
+            replay.plateau_at(2).unwrap().to_map_rows(),
+            engine.plateau().to_plateau().to_map_rows()
+        );
+    }
+
+    #[test]
+    fn checkpoints_resume_to_the_same_game() {
+        let checkpoint =
+            std::env::temp_dir().join(format!("filler_resume_{}.json", std::process::id()));
+        let build = || {
+            let mut builder = Engine::builder(native_bot::GREEDY);
+            builder.with_player2(native_bot::GREEDY);
+            builder
+                .with_plateau(Plateau::new(12, 12, &Point::new(2, 2), &Point::new(9, 9)).unwrap());
+            builder.with_piecebag(PieceBag::with_seed(7, [3, 5], [3, 5]).with_limit(12));
+            builder.first_to_move(Player::Player2);
+            builder.with_decay(6);
+            builder
+        };
+        let mut builder = build();
+        builder.with_checkpoint(checkpoint.clone(), 5);
+        let mut uninterrupted = builder.finish().unwrap();
+        let expected = uninterrupted.run();
+
+        let state = GameState::load(&checkpoint).unwrap();
+        std::fs::remove_file(&checkpoint).unwrap();
+        assert_eq!(state.move_count, 10);
+        let mut builder = build();
+        builder.resume(state);
+        let mut resumed = builder.finish().unwrap();
+        let result = resumed.run();
+
+        assert_eq!(resumed.game_id(), uninterrupted.game_id());
+        assert_eq!(result.moves, expected.moves);
+        assert_eq!(result.placement_counts, expected.placement_counts);
+        assert_eq!(
+            resumed.plateau().to_plateau().to_map_rows(),
+            uninterrupted.plateau().to_plateau().to_map_rows()
+        );
+        assert_eq!(resumed.history().len(), uninterrupted.history().len());
+    }
+
+    #[test]
+    fn game_ends_once_the_balance_stagnates() {
+        let mut builder = Engine::builder(native_bot::RANDOM);
+        builder.with_player2(native_bot::RANDOM);
+        builder.with_map("tiny").unwrap();
+        // Single cells only ever cover their own start, claiming nothing
+        builder.with_piecebag(PieceBag::finite(vec![Piece::new(1, 1, vec![true]); 20]));
+        builder.with_stagnation_limit(5);
+        let result = builder.finish().unwrap().run();
+
+        assert_eq!(result.termination, Some(Termination::Stagnation));
+        assert_eq!(result.moves, 7);
+    }
+}
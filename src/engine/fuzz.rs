@@ -0,0 +1,203 @@
+use super::{prelude, Bot, Prelude, Rejection, Sandbox};
+use crate::models::constants::*;
+use crate::models::{Piece, Plateau, Player};
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use std::convert::TryFrom;
+use std::fmt;
+use std::thread;
+use std::time::Duration;
+
+/// Largest plateau side generated while fuzzing
+const MAX_SIDE: usize = 99;
+/// Largest piece side generated while fuzzing
+const MAX_PIECE_SIDE: usize = 9;
+/// Fuzzing uses a short timeout so hangs are found quickly
+const FUZZ_TIMEOUT: Duration = Duration::from_secs(1);
+/// Number of problem descriptions kept in a report
+const MAX_SAMPLES: usize = 10;
+
+/// Outcome of throwing random frames at a bot
+#[derive(Debug, Default)]
+pub struct FuzzReport {
+    pub rounds: usize,
+    pub crashes: usize,
+    pub malformed: usize,
+    pub hangs: usize,
+    /// Well formed answers that were not legal placements
+    pub invalid_placements: usize,
+    pub samples: Vec<String>,
+}
+
+impl FuzzReport {
+    pub fn is_clean(&self) -> bool {
+        self.crashes == 0 && self.malformed == 0 && self.hangs == 0
+    }
+}
+
+impl fmt::Display for FuzzReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Rounds:             {}", self.rounds)?;
+        writeln!(f, "Crashes:            {}", self.crashes)?;
+        writeln!(f, "Malformed replies:  {}", self.malformed)?;
+        writeln!(f, "Hangs:              {}", self.hangs)?;
+        writeln!(f, "Invalid placements: {}", self.invalid_placements)?;
+        for sample in self.samples.iter() {
+            writeln!(f, "  {}", sample)?;
+        }
+        Ok(())
+    }
+}
+
+/// Sends `rounds` random plateaus and pieces to the bot at `path`, including
+/// odd board sizes, 1xN pieces and irregular pacing, and reports every crash,
+/// malformed reply and hang. A crashed bot is respawned for the next round.
+pub fn fuzz_bot(path: &str, rounds: usize, seed: u64) -> Result<FuzzReport, String> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut report = FuzzReport::default();
+    let name = prelude::default_name(path);
+    let prelude = Prelude::default().render(Player::Player1, path, &name, "");
+    let mut bot = Bot::new(
+        path,
+        name,
+        FUZZ_TIMEOUT,
+        Player::Player1,
+        prelude,
+        Sandbox::default(),
+    )?;
+
+    for round in 0..rounds {
+        let mut plateau = random_plateau(&mut rng);
+        let piece = random_piece(&mut rng);
+
+        if rng.gen_ratio(1, 10) {
+            let pause = rng.gen_range(0, FUZZ_TIMEOUT.as_millis() as u64 / 2);
+            thread::sleep(Duration::from_millis(pause));
+        }
+
+        let response = bot.request_placement(&mut plateau, &[piece], round, &mut []);
+        report.rounds += 1;
+        let rejection = match response.rejection {
+            Some(rejection) => rejection,
+            None => continue,
+        };
+        match rejection {
+            Rejection::BotCrashed => report.crashes += 1,
+            Rejection::Malformed => report.malformed += 1,
+            Rejection::Timeout => report.hangs += 1,
+            _ => {
+                report.invalid_placements += 1;
+                continue;
+            }
+        }
+        if report.samples.len() < MAX_SAMPLES {
+            report.samples.push(format!(
+                "round {} ({}x{}): {:?} {}",
+                round,
+                plateau.width(),
+                plateau.height(),
+                rejection,
+                response.error.unwrap_or_default().trim_end()
+            ));
+        }
+        if rejection != Rejection::Malformed {
+            bot.reset(false)?;
+        }
+    }
+    bot.end_game(&[], false);
+
+    Ok(report)
+}
+
+fn random_plateau<R: Rng>(rng: &mut R) -> Plateau {
+    loop {
+        let width = rng.gen_range(1, MAX_SIDE + 1);
+        let height = rng.gen_range(1, MAX_SIDE + 1);
+        let density = rng.gen_range(0.0, 0.5);
+
+        let mut rows = String::new();
+        for _ in 0..height {
+            for _ in 0..width {
+                let c = match rng.gen_bool(density) {
+                    true if rng.gen() => PLAYER1,
+                    true => PLAYER2,
+                    false => EMPTY,
+                };
+                rows.push(c);
+            }
+            rows.push('\n');
+        }
+        // Maps without both players are rejected, just roll again
+        if let Ok(plateau) = Plateau::try_from(rows) {
+            return plateau;
+        }
+    }
+}
+
+fn random_piece<R: Rng>(rng: &mut R) -> Piece {
+    let (width, height) = match rng.gen_range(0, 3) {
+        0 => (1, rng.gen_range(1, MAX_PIECE_SIDE + 1)),
+        1 => (rng.gen_range(1, MAX_PIECE_SIDE + 1), 1),
+        _ => (
+            rng.gen_range(1, MAX_PIECE_SIDE + 1),
+            rng.gen_range(1, MAX_PIECE_SIDE + 1),
+        ),
+    };
+    let mut cells: Vec<bool> = (0..width * height).map(|_| rng.gen()).collect();
+    let filled = rng.gen_range(0, cells.len());
+    cells[filled] = true;
+    Piece::new(width, height, cells)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::process::Command;
+
+    #[test]
+    fn generates_boards_and_pieces_in_bounds() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..50 {
+            let plateau = random_plateau(&mut rng);
+            assert!((1..=MAX_SIDE).contains(&plateau.width()));
+            assert!((1..=MAX_SIDE).contains(&plateau.height()));
+            assert!(plateau.view().territory(Player::Player1) > 0);
+            assert!(plateau.view().territory(Player::Player2) > 0);
+
+            let piece = random_piece(&mut rng);
+            assert!((1..=MAX_PIECE_SIDE).contains(&piece.width()));
+            assert!((1..=MAX_PIECE_SIDE).contains(&piece.height()));
+            assert!(piece.filled_points().next().is_some());
+        }
+    }
+
+    #[test]
+    fn hung_bots_are_killed_before_the_next_round() {
+        if !cfg!(unix) {
+            return;
+        }
+        let dir = env::temp_dir().join(format!("filler_fuzz_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let pids = dir.join("pids");
+        let script = dir.join("hung.sh");
+        let body = format!("#!/bin/sh\necho $$ >> {}\nexec sleep 600\n", pids.display());
+        fs::write(&script, body).unwrap();
+        Command::new("chmod")
+            .arg("+x")
+            .arg(&script)
+            .status()
+            .unwrap();
+
+        let report = fuzz_bot(&script.to_string_lossy(), 2, 1).unwrap();
+        assert_eq!(report.hangs, 2);
+        let pids = fs::read_to_string(&pids).unwrap();
+        assert_eq!(pids.lines().count(), 3);
+        for pid in pids.lines() {
+            let alive = Command::new("kill").args(["-0", pid]).status().unwrap();
+            assert!(!alive.success(), "bot {} is still running", pid);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
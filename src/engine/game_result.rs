@@ -0,0 +1,35 @@
+use super::{BotReport, Outcome, Termination};
+use crate::models::Player;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Summary of a finished game, returned by `Engine::run`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameResult {
+    pub game_id: String,
+    /// See `EngineBuilder::with_metadata`
+    pub metadata: BTreeMap<String, String>,
+    pub outcome: Outcome,
+    /// Each player's score under the configured `Scoring`
+    pub scores: Vec<(Player, usize)>,
+    /// Pieces each player placed
+    pub placement_counts: Vec<(Player, usize)>,
+    /// Moves played, rejected ones included
+    pub moves: usize,
+    /// Why the game ended, `None` if it ended without a recorded reason
+    pub termination: Option<Termination>,
+    /// Wall-clock time the game took
+    pub duration: Duration,
+    /// Diagnostics about each bot, taken once they were shut down
+    pub reports: Vec<BotReport>,
+}
+
+impl GameResult {
+    /// The winning player, `None` for a draw or no contest
+    pub fn winner(&self) -> Option<Player> {
+        match self.outcome {
+            Outcome::Win { winner, .. } => Some(winner),
+            Outcome::Draw | Outcome::NoContest => None,
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use super::PlayerResponse;
+use crate::models::{Piece, PieceBagState, Plateau};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+/// Serializable snapshot of a game in progress, enough to resume it with
+/// `EngineBuilder::resume` under the same rules
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GameState {
+    pub game_id: String,
+    pub move_count: usize,
+    /// Seat of the player who moved first
+    pub first_seat: usize,
+    /// Board with its cell stamps, hills and last placement
+    pub plateau: Plateau,
+    pub placement_counts: Vec<usize>,
+    /// Game time each player has left, if they are on a clock
+    pub clocks: Vec<Option<Duration>>,
+    pub piece_bag: PieceBagState,
+    /// Bags of the players dealt pieces of their own size
+    pub player_bags: [Option<PieceBagState>; 2],
+    /// Pieces dealt on the last turn, which a mirrored turn deals again
+    pub last_dealt: Vec<Piece>,
+    /// Wall-clock time in milliseconds since the epoch the game started at
+    pub started_at: i64,
+    /// Board the game started on
+    pub game_start: Plateau,
+    /// Moves still in memory, and the board before the first of them
+    pub history: Vec<PlayerResponse>,
+    pub history_start: Plateau,
+    pub compacted: CompactedHistory,
+}
+
+/// What is kept of the moves compacted out of an engine's history
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompactedHistory {
+    pub moves: usize,
+    /// Failed turns of each player
+    pub errors: [usize; 2],
+    pub last_timestamp: i64,
+}
+
+impl GameState {
+    /// Writes the state as JSON, going through a temporary file so a crash
+    /// mid-write never leaves a truncated checkpoint behind
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_string(self)?)?;
+        fs::rename(tmp, path)
+    }
+
+    /// Reads a checkpoint written by `save`
+    pub fn load(path: &Path) -> Result<GameState, String> {
+        let json = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        serde_json::from_str(&json).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{PieceBag, Point};
+    use std::env;
+
+    #[test]
+    fn save_round_trips() {
+        let plateau = Plateau::new(3, 1, &Point::new(0, 0), &Point::new(2, 0)).unwrap();
+        let state = GameState {
+            game_id: String::from("id"),
+            move_count: 4,
+            first_seat: 1,
+            plateau: plateau.clone(),
+            placement_counts: vec![2, 2],
+            clocks: vec![Some(Duration::from_millis(1500)), None],
+            piece_bag: PieceBag::finite(vec![Piece::new(1, 1, vec![true])]).state(),
+            player_bags: [None, Some(PieceBag::default().state())],
+            last_dealt: vec![],
+            started_at: 1000,
+            game_start: plateau.clone(),
+            history: vec![],
+            history_start: plateau,
+            compacted: CompactedHistory::default(),
+        };
+        let path = env::temp_dir().join(format!("filler_checkpoint_{}.json", std::process::id()));
+        state.save(&path).unwrap();
+
+        let loaded = GameState::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.move_count, 4);
+        assert_eq!(loaded.first_seat, 1);
+        assert_eq!(loaded.clocks, state.clocks);
+        assert_eq!(loaded.piece_bag, state.piece_bag);
+        assert_eq!(loaded.player_bags, state.player_bags);
+        assert_eq!(loaded.plateau.to_map_rows(), vec!["O.X"]);
+    }
+}
@@ -0,0 +1,42 @@
+use crate::models::Plateau;
+use serde::{Deserialize, Serialize};
+
+/// Rule under which the board expands by one ring of empty cells every
+/// `every` moves, up to `max_rings` rings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Growth {
+    pub every: usize,
+    pub max_rings: usize,
+}
+
+impl Growth {
+    /// Grows `plateau` if the move that brought the game to `moves_played`
+    /// moves is due a new ring
+    pub fn apply(&self, plateau: &mut Plateau, moves_played: usize) {
+        if self.every > 0
+            && moves_played.is_multiple_of(self.every)
+            && moves_played / self.every <= self.max_rings
+        {
+            plateau.grow_ring();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stops_after_max_rings() {
+        let growth = Growth {
+            every: 2,
+            max_rings: 2,
+        };
+        let mut plateau = Plateau::default();
+        let width = plateau.width();
+        for moves_played in 1..=10 {
+            growth.apply(&mut plateau, moves_played);
+        }
+        assert_eq!(plateau.width(), width + 4);
+    }
+}
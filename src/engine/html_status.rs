@@ -0,0 +1,118 @@
+use super::render::{self, Frame};
+use super::{OnPlayerResponse, PlayerResponse};
+use crate::models::identity::DEFAULT_COLORS;
+use crate::models::{PlateauView, Player};
+use std::cell::Cell;
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Seconds between browser reloads of the status page
+const REFRESH_SECONDS: usize = 2;
+
+/// Writes the board and scores to a static, self-refreshing HTML page every
+/// `every` moves, so any plain web server can show the game in progress
+pub struct HtmlStatus {
+    path: PathBuf,
+    every: usize,
+    moves: Cell<usize>,
+    colors: [String; 2],
+}
+
+impl HtmlStatus {
+    pub fn new(path: PathBuf, every: usize) -> Self {
+        HtmlStatus {
+            path,
+            every: every.max(1),
+            moves: Cell::new(0),
+            colors: [
+                String::from(DEFAULT_COLORS[0]),
+                String::from(DEFAULT_COLORS[1]),
+            ],
+        }
+    }
+
+    /// Draws `player` in `color` rather than its default colour
+    pub fn with_color(mut self, player: Player, color: &str) -> Self {
+        self.colors[player as usize] = String::from(color);
+        self
+    }
+
+    fn write(&self, plateau: PlateauView, finished: bool) {
+        let html = render(plateau, self.moves.get(), finished, &self.colors);
+        if let Err(e) = save(&self.path, &html) {
+            eprintln!("{}: {}", self.path.display(), e);
+        }
+    }
+}
+
+impl OnPlayerResponse for HtmlStatus {
+    fn on_player_move(&self, plateau: PlateauView, _: &PlayerResponse) {
+        let moves = self.moves.get() + 1;
+        self.moves.set(moves);
+        if moves.is_multiple_of(self.every) {
+            self.write(plateau, false);
+        }
+    }
+
+    fn on_game_end(&self, plateau: PlateauView) {
+        self.write(plateau, true);
+    }
+}
+
+/// Replaces the page in one step so the server never serves half of it
+fn save(path: &Path, html: &str) -> io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, html)?;
+    fs::rename(tmp, path)
+}
+
+/// Renders the plateau as a table of coloured cells under the scores. A
+/// finished game's page stops refreshing.
+pub fn render(plateau: PlateauView, moves: usize, finished: bool, colors: &[String; 2]) -> String {
+    let mut html = String::from("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    if !finished {
+        let _ = writeln!(
+            html,
+            "<meta http-equiv=\"refresh\" content=\"{}\">",
+            REFRESH_SECONDS
+        );
+    }
+    html += "<title>Filler</title>\n<style>\n";
+    html += &render::css(colors);
+    html += "</style>\n</head>\n<body>\n";
+
+    let status = match finished {
+        true => "Game over",
+        false => "In progress",
+    };
+    let _ = writeln!(html, "<p>{} after {} moves</p>", status, moves);
+    for player in [Player::Player1, Player::Player2].iter() {
+        let _ = writeln!(html, "<p>{}: {}</p>", player, plateau.territory(*player));
+    }
+
+    html += &Frame::draw(plateau).to_html();
+    html += "</body>\n</html>\n";
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Plateau;
+
+    #[test]
+    fn finished_page_stops_refreshing() {
+        let plateau = Plateau::default();
+        let colors = [String::from("red"), String::from("blue")];
+        let live = render(plateau.view(), 3, false, &colors);
+        let done = render(plateau.view(), 3, true, &colors);
+
+        assert!(live.contains("http-equiv=\"refresh\""));
+        assert!(!done.contains("http-equiv=\"refresh\""));
+        assert_eq!(done.matches("<tr>").count(), plateau.height());
+        assert_eq!(done.matches("class=\"p1\"").count(), 1);
+        assert!(done.contains("td.p2 { background: blue; }"));
+    }
+}
@@ -0,0 +1,94 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Interpreters bots are run with by default, by file extension
+pub const DEFAULT_INTERPRETERS: [(&str, &[&str]); 3] = [
+    ("py", &["python3"]),
+    ("js", &["node"]),
+    ("jar", &["java", "-jar"]),
+];
+
+/// Commands that scripts are started with, chosen by the script's file
+/// extension, so a bot can be handed in as `bot.py` without a wrapper.
+/// Files with other extensions are run directly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interpreters {
+    commands: BTreeMap<String, Vec<String>>,
+}
+
+impl Default for Interpreters {
+    fn default() -> Self {
+        let commands = DEFAULT_INTERPRETERS
+            .iter()
+            .map(|(extension, command)| {
+                let command = command.iter().map(|word| word.to_string()).collect();
+                (extension.to_string(), command)
+            })
+            .collect();
+        Interpreters { commands }
+    }
+}
+
+impl Interpreters {
+    /// No interpreters, every bot is run directly
+    pub fn none() -> Interpreters {
+        Interpreters {
+            commands: BTreeMap::new(),
+        }
+    }
+
+    /// Runs files ending in `extension` with `command`, or directly if
+    /// `command` is empty
+    pub fn set(&mut self, extension: &str, command: Vec<String>) {
+        let extension = extension.trim_start_matches('.').to_string();
+        match command.is_empty() {
+            true => self.commands.remove(&extension),
+            false => self.commands.insert(extension, command),
+        };
+    }
+
+    /// Command and leading arguments the bot at `path` is run with, if it
+    /// needs an interpreter
+    pub fn command_for(&self, path: &Path) -> Option<&[String]> {
+        let extension = path.extension()?.to_str()?;
+        self.commands.get(extension).map(Vec::as_slice)
+    }
+
+    /// Parses `EXT=COMMAND`, with the command's words separated by spaces
+    pub fn parse_mapping(mapping: &str) -> Result<(String, Vec<String>), String> {
+        let (extension, command) = mapping
+            .split_once('=')
+            .ok_or_else(|| String::from("Expected EXT=COMMAND"))?;
+        let extension = extension.trim().trim_start_matches('.');
+        if extension.is_empty() {
+            return Err(String::from("Missing file extension"));
+        }
+        let command = command.split_whitespace().map(String::from).collect();
+        Ok((extension.to_string(), command))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_the_interpreter_by_extension() {
+        let mut interpreters = Interpreters::default();
+        assert_eq!(
+            interpreters.command_for(Path::new("bots/bot.jar")).unwrap(),
+            ["java", "-jar"]
+        );
+        assert!(interpreters.command_for(Path::new("bot.filler")).is_none());
+
+        let (extension, command) = Interpreters::parse_mapping(".py=pypy3 -u").unwrap();
+        interpreters.set(&extension, command);
+        assert_eq!(
+            interpreters.command_for(Path::new("bot.py")).unwrap(),
+            ["pypy3", "-u"]
+        );
+        interpreters.set("js", vec![]);
+        assert!(interpreters.command_for(Path::new("bot.js")).is_none());
+        assert!(Interpreters::parse_mapping("=node").is_err());
+    }
+}
@@ -0,0 +1,79 @@
+use crate::models::{Piece, Plateau, Player};
+use serde_json::json;
+use std::time::Duration;
+
+/// Line a bot prints to ask for JSON frames from the next turn on
+pub const REQUEST_LINE: &str = "$$$ frames json";
+
+/// One turn as a single line of JSON, for bots that would rather not parse
+/// the ASCII blocks. Board rows use the same characters as the plain frame.
+pub fn render(
+    plateau: &Plateau,
+    pieces: &[Piece],
+    player: Player,
+    budget: Duration,
+    clock: Option<Duration>,
+) -> String {
+    let board: Vec<String> = plateau
+        .render_protocol()
+        .lines()
+        .skip(1)
+        .map(String::from)
+        .collect();
+    let pieces: Vec<_> = pieces
+        .iter()
+        .map(|piece| {
+            let rows: Vec<String> = piece
+                .to_string()
+                .lines()
+                .skip(1)
+                .map(String::from)
+                .collect();
+            json!({ "width": piece.width(), "height": piece.height(), "rows": rows })
+        })
+        .collect();
+
+    let mut frame = json!({
+        "player": player as usize + 1,
+        "move": plateau.move_number(),
+        "width": plateau.width(),
+        "height": plateau.height(),
+        "board": board,
+        "pieces": pieces,
+        "clock": {
+            "move_ms": budget.as_millis() as u64,
+            "total_ms": clock.map(|clock| clock.as_millis() as u64),
+        },
+    })
+    .to_string();
+    frame.push('\n');
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Point;
+    use serde_json::Value;
+
+    #[test]
+    fn frame_is_one_json_line() {
+        let plateau = Plateau::new(3, 2, &Point::new(0, 0), &Point::new(2, 1)).unwrap();
+        let piece = Piece::new(2, 1, vec![true, false]);
+        let frame = render(
+            &plateau,
+            &[piece],
+            Player::Player2,
+            Duration::from_secs(2),
+            None,
+        );
+        assert_eq!(frame.lines().count(), 1);
+
+        let frame: Value = serde_json::from_str(&frame).unwrap();
+        assert_eq!(frame["player"], 2);
+        assert_eq!(frame["board"], json!(["O..", "..X"]));
+        assert_eq!(frame["pieces"][0]["rows"], json!(["*."]));
+        assert_eq!(frame["clock"]["move_ms"], 2000);
+        assert!(frame["clock"]["total_ms"].is_null());
+    }
+}
@@ -0,0 +1,148 @@
+use super::{OnPlayerResponse, PlayerResponse};
+use crate::models::{PlateauView, Player};
+use serde_json::{json, Value};
+use std::cell::{Cell, RefCell};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+const SEATS: [Player; 2] = [Player::Player1, Player::Player2];
+
+/// Writes one JSON object per move, then a summary object, to a JSON Lines
+/// file. Unlike a replay every line stands on its own, with the move's
+/// timing and the territory after it, for ad-hoc analysis with jq or DuckDB.
+pub struct JsonLog {
+    out: RefCell<BufWriter<File>>,
+    moves: Cell<usize>,
+    errors: Cell<[usize; 2]>,
+    first_timestamp: Cell<Option<i64>>,
+    last_timestamp: Cell<Option<i64>>,
+}
+
+impl JsonLog {
+    pub fn create(path: &Path) -> io::Result<JsonLog> {
+        Ok(JsonLog {
+            out: RefCell::new(BufWriter::new(File::create(path)?)),
+            moves: Cell::new(0),
+            errors: Cell::new([0, 0]),
+            first_timestamp: Cell::new(None),
+            last_timestamp: Cell::new(None),
+        })
+    }
+
+    fn write(&self, line: Value) {
+        let mut out = self.out.borrow_mut();
+        if let Err(e) = writeln!(out, "{}", line) {
+            eprintln!("JSON log: {}", e);
+        }
+    }
+}
+
+/// Cells held by each player, in seat order
+fn territory(plateau: PlateauView) -> Vec<usize> {
+    SEATS
+        .iter()
+        .map(|player| plateau.territory(*player))
+        .collect()
+}
+
+impl OnPlayerResponse for JsonLog {
+    fn on_player_move(&self, plateau: PlateauView, response: &PlayerResponse) {
+        let move_number = self.moves.get();
+        self.moves.set(move_number + 1);
+        if response.error.is_some() {
+            let mut errors = self.errors.get();
+            errors[response.player as usize] += 1;
+            self.errors.set(errors);
+        }
+        let elapsed_ms = self
+            .last_timestamp
+            .get()
+            .map(|last| (response.timestamp - last).max(0));
+        if self.first_timestamp.get().is_none() {
+            self.first_timestamp.set(Some(response.timestamp));
+        }
+        self.last_timestamp.set(Some(response.timestamp));
+
+        self.write(json!({
+            "type": "move",
+            "move": move_number,
+            "player": response.player,
+            "name": response.name,
+            "piece": [response.piece.width(), response.piece.height()],
+            "pieces": 1 + response.batch.len(),
+            "placement": response.placement,
+            "batch_placements": response.batch_placements,
+            "error": response.error,
+            "rejection": response.rejection,
+            "timestamp": response.timestamp,
+            "elapsed_ms": elapsed_ms,
+            "territory": territory(plateau),
+        }));
+    }
+
+    fn on_game_end(&self, plateau: PlateauView) {
+        let duration_ms = match (self.first_timestamp.get(), self.last_timestamp.get()) {
+            (Some(first), Some(last)) => Some(last - first),
+            _ => None,
+        };
+        self.write(json!({
+            "type": "summary",
+            "moves": self.moves.get(),
+            "errors": self.errors.get(),
+            "duration_ms": duration_ms,
+            "territory": territory(plateau),
+        }));
+        if let Err(e) = self.out.borrow_mut().flush() {
+            eprintln!("JSON log: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Piece, Plateau};
+    use std::{env, fs};
+
+    #[test]
+    fn writes_a_line_per_move_and_a_summary() {
+        let path = env::temp_dir().join(format!("filler_log_{}.jsonl", std::process::id()));
+        let log = JsonLog::create(&path).unwrap();
+        let plateau = Plateau::default();
+        let mut response = PlayerResponse {
+            player: Player::Player2,
+            piece: Piece::new_blank(2, 3),
+            batch: vec![],
+            raw_response: None,
+            placement: None,
+            batch_placements: vec![],
+            placement_count: 0,
+            error: Some(String::from("Timeout")),
+            rejection: None,
+            timestamp: 1000,
+            board_hash: None,
+            name: None,
+            notes: vec![],
+        };
+        log.on_player_move(plateau.view(), &response);
+        response.timestamp = 1250;
+        log.on_player_move(plateau.view(), &response);
+        log.on_game_end(plateau.view());
+
+        let lines: Vec<Value> = fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["elapsed_ms"], Value::Null);
+        assert_eq!(lines[1]["elapsed_ms"], 250);
+        assert_eq!(lines[1]["piece"], json!([2, 3]));
+        assert_eq!(lines[2]["type"], "summary");
+        assert_eq!(lines[2]["errors"], json!([0, 2]));
+        assert_eq!(lines[2]["territory"], json!([1, 1]));
+    }
+}
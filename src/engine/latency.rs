@@ -0,0 +1,78 @@
+use rand::prelude::*;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Artificial delay added before every frame sent to a bot, counted against
+/// its thinking time as if the engine were remote. Parsed from `MS` or
+/// `MS~JITTER`, the delay then varying by up to `JITTER` milliseconds either
+/// way.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Latency {
+    pub delay: Duration,
+    pub jitter: Duration,
+}
+
+impl Latency {
+    /// Delay for one frame
+    pub fn sample(&self) -> Duration {
+        if self.jitter == Duration::default() {
+            return self.delay;
+        }
+        let jitter = self.jitter.as_millis() as i64;
+        let offset = thread_rng().gen_range(-jitter, jitter + 1);
+        let millis = (self.delay.as_millis() as i64 + offset).max(0);
+        Duration::from_millis(millis as u64)
+    }
+}
+
+impl FromStr for Latency {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let millis = |part: &str| {
+            part.trim()
+                .parse()
+                .map(Duration::from_millis)
+                .map_err(|_| format!("Invalid latency: {}", s))
+        };
+        let mut parts = s.splitn(2, '~');
+        let delay = millis(parts.next().unwrap())?;
+        let jitter = match parts.next() {
+            Some(jitter) => millis(jitter)?,
+            None => Duration::default(),
+        };
+        Ok(Latency { delay, jitter })
+    }
+}
+
+/// Writes the latency back in the form `FromStr` reads
+impl fmt::Display for Latency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.delay.as_millis())?;
+        if self.jitter != Duration::default() {
+            write!(f, "~{}", self.jitter.as_millis())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_stay_within_jitter() {
+        let latency: Latency = "20~5".parse().unwrap();
+        assert_eq!(latency.to_string(), "20~5");
+        for _ in 0..100 {
+            let sample = latency.sample();
+            assert!(sample >= Duration::from_millis(15) && sample <= Duration::from_millis(25));
+        }
+        assert_eq!(
+            "7".parse::<Latency>().unwrap().sample(),
+            Duration::from_millis(7)
+        );
+        assert!("fast".parse::<Latency>().is_err());
+    }
+}
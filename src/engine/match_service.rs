@@ -0,0 +1,453 @@
+use super::{Engine, GameResult, OnPlayerResponse, PlayerResponse};
+use crate::models::piece::RANGE_DEFAULT;
+use crate::models::{PieceBag, Plateau, PlateauView};
+use std::future;
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// A game to be played by a `MatchService`
+#[derive(Debug, Clone)]
+pub struct MatchSpec {
+    pub player1: String,
+    pub player2: String,
+    /// Board to play on, the default plateau otherwise
+    pub plateau: Option<Plateau>,
+    /// Seed of the pieces, a random one otherwise
+    pub seed: Option<u64>,
+    /// Time each bot may take per move, see `EngineBuilder::with_timeout`
+    pub move_timeout: Option<Duration>,
+}
+
+impl MatchSpec {
+    pub fn new(player1: &str, player2: &str) -> MatchSpec {
+        MatchSpec {
+            player1: String::from(player1),
+            player2: String::from(player2),
+            plateau: None,
+            seed: None,
+            move_timeout: None,
+        }
+    }
+}
+
+/// What a caller of `MatchService::call` hears about its game: every move
+/// as it is played, then the result, or why the game could not be played
+#[derive(Clone)]
+pub enum MatchEvent {
+    Move(PlayerResponse),
+    Finished(GameResult),
+    Failed(String),
+}
+
+/// The events of one game, awaited with `next_event` or read as a blocking
+/// iterator. It ends after the result or the failure.
+pub struct MatchStream {
+    events: Receiver<MatchEvent>,
+    waker: Arc<Mutex<Option<Waker>>>,
+    over: bool,
+}
+
+impl MatchStream {
+    fn new() -> (MatchStream, EventSender) {
+        let (sender, events) = mpsc::channel();
+        let waker = Arc::new(Mutex::new(None));
+        let sender = EventSender {
+            events: sender,
+            waker: Arc::clone(&waker),
+        };
+        let stream = MatchStream {
+            events,
+            waker,
+            over: false,
+        };
+        (stream, sender)
+    }
+
+    /// The next event, `Poll::Ready(None)` once the game is over
+    pub fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<MatchEvent>> {
+        if self.over {
+            return Poll::Ready(None);
+        }
+        let event = match self.events.try_recv() {
+            Err(TryRecvError::Empty) => {
+                *self.waker.lock().unwrap() = Some(cx.waker().clone());
+                // An event sent before the waker was stored woke no one
+                self.events.try_recv()
+            }
+            received => received,
+        };
+        match event {
+            Ok(event) => Poll::Ready(Some(self.pass(event))),
+            Err(TryRecvError::Empty) => Poll::Pending,
+            Err(TryRecvError::Disconnected) => {
+                self.over = true;
+                Poll::Ready(None)
+            }
+        }
+    }
+
+    pub async fn next_event(&mut self) -> Option<MatchEvent> {
+        future::poll_fn(|cx| self.poll_next(cx)).await
+    }
+
+    fn pass(&mut self, event: MatchEvent) -> MatchEvent {
+        self.over = matches!(event, MatchEvent::Finished(_) | MatchEvent::Failed(_));
+        event
+    }
+}
+
+impl Iterator for MatchStream {
+    type Item = MatchEvent;
+
+    fn next(&mut self) -> Option<MatchEvent> {
+        if self.over {
+            return None;
+        }
+        let event = self.events.recv().ok();
+        self.over = event.is_none();
+        event.map(|event| self.pass(event))
+    }
+}
+
+/// The worker's end of a `MatchStream`
+#[derive(Clone)]
+struct EventSender {
+    events: Sender<MatchEvent>,
+    waker: Arc<Mutex<Option<Waker>>>,
+}
+
+impl EventSender {
+    fn send(&self, event: MatchEvent) {
+        let _ = self.events.send(event);
+        if let Some(waker) = self.waker.lock().unwrap().take() {
+            waker.wake();
+        }
+    }
+}
+
+type Job = (MatchSpec, EventSender);
+
+/// Room in the queue of a `MatchService`, a slot being taken from when a
+/// game is queued until a worker picks it up
+struct Slots {
+    capacity: usize,
+    taken: AtomicUsize,
+    waiting: Mutex<Option<Waker>>,
+}
+
+impl Slots {
+    fn new(capacity: usize) -> Slots {
+        Slots {
+            capacity,
+            taken: AtomicUsize::new(0),
+            waiting: Mutex::new(None),
+        }
+    }
+
+    fn try_reserve(&self) -> bool {
+        self.taken
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |taken| {
+                Some(taken + 1).filter(|_| taken < self.capacity)
+            })
+            .is_ok()
+    }
+
+    fn poll_reserve(&self, cx: &mut Context<'_>) -> Poll<()> {
+        if self.try_reserve() {
+            return Poll::Ready(());
+        }
+        *self.waiting.lock().unwrap() = Some(cx.waker().clone());
+        // A slot freed before the waker was stored woke no one
+        match self.try_reserve() {
+            true => Poll::Ready(()),
+            false => Poll::Pending,
+        }
+    }
+
+    fn release(&self) {
+        self.taken.fetch_sub(1, Ordering::SeqCst);
+        if let Some(waker) = self.waiting.lock().unwrap().take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Plays games for an embedding application on a fixed set of worker
+/// threads. Games wait in a bounded queue, so callers feel backpressure
+/// instead of piling up work: `poll_ready` waits for room the way
+/// `tower::Service` does, and with the `tower` feature the service is one,
+/// for timeouts, rate limits and the like to be layered on top.
+pub struct MatchService {
+    queue: Option<Sender<Job>>,
+    slots: Arc<Slots>,
+    /// Whether `poll_ready` holds a slot for the next `call`
+    reserved: bool,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl MatchService {
+    /// Starts `workers` threads that play games from a queue of at most
+    /// `queue` waiting games
+    pub fn new(workers: usize, queue: usize) -> MatchService {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let slots = Arc::new(Slots::new(queue.max(1)));
+        let workers = (0..workers.max(1))
+            .map(|_| {
+                let receiver = Arc::clone(&receiver);
+                let slots = Arc::clone(&slots);
+                thread::spawn(move || loop {
+                    let job = receiver.lock().unwrap().recv();
+                    match job {
+                        Ok((spec, events)) => {
+                            slots.release();
+                            play_isolated(spec, events)
+                        }
+                        Err(_) => break,
+                    }
+                })
+            })
+            .collect();
+        MatchService {
+            queue: Some(sender),
+            slots,
+            reserved: false,
+            workers,
+        }
+    }
+
+    /// Waits for room in the queue and holds it for the next `call`
+    pub fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        if !self.reserved {
+            if self.slots.poll_reserve(cx).is_pending() {
+                return Poll::Pending;
+            }
+            self.reserved = true;
+        }
+        Poll::Ready(())
+    }
+
+    /// `poll_ready` as a future
+    pub async fn ready(&mut self) {
+        future::poll_fn(|cx| self.poll_ready(cx)).await
+    }
+
+    /// Queues a game and returns the stream of its events. Without room held
+    /// by `poll_ready` the spec comes back if the queue is full.
+    pub fn call(&mut self, spec: MatchSpec) -> Result<MatchStream, Box<MatchSpec>> {
+        if !mem::take(&mut self.reserved) && !self.slots.try_reserve() {
+            return Err(Box::new(spec));
+        }
+        let (stream, events) = MatchStream::new();
+        let queue = self.queue.as_ref().unwrap();
+        match queue.send((spec, events)) {
+            Ok(()) => Ok(stream),
+            Err(mpsc::SendError((spec, _))) => Err(Box::new(spec)),
+        }
+    }
+}
+
+#[cfg(feature = "tower")]
+impl tower_service::Service<MatchSpec> for MatchService {
+    type Response = MatchStream;
+    type Error = String;
+    type Future = future::Ready<Result<MatchStream, String>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), String>> {
+        MatchService::poll_ready(self, cx).map(Ok)
+    }
+
+    fn call(&mut self, spec: MatchSpec) -> Self::Future {
+        let stream = MatchService::call(self, spec);
+        future::ready(stream.map_err(|_| String::from("match queue is full")))
+    }
+}
+
+/// Lets the queued games finish, then stops the workers
+impl Drop for MatchService {
+    fn drop(&mut self) {
+        self.queue.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Passes every move on to the caller
+struct EventSink(EventSender);
+
+impl OnPlayerResponse for EventSink {
+    fn on_player_move(&self, _: PlateauView, player_response: &PlayerResponse) {
+        self.0.send(MatchEvent::Move(player_response.clone()));
+    }
+}
+
+/// Plays a game, reporting an engine panic as a failed game so the worker
+/// lives on and the caller is not left waiting for a result
+fn play_isolated(spec: MatchSpec, events: EventSender) {
+    let failed = events.clone();
+    if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| play(spec, events))) {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| String::from(*message))
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| String::from("unknown panic"));
+        failed.send(MatchEvent::Failed(format!("engine panicked: {}", message)));
+    }
+}
+
+fn play(spec: MatchSpec, events: EventSender) {
+    let mut builder = Engine::builder(&spec.player1);
+    builder.with_player2(&spec.player2);
+    if let Some(plateau) = spec.plateau.clone() {
+        builder.with_plateau(plateau);
+    }
+    if let Some(seed) = spec.seed {
+        builder.with_piecebag(PieceBag::with_seed(seed, RANGE_DEFAULT, RANGE_DEFAULT));
+    }
+    if let Some(timeout) = spec.move_timeout {
+        builder.with_timeout(timeout);
+    }
+    builder.with_on_player_response(Box::new(EventSink(events.clone())));
+    let event = match builder.finish() {
+        Ok(mut engine) => MatchEvent::Finished(engine.run()),
+        Err(e) => MatchEvent::Failed(e),
+    };
+    events.send(event);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::native_bot::{GREEDY, RANDOM};
+    use crate::models::maps;
+    use std::future::Future;
+    use std::sync::atomic::AtomicBool;
+    use std::task::Wake;
+
+    /// Runs a future on this thread, parking while it is pending
+    fn block_on<F: Future>(future: F) -> F::Output {
+        struct Unpark(thread::Thread);
+        impl Wake for Unpark {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark();
+            }
+        }
+        let waker = Waker::from(Arc::new(Unpark(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = Box::pin(future);
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    fn tiny_game(seed: u64) -> MatchSpec {
+        let mut spec = MatchSpec::new(GREEDY, RANDOM);
+        spec.plateau = maps::get("tiny");
+        spec.seed = Some(seed);
+        spec
+    }
+
+    #[test]
+    fn streams_moves_then_the_result() {
+        let mut service = MatchService::new(2, 4);
+        let events: Vec<MatchEvent> = service.call(tiny_game(1)).unwrap().collect();
+
+        let moves = events
+            .iter()
+            .filter(|event| matches!(event, MatchEvent::Move(_)))
+            .count();
+        assert!(moves > 0);
+        match events.last() {
+            Some(MatchEvent::Finished(result)) => assert!(result.moves <= moves),
+            _ => panic!("the stream should end with the result"),
+        }
+    }
+
+    #[test]
+    fn a_panicking_game_fails_without_killing_its_worker() {
+        let mut service = MatchService::new(1, 4);
+        let mut spec = MatchSpec::new(GREEDY, RANDOM);
+        spec.plateau = maps::get("tiny");
+        // The deadline of a move this long overflows, panicking the engine
+        spec.move_timeout = Some(Duration::MAX);
+        let events: Vec<MatchEvent> = service.call(spec).unwrap().collect();
+        match events.last() {
+            Some(MatchEvent::Failed(e)) => assert!(e.starts_with("engine panicked")),
+            _ => panic!("the stream should end with the failure"),
+        }
+
+        let mut spec = MatchSpec::new(GREEDY, RANDOM);
+        spec.plateau = maps::get("tiny");
+        let events: Vec<MatchEvent> = service.call(spec).unwrap().collect();
+        assert!(matches!(events.last(), Some(MatchEvent::Finished(_))));
+    }
+
+    #[test]
+    fn a_full_queue_is_ready_again_once_a_worker_takes_a_game() {
+        #[derive(Default)]
+        struct Flag(AtomicBool);
+        impl Wake for Flag {
+            fn wake(self: Arc<Self>) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+        let woken = Arc::new(Flag::default());
+        let waker = Waker::from(Arc::clone(&woken));
+        let mut cx = Context::from_waker(&waker);
+        let slots = Slots::new(1);
+
+        assert!(slots.poll_reserve(&mut cx).is_ready());
+        assert!(slots.poll_reserve(&mut cx).is_pending());
+        slots.release();
+        assert!(woken.0.load(Ordering::SeqCst));
+        assert!(slots.poll_reserve(&mut cx).is_ready());
+    }
+
+    #[test]
+    fn awaits_room_for_each_game_and_streams_its_events() {
+        let mut service = MatchService::new(1, 1);
+        let finished = block_on(async {
+            let mut streams = vec![];
+            for seed in 0..3 {
+                service.ready().await;
+                streams.push(service.call(tiny_game(seed)).unwrap());
+            }
+            let mut finished = 0;
+            for mut stream in streams {
+                let mut last = None;
+                while let Some(event) = stream.next_event().await {
+                    last = Some(event);
+                }
+                if let Some(MatchEvent::Finished(_)) = last {
+                    finished += 1;
+                }
+            }
+            finished
+        });
+        assert_eq!(finished, 3);
+    }
+
+    #[cfg(feature = "tower")]
+    #[test]
+    fn is_a_tower_service() {
+        use tower_service::Service;
+
+        let mut service = MatchService::new(1, 1);
+        let stream = block_on(async {
+            future::poll_fn(|cx| Service::poll_ready(&mut service, cx)).await?;
+            Service::call(&mut service, tiny_game(1)).await
+        });
+        let events: Vec<MatchEvent> = stream.unwrap().collect();
+        assert!(matches!(events.last(), Some(MatchEvent::Finished(_))));
+    }
+}
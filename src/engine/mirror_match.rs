@@ -0,0 +1,115 @@
+use super::{Engine, PlayerResponse};
+use crate::models::{PieceBag, Plateau, Player};
+use std::fmt;
+
+/// Outcome of pitting a bot against itself with the seats exchanged
+#[derive(Debug, Default)]
+pub struct MirrorReport {
+    pub moves: usize,
+    /// First move at which a rerun of the same game went differently
+    pub nondeterministic: Option<usize>,
+    /// First move at which the game with the seats exchanged went
+    /// differently, pointing at a bug that depends on the player number
+    pub asymmetric: Option<usize>,
+}
+
+impl MirrorReport {
+    pub fn is_clean(&self) -> bool {
+        self.nondeterministic.is_none() && self.asymmetric.is_none()
+    }
+}
+
+impl fmt::Display for MirrorReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let show = |divergence: Option<usize>| match divergence {
+            Some(move_number) => format!("differs from move {}", move_number),
+            None => String::from("OK"),
+        };
+        writeln!(f, "Moves compared:   {}", self.moves)?;
+        writeln!(f, "Rerun:            {}", show(self.nondeterministic))?;
+        writeln!(f, "Seats exchanged:  {}", show(self.asymmetric))
+    }
+}
+
+/// Plays the bot at `path` against itself three times with the same pieces:
+/// twice on `plateau`, and once with the players' starts exchanged and
+/// Player2 moving first. A deterministic bot that does not depend on its
+/// player number makes the same moves in all three games.
+pub fn mirror_match(path: &str, plateau: &Plateau, seed: u64) -> Result<MirrorReport, String> {
+    let mut state = PieceBag::default().state();
+    state.seed = seed;
+    let bag = PieceBag::from_state(&state);
+
+    let original = play(path, plateau.clone(), bag.fork(), false)?;
+    let rerun = play(path, plateau.clone(), bag.fork(), false)?;
+    let swapped = play(path, plateau.with_players_swapped(), bag, true)?;
+
+    Ok(MirrorReport {
+        moves: original.len(),
+        nondeterministic: divergence(&original, &rerun),
+        asymmetric: divergence(&original, &swapped),
+    })
+}
+
+fn play(
+    path: &str,
+    plateau: Plateau,
+    bag: PieceBag,
+    swapped: bool,
+) -> Result<Vec<PlayerResponse>, String> {
+    let mut builder = Engine::builder(path);
+    builder.with_player2(path);
+    builder.with_plateau(plateau);
+    builder.with_piecebag(bag);
+    if swapped {
+        builder.first_to_move(Player::Player2);
+    }
+    let mut engine = builder.finish()?;
+    engine.run();
+    Ok(engine.history().to_vec())
+}
+
+/// First move whose placement or rejection differs between the two games
+fn divergence(original: &[PlayerResponse], other: &[PlayerResponse]) -> Option<usize> {
+    let differs = original
+        .iter()
+        .zip(other.iter())
+        .position(|(a, b)| a.placement != b.placement || a.rejection != b.rejection);
+    match differs {
+        Some(move_number) => Some(move_number),
+        None if original.len() != other.len() => Some(original.len().min(other.len())),
+        None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Piece, Point};
+
+    fn response(x: i32) -> PlayerResponse {
+        PlayerResponse {
+            player: Player::Player1,
+            piece: Piece::new_blank(1, 1),
+            batch: vec![],
+            raw_response: None,
+            placement: Some(Point::new(x, 0)),
+            batch_placements: vec![],
+            placement_count: 0,
+            error: None,
+            rejection: None,
+            timestamp: 0,
+            board_hash: None,
+            name: None,
+            notes: vec![],
+        }
+    }
+
+    #[test]
+    fn divergence_finds_first_difference() {
+        let game = vec![response(0), response(1), response(2)];
+        assert_eq!(divergence(&game, &game), None);
+        assert_eq!(divergence(&game, &[response(0), response(5)]), Some(1));
+        assert_eq!(divergence(&game, &game[..2]), Some(2));
+    }
+}
@@ -1,11 +1,123 @@
+#[allow(clippy::module_inception)]
 pub mod engine;
-pub use engine::Engine;
+pub use engine::{Engine, OnPlayerResponse, PieceFilter, Referee, Verdict};
 
 pub mod player_error;
 pub use player_error::PlayerError;
 
+pub mod native_bot;
+pub use native_bot::{BotKind, NativeBot, Strategy};
+
+pub mod tcp_bot;
+
+mod json_frame;
+
 mod bot;
 use bot::Bot;
 
+pub mod bot_adapter;
+pub use bot_adapter::BotAdapter;
+
+pub mod bot_registry;
+pub use bot_registry::BotRegistry;
+
+pub mod bot_report;
+pub use bot_report::BotReport;
+
+mod game_id;
+
+pub mod growth;
+pub use growth::Growth;
+
+pub mod html_status;
+pub use html_status::HtmlStatus;
+
+pub mod json_log;
+pub use json_log::JsonLog;
+
+pub mod game_state;
+pub use game_state::GameState;
+
+pub mod fuzz;
+pub use fuzz::FuzzReport;
+
+pub mod doctor;
+pub use doctor::Finding;
+
+pub mod audit;
+pub use audit::AuditReport;
+
+pub mod latency;
+pub use latency::Latency;
+
+pub mod mirror_match;
+pub use mirror_match::MirrorReport;
+
+pub mod tournament;
+pub use tournament::{Standings, Tournament};
+
+pub mod storage;
+pub use storage::Storage;
+
+pub mod match_service;
+pub use match_service::{MatchEvent, MatchService, MatchSpec, MatchStream};
+
+pub mod outcome;
+pub use outcome::{Outcome, TieBreak};
+
+pub mod game_result;
+pub use game_result::GameResult;
+
+pub mod placement;
+pub use placement::Placement;
+
+mod preflight;
+
+pub mod scoring;
+pub use scoring::{HillScoring, RecencyScoring, Scoring};
+
+pub mod resources;
+pub use resources::ResourceSample;
+
+pub mod sandbox;
+pub use sandbox::Sandbox;
+
+pub mod interpreters;
+pub use interpreters::Interpreters;
+
+pub mod spectator_delay;
+pub use spectator_delay::SpectatorDelay;
+
+pub mod subscription;
+pub use subscription::Subscription;
+
+pub mod termination;
+pub use termination::Termination;
+
+pub mod playback;
+
+pub mod animate;
+pub use animate::Pacing;
+
+pub mod asciicast;
+
+pub mod render;
+pub use render::Frame;
+
+pub mod replay;
+pub use replay::Replay;
+
+pub mod replay_cursor;
+pub use replay_cursor::ReplayCursor;
+
+pub mod rule_set;
+pub use rule_set::{PieceRange, RuleSet};
+
+pub mod position;
+pub use position::Position;
+
+pub mod prelude;
+pub use prelude::Prelude;
+
 mod player_response;
-use player_response::PlayerResponse;
+pub use player_response::{PlayerResponse, Rejection};
@@ -0,0 +1,342 @@
+use super::tcp_bot;
+use crate::models::constants::*;
+use crate::models::{OverlapRule, Piece, Plateau, Player, Point};
+use rand::prelude::*;
+use std::convert::TryFrom;
+
+/// Bot path that selects the built-in random bot instead of a program
+pub const RANDOM: &str = "random";
+/// Bot path that selects the built-in greedy bot instead of a program
+pub const GREEDY: &str = "greedy";
+
+/// Strategies of the built-in bots
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    Random,
+    Greedy,
+}
+
+impl Strategy {
+    pub const ALL: [Strategy; 2] = [Strategy::Random, Strategy::Greedy];
+
+    /// Bot path that selects the strategy
+    pub fn name(self) -> &'static str {
+        match self {
+            Strategy::Random => RANDOM,
+            Strategy::Greedy => GREEDY,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Strategy> {
+        Strategy::ALL
+            .iter()
+            .copied()
+            .find(|strategy| strategy.name() == name)
+    }
+
+    pub fn create(self, player: Player) -> Box<dyn NativeBot> {
+        match self {
+            Strategy::Random => Box::new(RandomBot::new(player)),
+            Strategy::Greedy => Box::new(GreedyBot::new(player)),
+        }
+    }
+}
+
+/// How a bot path is played: by a built-in strategy, by starting a program
+/// or by a remote bot connecting to the engine
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BotKind<'a> {
+    ExternalProcess(&'a str),
+    Builtin(Strategy),
+    /// Address to listen on, see `tcp_bot`
+    Remote(String),
+}
+
+impl<'a> BotKind<'a> {
+    pub fn of(path: &'a str) -> BotKind<'a> {
+        if let Some(address) = tcp_bot::listen_address(path) {
+            return BotKind::Remote(address);
+        }
+        match Strategy::from_name(path) {
+            Some(strategy) => BotKind::Builtin(strategy),
+            None => BotKind::ExternalProcess(path),
+        }
+    }
+
+    /// Whether the bot is a program that is started from a file
+    pub fn is_process(&self) -> bool {
+        matches!(self, BotKind::ExternalProcess(_))
+    }
+}
+
+/// A bot that runs inside the engine rather than as a separate process. It
+/// reads the same frames a process would and answers with placements.
+pub trait NativeBot: Send {
+    fn player(&self) -> Player;
+    /// Own cells a piece has to cover in this game, see `OverlapRule`
+    fn overlap_rule(&self) -> OverlapRule;
+    fn set_overlap_rule(&mut self, rule: OverlapRule);
+    /// Picks a placement for `piece` on a plateau given as its rows
+    fn place(&mut self, rows: &[Vec<char>], piece: &Piece) -> Point;
+}
+
+/// Built-in bot registered under `name`, if there is one
+pub fn native_bot(name: &str, player: Player) -> Option<Box<dyn NativeBot>> {
+    Strategy::from_name(name).map(|strategy| strategy.create(player))
+}
+
+/// Answers a frame with one placement line per piece in it. Each piece of a
+/// batch is placed on the board left by the ones before it.
+pub fn respond(bot: &mut dyn NativeBot, frame: &str) -> Vec<String> {
+    let (mut rows, pieces) = parse_frame(frame);
+    let (player, rule) = (bot.player(), bot.overlap_rule());
+    pieces
+        .iter()
+        .map(|piece| {
+            let p = bot.place(&rows, piece);
+            if is_valid(&rows, piece, p, player, rule) {
+                for point in piece.filled_points() {
+                    rows[(p.y + point.y) as usize][(p.x + point.x) as usize] =
+                        PLAYER_TOKENS[player as usize];
+                }
+            }
+            format!("{} {}\n", p.y, p.x)
+        })
+        .collect()
+}
+
+/// Picks uniformly among the valid placements, useful for smoke testing
+pub struct RandomBot {
+    player: Player,
+    overlap: OverlapRule,
+    rng: StdRng,
+}
+
+impl RandomBot {
+    pub fn new(player: Player) -> Self {
+        RandomBot {
+            player,
+            overlap: OverlapRule::default(),
+            rng: StdRng::from_entropy(),
+        }
+    }
+}
+
+impl NativeBot for RandomBot {
+    fn player(&self) -> Player {
+        self.player
+    }
+
+    fn overlap_rule(&self) -> OverlapRule {
+        self.overlap
+    }
+
+    fn set_overlap_rule(&mut self, rule: OverlapRule) {
+        self.overlap = rule;
+    }
+
+    fn place(&mut self, rows: &[Vec<char>], piece: &Piece) -> Point {
+        let valid = valid_placements(rows, piece, self.player, self.overlap);
+        valid.choose(&mut self.rng).copied().unwrap_or_default()
+    }
+}
+
+/// Moves towards the opponent: picks the valid placement whose cells are
+/// closest to the opponent's territory in total, the usual filler heuristic
+pub struct GreedyBot {
+    player: Player,
+    overlap: OverlapRule,
+}
+
+impl GreedyBot {
+    pub fn new(player: Player) -> Self {
+        GreedyBot {
+            player,
+            overlap: OverlapRule::default(),
+        }
+    }
+}
+
+impl NativeBot for GreedyBot {
+    fn player(&self) -> Player {
+        self.player
+    }
+
+    fn overlap_rule(&self) -> OverlapRule {
+        self.overlap
+    }
+
+    fn set_overlap_rule(&mut self, rule: OverlapRule) {
+        self.overlap = rule;
+    }
+
+    fn place(&mut self, rows: &[Vec<char>], piece: &Piece) -> Point {
+        let heat = heat_map(rows, self.player);
+        let score = |placement: &Point| -> u64 {
+            piece
+                .filled_points()
+                .map(|p| heat[(placement.y + p.y) as usize][(placement.x + p.x) as usize])
+                .map(u64::from)
+                .sum()
+        };
+        valid_placements(rows, piece, self.player, self.overlap)
+            .into_iter()
+            .min_by_key(score)
+            .unwrap_or_default()
+    }
+}
+
+/// `Plateau::heat_map` of a plateau given as its rows, one row of heat per
+/// row of cells. Every cell is `u32::MAX` while the opponent is out of sight.
+fn heat_map(rows: &[Vec<char>], player: Player) -> Vec<Vec<u32>> {
+    let width = rows.first().map_or(0, Vec::len);
+    let map: String = rows
+        .iter()
+        .flat_map(|row| row.iter().map(char::to_ascii_uppercase).chain(Some('\n')))
+        .collect();
+    let heat = match Plateau::try_from(map) {
+        Ok(plateau) => plateau.heat_map(player),
+        Err(_) => vec![u32::MAX; width * rows.len()],
+    };
+    heat.chunks(width.max(1)).map(<[u32]>::to_vec).collect()
+}
+
+/// Every placement of `piece` the engine would accept for `player` under
+/// `rule`
+pub fn valid_placements(
+    rows: &[Vec<char>],
+    piece: &Piece,
+    player: Player,
+    rule: OverlapRule,
+) -> Vec<Point> {
+    let height = rows.len() as i32;
+    let width = rows.first().map_or(0, Vec::len) as i32;
+    let mut valid = vec![];
+    for y in (1 - piece.height() as i32)..height {
+        for x in (1 - piece.width() as i32)..width {
+            if is_valid(rows, piece, Point::new(x, y), player, rule) {
+                valid.push(Point::new(x, y));
+            }
+        }
+    }
+    valid
+}
+
+fn is_valid(
+    rows: &[Vec<char>],
+    piece: &Piece,
+    placement: Point,
+    player: Player,
+    rule: OverlapRule,
+) -> bool {
+    let (own, own_new) = match player {
+        Player::Player1 => (PLAYER1, PLAYER1_NEW),
+        Player::Player2 => (PLAYER2, PLAYER2_NEW),
+    };
+    let mut overlap = 0;
+    for point in piece.filled_points() {
+        let (x, y) = (placement.x + point.x, placement.y + point.y);
+        let cell = match rows.get(y as usize).and_then(|row| row.get(x as usize)) {
+            Some(cell) if x >= 0 && y >= 0 => *cell,
+            _ => return false,
+        };
+        match cell {
+            EMPTY => {}
+            c if c == own || c == own_new => overlap += 1,
+            _ => return false,
+        }
+    }
+    rule.allows(overlap)
+}
+
+/// Pulls the plateau rows and the pieces out of a frame, skipping `$$$`
+/// lines. Both the bare and the legacy board layouts are understood.
+fn parse_frame(frame: &str) -> (Vec<Vec<char>>, Vec<Piece>) {
+    let mut rows = vec![];
+    let mut pieces = vec![];
+    let mut lines = frame.lines().filter(|line| !line.starts_with("$$$"));
+
+    while let Some(line) = lines.next() {
+        let (height, width) = match dimensions(line) {
+            Some(dimensions) => dimensions,
+            None => continue,
+        };
+        if line.starts_with("Plateau") {
+            let mut board: Vec<&str> = lines.by_ref().take(height).collect();
+            if board.first().is_some_and(|row| row.starts_with(' ')) {
+                board.extend(lines.next());
+                board.remove(0);
+            }
+            rows = board
+                .iter()
+                .map(|row| row.rsplit(' ').next().unwrap_or("").chars().collect())
+                .collect();
+        } else if line.starts_with("Piece") {
+            let cells = lines
+                .by_ref()
+                .take(height)
+                .flat_map(|row| row.chars().map(|c| c == '*'))
+                .collect::<Vec<bool>>();
+            if cells.len() == width * height {
+                pieces.push(Piece::new(width, height, cells));
+            }
+        }
+    }
+    (rows, pieces)
+}
+
+/// Reads `H W` from a `Plateau H W:` or `Piece H W:` header
+fn dimensions(line: &str) -> Option<(usize, usize)> {
+    let mut words = line.trim_end_matches(':').split(' ').skip(1);
+    let height = words.next()?.parse().ok()?;
+    let width = words.next()?.parse().ok()?;
+    Some((height, width))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn random_bot_answers_with_a_valid_placement() {
+        let frame = "Plateau 3 4:\nO...\n....\n...X\nPiece 1 2:\n**\n";
+        let mut bot = RandomBot::new(Player::Player1);
+        let answer = respond(&mut bot, frame);
+
+        assert!(answer == vec!["0 0\n"] || answer == vec!["0 -1\n"]);
+    }
+
+    #[test]
+    fn greedy_bot_moves_towards_the_opponent() {
+        let frame = "Plateau 3 5:\n.....\n..O..\n....X\nPiece 1 2:\n**\n";
+        let mut bot = Strategy::Greedy.create(Player::Player1);
+
+        assert_eq!(respond(bot.as_mut(), frame), vec!["1 2\n"]);
+        assert_eq!(BotKind::of(GREEDY), BotKind::Builtin(Strategy::Greedy));
+        assert_eq!(BotKind::of("bot.py"), BotKind::ExternalProcess("bot.py"));
+        assert_eq!(
+            BotKind::of("tcp:4242"),
+            BotKind::Remote(String::from("0.0.0.0:4242"))
+        );
+    }
+
+    #[test]
+    fn placements_follow_the_overlap_rule() {
+        let rows: Vec<Vec<char>> = vec!["OO..".chars().collect(), "...X".chars().collect()];
+        let piece = Piece::new(2, 1, vec![true, true]);
+
+        let standard = valid_placements(&rows, &piece, Player::Player1, OverlapRule::default());
+        assert_eq!(standard, vec![Point::new(1, 0)]);
+        let double = valid_placements(&rows, &piece, Player::Player1, OverlapRule::Exactly(2));
+        assert_eq!(double, vec![Point::new(0, 0)]);
+    }
+
+    #[test]
+    fn reads_legacy_boards() {
+        let frame = "Plateau 2 3:\n    012\n000 O..\n001 ..X\nPiece 1 1:\n*\n";
+        let (rows, pieces) = parse_frame(frame);
+
+        assert_eq!(rows, vec![vec!['O', '.', '.'], vec!['.', '.', 'X']]);
+        assert_eq!(pieces.len(), 1);
+    }
+}
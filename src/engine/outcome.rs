@@ -0,0 +1,161 @@
+use crate::models::Player;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Rule that decides a game whose scores are level. Parsed from `errors` or
+/// `clock`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TieBreak {
+    /// The player with fewer rejected moves wins
+    FewestErrors,
+    /// The player that spent less time thinking wins
+    FasterClock,
+}
+
+impl FromStr for TieBreak {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "errors" => Ok(TieBreak::FewestErrors),
+            "clock" => Ok(TieBreak::FasterClock),
+            _ => Err(format!(
+                "Unknown tie-break '{}', expected errors or clock",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for TieBreak {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rule = match self {
+            TieBreak::FewestErrors => "fewest errors",
+            TieBreak::FasterClock => "faster clock",
+        };
+        write!(f, "{}", rule)
+    }
+}
+
+/// Result of a finished game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Outcome {
+    /// `winner` scored more, or scored the same and won on `tie_break`
+    Win {
+        winner: Player,
+        tie_break: Option<TieBreak>,
+    },
+    Draw,
+    /// Neither player placed a piece before errors ended the game, as when
+    /// no bot could be started. Not a draw, the game was never played.
+    NoContest,
+}
+
+impl Outcome {
+    /// Decides a two player game from each side's score, then from the
+    /// `tie_breaks` in order. `measure` gives a player's standing under a
+    /// tie-break, lower being better.
+    pub fn decide<F>(scores: &[(Player, usize)], tie_breaks: &[TieBreak], measure: F) -> Outcome
+    where
+        F: Fn(Player, TieBreak) -> u64,
+    {
+        let (a, b) = match scores {
+            [a, b] => (a, b),
+            _ => return Outcome::Draw,
+        };
+        if a.1 != b.1 {
+            let winner = if a.1 > b.1 { a.0 } else { b.0 };
+            return Outcome::Win {
+                winner,
+                tie_break: None,
+            };
+        }
+        for tie_break in tie_breaks {
+            let (measure_a, measure_b) = (measure(a.0, *tie_break), measure(b.0, *tie_break));
+            if measure_a != measure_b {
+                let winner = if measure_a < measure_b { a.0 } else { b.0 };
+                return Outcome::Win {
+                    winner,
+                    tie_break: Some(*tie_break),
+                };
+            }
+        }
+        Outcome::Draw
+    }
+
+    /// Exit code the CLI reports the outcome with: the winner's player
+    /// number, 3 for a draw or 4 for no contest
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Outcome::Win {
+                winner: Player::Player1,
+                ..
+            } => 1,
+            Outcome::Win {
+                winner: Player::Player2,
+                ..
+            } => 2,
+            Outcome::Draw => 3,
+            Outcome::NoContest => 4,
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Outcome::Win {
+                winner,
+                tie_break: None,
+            } => write!(f, "<{}> wins", winner),
+            Outcome::Win {
+                winner,
+                tie_break: Some(tie_break),
+            } => write!(f, "<{}> wins on {}", winner, tie_break),
+            Outcome::Draw => write!(f, "Draw"),
+            Outcome::NoContest => write!(f, "No contest"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Player::*;
+
+    #[test]
+    fn level_scores_go_to_tie_breaks() {
+        let errors = |player, tie_break| match (player, tie_break) {
+            (Player1, TieBreak::FewestErrors) => 2,
+            (Player2, TieBreak::FewestErrors) => 2,
+            (Player1, TieBreak::FasterClock) => 900,
+            (Player2, TieBreak::FasterClock) => 400,
+        };
+        let level = [(Player1, 10), (Player2, 10)];
+
+        assert_eq!(
+            Outcome::decide(&[(Player1, 11), (Player2, 10)], &[], errors),
+            Outcome::Win {
+                winner: Player1,
+                tie_break: None
+            }
+        );
+        assert_eq!(Outcome::decide(&level, &[], errors), Outcome::Draw);
+        assert_eq!(
+            Outcome::decide(&level, &[TieBreak::FewestErrors], errors),
+            Outcome::Draw
+        );
+        assert_eq!(
+            Outcome::decide(
+                &level,
+                &[TieBreak::FewestErrors, TieBreak::FasterClock],
+                errors
+            ),
+            Outcome::Win {
+                winner: Player2,
+                tie_break: Some(TieBreak::FasterClock)
+            }
+        );
+    }
+}
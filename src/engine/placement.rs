@@ -0,0 +1,11 @@
+use crate::models::{Player, Point};
+use serde::Serialize;
+
+/// A successful placement made by a player
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Placement {
+    pub player: Player,
+    /// Index of the move in the game, counting both players
+    pub move_number: usize,
+    pub point: Point,
+}
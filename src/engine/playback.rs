@@ -0,0 +1,70 @@
+use super::{Replay, ReplayCursor};
+use std::io::Write;
+use std::thread;
+use std::time::Duration;
+
+/// Replays a saved game to `out`, sleeping between moves for the time the
+/// original move took divided by `speed`. Multi-piece turns are listed but
+/// not drawn, as the replay only keeps their final placement.
+pub fn play_back<W: Write>(replay: &str, speed: f64, out: &mut W) -> Result<(), String> {
+    let mut cursor = ReplayCursor::new(Replay::from_json(replay)?)?;
+
+    let mut previous = cursor.replay().started_at;
+    while cursor.step_forward()?.is_some() {
+        let move_number = cursor.position() - 1;
+        let response = cursor.last_move().unwrap();
+        let timestamp = match response.timestamp {
+            0 => previous,
+            timestamp => timestamp,
+        };
+        let elapsed = (timestamp - previous).max(0) as u64;
+        previous = timestamp;
+        if speed > 0.0 && elapsed > 0 {
+            thread::sleep(Duration::from_millis(elapsed).div_f64(speed));
+        }
+
+        let status = match (&response.error, response.placement) {
+            (Some(error), _) => error.clone(),
+            (None, Some(p)) => format!("placed at ({}, {})", p.x, p.y),
+            (None, None) => String::from("no placement"),
+        };
+        writeln!(
+            out,
+            "Move {}: <{}> {} ({} ms)",
+            move_number, response.player, status, elapsed
+        )
+        .and_then(|_| write!(out, "{}", cursor.plateau()))
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replays_successful_moves() {
+        let replay = r#"{
+            "started_at": 1000,
+            "players": ["a", "b"],
+            "plateau": {"width": 3, "height": 2,
+                "player1_start": {"x": 0, "y": 0}, "player2_start": {"x": 2, "y": 1}},
+            "history": [
+                {"player": "Player1", "piece": {"width": 2, "height": 1, "cells": [1, 1]},
+                 "raw_response": "0 0\n", "placement": {"x": 0, "y": 0}, "placement_count": 1,
+                 "error": null, "timestamp": 1010},
+                {"player": "Player2", "piece": {"width": 1, "height": 1, "cells": [1]},
+                 "raw_response": "9 9\n", "placement": {"x": 9, "y": 9}, "placement_count": 0,
+                 "error": "Piece out of bounds", "timestamp": 1015}
+            ],
+            "termination": null
+        }"#;
+        let mut out = vec![];
+        play_back(replay, 0.0, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert!(out.contains("Move 0: <O> placed at (0, 0) (10 ms)"));
+        assert!(out.contains("Move 1: <X> Piece out of bounds (5 ms)"));
+    }
+}
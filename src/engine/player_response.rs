@@ -1,13 +1,83 @@
 extern crate serde;
 
-use crate::models::{Piece, Player};
-use serde::Serialize;
+use crate::models::{Piece, PlacementError, Player, Point};
+use serde::{Deserialize, Serialize};
 
-#[derive(Serialize)]
+/// Why a move was not accepted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Rejection {
+    Timeout,
+    Malformed,
+    OutOfBounds,
+    NoOverlap,
+    DoubleOverlap,
+    OpponentOverlap,
+    Blocked,
+    BotCrashed,
+    /// Refused by a `Referee`
+    Vetoed,
+}
+
+impl From<PlacementError> for Rejection {
+    fn from(error: PlacementError) -> Self {
+        match error {
+            PlacementError::OutOfBounds => Rejection::OutOfBounds,
+            PlacementError::NoOverlap => Rejection::NoOverlap,
+            PlacementError::DoubleOverlap => Rejection::DoubleOverlap,
+            PlacementError::OpponentOverlap => Rejection::OpponentOverlap,
+            PlacementError::Blocked => Rejection::Blocked,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct PlayerResponse {
     pub player: Player,
     pub piece: Piece,
+    /// Any further pieces dealt in a multi-piece turn
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub batch: Vec<Piece>,
     pub raw_response: Option<String>,
+    /// Coordinates the bot attempted for `piece`
+    pub placement: Option<Point>,
+    /// Coordinates attempted for the pieces of `batch`, up to the first one
+    /// that was rejected
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub batch_placements: Vec<Point>,
     pub placement_count: usize,
     pub error: Option<String>,
+    #[serde(default)]
+    pub rejection: Option<Rejection>,
+    /// Wall-clock time in milliseconds since the epoch at which the move ended
+    #[serde(default)]
+    pub timestamp: i64,
+    /// `Plateau::content_hash` of the board once the move was resolved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub board_hash: Option<String>,
+    /// Display name of the player in the seat, see `Identity`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Remarks `Referee`s attached to the move
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+}
+
+impl PlayerResponse {
+    pub fn reject(&mut self, rejection: Rejection, message: String) {
+        self.rejection = Some(rejection);
+        self.error = Some(message);
+    }
+
+    /// Every piece of an accepted turn with where it was placed, in the order
+    /// they were placed. Empty for a rejected turn.
+    pub fn placements(&self) -> Vec<(&Piece, Point)> {
+        if self.error.is_some() {
+            return vec![];
+        }
+        let pieces = Some(&self.piece).into_iter().chain(self.batch.iter());
+        pieces
+            .zip(self.placement.iter().chain(self.batch_placements.iter()))
+            .map(|(piece, point)| (piece, *point))
+            .collect()
+    }
 }
@@ -0,0 +1,181 @@
+use super::Replay;
+use crate::models::constants::*;
+use crate::models::{Piece, Plateau, Player};
+use std::convert::TryFrom;
+
+/// A board to start a game from, the side that moves first and the pieces
+/// to deal, for drilling a bot on one scenario
+#[derive(Clone)]
+pub struct Position {
+    pub plateau: Plateau,
+    pub to_move: Player,
+    /// Pieces dealt in order, an empty list deals random pieces
+    pub pieces: Vec<Piece>,
+}
+
+impl Position {
+    /// The board a replay starts from with the replay's pieces, as cut by
+    /// `Replay::slice`
+    pub fn from_replay(replay: &Replay) -> Result<Position, String> {
+        let pieces = replay
+            .history
+            .iter()
+            .flat_map(|response| Some(&response.piece).into_iter().chain(&response.batch))
+            .cloned()
+            .collect();
+        Ok(Position {
+            plateau: replay.initial_plateau()?,
+            to_move: replay.history.first().map_or(Player::Player1, |r| r.player),
+            pieces,
+        })
+    }
+
+    /// Reads a position file: a board, then the pieces to deal in the
+    /// `Piece H W:` format bots receive, optionally after a blank line. The
+    /// board's notation is detected from its first line:
+    /// - the map file format
+    /// - a board dump from the original VM, headed `Plateau H W:`, with or
+    ///   without column and row numbers
+    /// - run-length rows such as `O4./5./4.X`, where a count repeats the
+    ///   cell after it and rows are split by `/` or line breaks
+    ///
+    /// Player1 moves first.
+    pub fn parse(text: &str) -> Result<Position, String> {
+        let lines: Vec<&str> = text
+            .lines()
+            .filter(|line| !line.starts_with("$$$"))
+            .skip_while(|line| line.trim().is_empty())
+            .collect();
+        let board_end = lines
+            .iter()
+            .position(|line| line.trim().is_empty() || line.starts_with("Piece"))
+            .unwrap_or(lines.len());
+        let rows = board_rows(&lines[..board_end])?;
+        let plateau = Plateau::try_from(rows.join("\n") + "\n")?;
+
+        let mut pieces = vec![];
+        let rest = &lines[board_end..];
+        let mut i = 0;
+        while i < rest.len() {
+            if rest[i].trim().is_empty() {
+                i += 1;
+                continue;
+            }
+            let height = rest[i]
+                .split(' ')
+                .nth(1)
+                .and_then(|height| height.parse::<usize>().ok())
+                .ok_or_else(|| format!("Bad piece header: {}", rest[i]))?;
+            let end = (i + 1 + height).min(rest.len());
+            pieces.push(rest[i..end].join("\n").parse()?);
+            i = end;
+        }
+
+        Ok(Position {
+            plateau,
+            to_move: Player::Player1,
+            pieces,
+        })
+    }
+}
+
+/// The board's rows in map file format, whichever notation they are in
+fn board_rows(board: &[&str]) -> Result<Vec<String>, String> {
+    match board.first() {
+        None => Err(String::from("Position has no board")),
+        Some(header) if header.starts_with("Plateau") => Ok(dump_rows(&board[1..])),
+        Some(_) if board.iter().any(|row| row.contains(is_run_length)) => board
+            .iter()
+            .flat_map(|row| row.split('/'))
+            .map(expand_run_length)
+            .collect(),
+        Some(_) => Ok(board.iter().map(|row| row.to_string()).collect()),
+    }
+}
+
+fn is_run_length(c: char) -> bool {
+    c.is_ascii_digit() || c == '/'
+}
+
+/// Rows of a VM board dump without the numbering, with the last piece's
+/// cells as plain owned cells
+fn dump_rows(board: &[&str]) -> Vec<String> {
+    let numbered = board.first().is_some_and(|row| row.starts_with(' '));
+    board
+        .iter()
+        .skip(numbered as usize)
+        .map(|row| {
+            row.rsplit(' ')
+                .next()
+                .unwrap_or("")
+                .chars()
+                .map(|c| match c {
+                    PLAYER1_NEW => PLAYER1,
+                    PLAYER2_NEW => PLAYER2,
+                    c => c,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Expands a run-length row such as `3.O2.`
+fn expand_run_length(row: &str) -> Result<String, String> {
+    let mut out = String::new();
+    let mut count = String::new();
+    for c in row.trim().chars() {
+        if c.is_ascii_digit() {
+            count.push(c);
+            continue;
+        }
+        let repeat = match count.is_empty() {
+            true => 1,
+            false => count
+                .parse()
+                .map_err(|_| format!("Bad run length {} in row {}", count, row))?,
+        };
+        out.extend(std::iter::repeat_n(c, repeat));
+        count.clear();
+    }
+    match count.is_empty() {
+        true => Ok(out),
+        false => Err(format!(
+            "Run length without a cell at the end of row {}",
+            row
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_board_and_pieces() {
+        let text = "O..\n..X\n\nPiece 1 2:\n**\nPiece 2 1:\n*\n*\n";
+        let position = Position::parse(text).unwrap();
+
+        assert_eq!(position.plateau.to_map_rows(), vec!["O..", "..X"]);
+        assert_eq!(position.pieces.len(), 2);
+        assert_eq!(position.pieces[1].height(), 2);
+        assert!(Position::parse("O..\n..X\n").unwrap().pieces.is_empty());
+        assert!(Position::parse("O..\n..X\n\nPiece 2 2:\n**\n").is_err());
+    }
+
+    #[test]
+    fn detects_the_board_notation() {
+        let dump = "$$$ exec p1 : [a]\nPlateau 2 3:\n    012\n000 Oo.\n001 ..X\nPiece 1 1:\n*\n";
+        let position = Position::parse(dump).unwrap();
+        assert_eq!(position.plateau.to_map_rows(), vec!["OO.", "..X"]);
+        assert_eq!(position.pieces.len(), 1);
+
+        let bare = Position::parse("Plateau 2 3:\nO..\n..X\n").unwrap();
+        assert_eq!(bare.plateau.to_map_rows(), vec!["O..", "..X"]);
+
+        let compact = Position::parse("O11./12./11.X\n\nPiece 1 1:\n*\n").unwrap();
+        assert_eq!(compact.plateau.width(), 12);
+        assert_eq!(compact.plateau.to_map_rows()[2], "...........X");
+        assert_eq!(compact.pieces.len(), 1);
+        assert!(Position::parse("O2./3.X2").is_err());
+    }
+}
@@ -0,0 +1,110 @@
+use super::{BotKind, Interpreters};
+use crate::models::{PieceBagState, Plateau, Player};
+use std::fs;
+use std::path::Path;
+
+/// Smallest piece side the PieceBag can grow a shape in
+pub(crate) const MIN_PIECE_SIDE: usize = 3;
+
+pub fn check_bot(path: &str, interpreters: &Interpreters) -> Option<String> {
+    if !BotKind::of(path).is_process() {
+        return None;
+    }
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) => return Some(format!("Bot {}: {}", path, e)),
+    };
+    if !metadata.is_file() {
+        return Some(format!("Bot {}: not a file", path));
+    }
+    // Scripts are run through their interpreter and need not be executable
+    if interpreters.command_for(Path::new(path)).is_none() && !is_executable(&metadata) {
+        return Some(format!("Bot {}: not executable", path));
+    }
+    None
+}
+
+#[cfg(unix)]
+pub(crate) fn is_executable(metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+pub(crate) fn is_executable(_: &fs::Metadata) -> bool {
+    true
+}
+
+pub fn check_plateau(plateau: &Plateau) -> Vec<String> {
+    let mut problems = vec![];
+    let view = plateau.view();
+    let p1 = plateau.player_start(Player::Player1);
+    let p2 = plateau.player_start(Player::Player2);
+
+    for (player, start) in [(Player::Player1, p1), (Player::Player2, p2)].iter() {
+        if !view.is_in_bounds(start) {
+            problems.push(format!("Start of {} is out of bounds", player));
+        } else if view.get(start) != Some(*player) {
+            problems.push(format!("Start of {} is not owned by {}", player, player));
+        }
+    }
+    if p1.x == p2.x && p1.y == p2.y {
+        problems.push(String::from("Both players start on the same cell"));
+    }
+    problems
+}
+
+pub fn check_piece_bag(bag: &PieceBagState, plateau: &Plateau) -> Vec<String> {
+    let mut problems = vec![];
+    let ranges = [
+        ("width", bag.width_range, plateau.width()),
+        ("height", bag.height_range, plateau.height()),
+    ];
+
+    for (name, [min, max], limit) in ranges.iter() {
+        if *min < MIN_PIECE_SIDE {
+            problems.push(format!(
+                "Piece {} range starts below {}",
+                name, MIN_PIECE_SIDE
+            ));
+        }
+        if min >= max {
+            problems.push(format!("Piece {} range {}..{} is empty", name, min, max));
+        }
+        if *max > limit + 1 {
+            problems.push(format!(
+                "Pieces may be {} cells in {}, plateau is only {}",
+                max - 1,
+                name,
+                limit
+            ));
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PieceBag;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn sane_defaults_pass() {
+        let plateau = Plateau::default();
+        assert!(check_plateau(&plateau).is_empty());
+        assert!(check_piece_bag(&PieceBag::default().state(), &plateau).is_empty());
+    }
+
+    #[test]
+    fn oversized_pieces_are_reported() {
+        let plateau = Plateau::try_from(String::from("O..\n..X\n")).unwrap();
+        let problems = check_piece_bag(&PieceBag::default().state(), &plateau);
+        assert_eq!(problems.len(), 2);
+    }
+
+    #[test]
+    fn missing_bot_is_reported() {
+        assert!(check_bot("./does/not/exist.filler", &Interpreters::default()).is_some());
+    }
+}
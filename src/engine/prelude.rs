@@ -0,0 +1,67 @@
+use crate::models::Player;
+use std::path::Path;
+
+/// The line sent by the original VM, which some bots parse to learn their seat
+const DEFAULT_TEMPLATE: &str = "$$$ exec p{player} : {path}";
+
+/// First line sent to a bot once it is spawned. The template may contain the
+/// placeholders `{player}` (1 or 2), `{path}`, `{name}` and `{opponent}`.
+#[derive(Debug, Clone)]
+pub struct Prelude {
+    template: String,
+}
+
+impl Default for Prelude {
+    fn default() -> Self {
+        Prelude::new(DEFAULT_TEMPLATE)
+    }
+}
+
+impl Prelude {
+    pub fn new(template: &str) -> Self {
+        Prelude {
+            template: String::from(template),
+        }
+    }
+
+    pub fn render(&self, player: Player, path: &str, name: &str, opponent: &str) -> String {
+        let number = match player {
+            Player::Player1 => "1",
+            Player::Player2 => "2",
+        };
+        let mut line = self
+            .template
+            .replace("{player}", number)
+            .replace("{path}", path)
+            .replace("{name}", name)
+            .replace("{opponent}", opponent);
+        line.push('\n');
+        line
+    }
+}
+
+/// Name shown for a bot when none was configured, its executable's file name
+pub fn default_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| String::from(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_matches_original_vm() {
+        let line = Prelude::default().render(Player::Player2, "./bot.filler", "bot", "other");
+        assert_eq!(line, "$$$ exec p2 : ./bot.filler\n");
+    }
+
+    #[test]
+    fn custom_template() {
+        let prelude = Prelude::new("$$$ p{player} {name} vs {opponent}");
+        let line = prelude.render(Player::Player1, "./a.filler", "alice", "bob");
+        assert_eq!(line, "$$$ p1 alice vs bob\n");
+    }
+}
@@ -0,0 +1,251 @@
+use crate::models::constants::*;
+use crate::models::{PlateauView, Player, Point};
+use std::fmt::Write;
+
+/// Moves the cursor to the top left corner and clears the screen
+const CLEAR: &str = "\x1b[H\x1b[2J";
+const RESET: &str = "\x1b[0m";
+
+/// What a cell of the board is drawn as
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tile {
+    Empty,
+    Blocked,
+    Hill,
+    /// A claimed cell, `new` when the last piece placed covers it
+    Claimed {
+        player: Player,
+        new: bool,
+    },
+}
+
+impl Tile {
+    /// The token the cell is written as, as in the protocol
+    pub fn token(self) -> char {
+        match self {
+            Tile::Empty => EMPTY,
+            Tile::Blocked => BLOCKED,
+            Tile::Hill => HILL,
+            Tile::Claimed {
+                player: Player::Player1,
+                new: false,
+            } => PLAYER1,
+            Tile::Claimed {
+                player: Player::Player1,
+                new: true,
+            } => PLAYER1_NEW,
+            Tile::Claimed {
+                player: Player::Player2,
+                new: false,
+            } => PLAYER2,
+            Tile::Claimed {
+                player: Player::Player2,
+                new: true,
+            } => PLAYER2_NEW,
+        }
+    }
+
+    /// ANSI colour code the cell is drawn in, new pieces in a brighter shade
+    fn ansi(self) -> Option<&'static str> {
+        match self {
+            Tile::Empty => None,
+            Tile::Blocked => Some("90"),
+            Tile::Hill => Some("33"),
+            Tile::Claimed {
+                player: Player::Player1,
+                new: false,
+            } => Some("31"),
+            Tile::Claimed {
+                player: Player::Player1,
+                new: true,
+            } => Some("91;1"),
+            Tile::Claimed {
+                player: Player::Player2,
+                new: false,
+            } => Some("34"),
+            Tile::Claimed {
+                player: Player::Player2,
+                new: true,
+            } => Some("94;1"),
+        }
+    }
+
+    /// CSS classes of the cell, styled by `css`
+    fn classes(self) -> Option<&'static str> {
+        match self {
+            Tile::Empty => None,
+            Tile::Blocked => Some("blocked"),
+            Tile::Hill => Some("hill"),
+            Tile::Claimed {
+                player: Player::Player1,
+                new: false,
+            } => Some("p1"),
+            Tile::Claimed {
+                player: Player::Player1,
+                new: true,
+            } => Some("p1 new"),
+            Tile::Claimed {
+                player: Player::Player2,
+                new: false,
+            } => Some("p2"),
+            Tile::Claimed {
+                player: Player::Player2,
+                new: true,
+            } => Some("p2 new"),
+        }
+    }
+}
+
+/// A board drawn offscreen. Every visual output is written from one, so the
+/// terminal, casts and the status page all show the same board.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    tiles: Vec<Tile>,
+}
+
+impl Frame {
+    pub fn draw(plateau: PlateauView) -> Frame {
+        let tiles = plateau
+            .cells()
+            .map(|(p, owner)| match owner {
+                Some(player) => Tile::Claimed {
+                    player,
+                    new: plateau.is_new(&p),
+                },
+                None if plateau.is_blocked(&p) => Tile::Blocked,
+                None if plateau.is_hill(&p) => Tile::Hill,
+                None => Tile::Empty,
+            })
+            .collect();
+        Frame {
+            width: plateau.width(),
+            height: plateau.height(),
+            tiles,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The tile at `p`, `None` out of bounds
+    pub fn get(&self, p: &Point) -> Option<Tile> {
+        if p.x < 0 || p.y < 0 || p.x as usize >= self.width || p.y as usize >= self.height {
+            return None;
+        }
+        Some(self.tiles[p.y as usize * self.width + p.x as usize])
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[Tile]> {
+        self.tiles.chunks(self.width.max(1))
+    }
+
+    /// The board with its coordinates, laid out like `Plateau::render_human`
+    pub fn to_text(&self) -> String {
+        self.write(false)
+    }
+
+    /// `to_text` with the cells coloured for a terminal
+    pub fn to_ansi(&self) -> String {
+        self.write(true)
+    }
+
+    /// Clears the screen and draws a status line over the coloured board
+    pub fn to_screen(&self, status: &str) -> String {
+        let mut out = format!("{}{}\r\n", CLEAR, status);
+        for line in self.to_ansi().lines() {
+            out += line;
+            out += "\r\n";
+        }
+        out
+    }
+
+    /// The board as an HTML table, its cells styled by `css`
+    pub fn to_html(&self) -> String {
+        let mut html = String::from("<table>\n");
+        for row in self.rows() {
+            html += "<tr>";
+            for tile in row {
+                match tile.classes() {
+                    Some(classes) => {
+                        let _ = write!(html, "<td class=\"{}\"></td>", classes);
+                    }
+                    None => html += "<td></td>",
+                }
+            }
+            html += "</tr>\n";
+        }
+        html += "</table>\n";
+        html
+    }
+
+    fn write(&self, colored: bool) -> String {
+        let mut out = format!("Plateau {} {}:\n    ", self.height, self.width);
+        out.extend((0..self.width).map(|x| (x % 10).to_string()));
+        out.push('\n');
+        for (y, row) in self.rows().enumerate() {
+            let _ = write!(out, "{:03} ", y);
+            for tile in row {
+                match tile.ansi().filter(|_| colored) {
+                    Some(code) => {
+                        let _ = write!(out, "\x1b[{}m{}{}", code, tile.token(), RESET);
+                    }
+                    None => out.push(tile.token()),
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Style sheet for `Frame::to_html`, with the players drawn in `colors`
+pub fn css(colors: &[String; 2]) -> String {
+    let mut css = String::from(
+        "table { border-collapse: collapse; }\n\
+         td { width: 6px; height: 6px; padding: 0; background: #eee; }\n\
+         td.blocked { background: #444; }\n\
+         td.hill { background: #ec4; }\n\
+         td.new { filter: brightness(1.4); }\n",
+    );
+    let _ = writeln!(css, "td.p1 {{ background: {}; }}", colors[0]);
+    let _ = writeln!(css, "td.p2 {{ background: {}; }}", colors[1]);
+    css
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Piece, Plateau};
+
+    #[test]
+    fn every_output_draws_the_same_tiles() {
+        let mut plateau = Plateau::default();
+        let start = plateau.player_start(Player::Player1);
+        plateau
+            .place_piece(&Piece::new(1, 1, vec![true]), &start, Player::Player1)
+            .unwrap();
+        let frame = Frame::draw(plateau.view());
+
+        assert_eq!(frame.get(&start).map(Tile::token), Some(PLAYER1_NEW));
+        assert_eq!(frame.to_text(), plateau.render_human());
+        let colored = frame.to_ansi().replace(RESET, "");
+        assert_eq!(
+            colored.matches("\x1b[").count(),
+            frame
+                .tiles
+                .iter()
+                .filter(|tile| **tile != Tile::Empty)
+                .count()
+        );
+        let html = frame.to_html();
+        assert_eq!(html.matches("<tr>").count(), frame.height());
+        assert_eq!(html.matches("<td").count(), frame.width() * frame.height());
+    }
+}
@@ -0,0 +1,403 @@
+use super::engine::hash_hex;
+use super::game_id::new_game_id;
+use super::{Outcome, PlayerResponse, RuleSet, Termination};
+use crate::models::{Identity, PieceBag, Plateau, Point};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::ops::Range;
+
+/// Version of the engine, as recorded in the headers of its replays
+const ENGINE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Settings recorded with the rules in the header that are not part of
+/// `RuleSet`
+const RECORDED_ALONGSIDE: [&str; 2] = ["scoring", "fog"];
+
+/// `MAJOR.MINOR.PATCH` as numbers, so versions compare in order
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let mut parts = version.splitn(3, '.').map(|part| part.parse().ok());
+    Some((parts.next()??, parts.next()??, parts.next()??))
+}
+
+/// A saved game, as written by `Engine::replay`
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Replay {
+    #[serde(default)]
+    pub header: Value,
+    #[serde(default)]
+    pub game_id: Option<String>,
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+    #[serde(default)]
+    pub started_at: i64,
+    pub players: Vec<String>,
+    #[serde(default)]
+    pub identities: Vec<Identity>,
+    pub plateau: ReplayPlateau,
+    pub history: Vec<PlayerResponse>,
+    pub termination: Option<Termination>,
+    #[serde(default)]
+    pub outcome: Option<Outcome>,
+}
+
+/// The board a replay starts from
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ReplayPlateau {
+    pub width: usize,
+    pub height: usize,
+    pub player1_start: Point,
+    pub player2_start: Point,
+    /// Starting board in map file format, older replays only have the starts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rows: Option<Vec<String>>,
+}
+
+impl Replay {
+    pub fn from_json(json: &str) -> Result<Replay, String> {
+        serde_json::from_str(json).map_err(|e| e.to_string())
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// The board before the first move
+    pub fn initial_plateau(&self) -> Result<Plateau, String> {
+        let plateau = &self.plateau;
+        let mut initial = match &plateau.rows {
+            Some(rows) => Plateau::try_from(rows.join("\n"))?,
+            None => Plateau::new(
+                plateau.width,
+                plateau.height,
+                &plateau.player1_start,
+                &plateau.player2_start,
+            )?,
+        };
+        self.rules()?.prepare(&mut initial);
+        Ok(initial)
+    }
+
+    /// Rules recorded in the header, the defaults for replays without them.
+    /// Fails for replays of a newer engine or with rules this one does not
+    /// know, which it could not play back faithfully.
+    pub fn rules(&self) -> Result<RuleSet, String> {
+        if let Some(version) = self.header.get("engine_version") {
+            let known = version
+                .as_str()
+                .and_then(parse_version)
+                .is_some_and(|version| version <= parse_version(ENGINE_VERSION).unwrap());
+            if !known {
+                return Err(format!(
+                    "Replay written by engine version {}, this is {}",
+                    version, ENGINE_VERSION
+                ));
+            }
+        }
+        let rules = match self.header.get("rules") {
+            Some(rules) => rules,
+            None => return Ok(RuleSet::default()),
+        };
+        let known = serde_json::to_value(RuleSet::default()).unwrap();
+        let unknown = rules.as_object().and_then(|rules| {
+            rules.keys().find(|key| {
+                known.get(key.as_str()).is_none() && !RECORDED_ALONGSIDE.contains(&key.as_str())
+            })
+        });
+        if let Some(key) = unknown {
+            return Err(format!("Unknown rule in the replay header: {}", key));
+        }
+        serde_json::from_value(rules.clone()).map_err(|e| format!("Replay rules: {}", e))
+    }
+
+    /// A bag that deals the game's random pieces again from the start, from
+    /// the seed in the header. `None` if the header has no seed or the game
+    /// was dealt a fixed list of pieces.
+    pub fn piece_bag(&self) -> Option<PieceBag> {
+        let bag = &self.header["piece_bag"];
+        if bag["fixed"].as_bool() == Some(true) {
+            return None;
+        }
+        let range = |key: &str| serde_json::from_value::<[usize; 2]>(bag[key].clone()).ok();
+        let piece_bag = PieceBag::with_seed(
+            bag["seed"].as_u64()?,
+            range("width_range")?,
+            range("height_range")?,
+        );
+        Some(match bag["limit"].as_u64() {
+            Some(limit) => piece_bag.with_limit(limit as usize),
+            None => piece_bag,
+        })
+    }
+
+    /// Moves played before the first one in `history`, non-zero for slices
+    fn first_move(&self) -> usize {
+        self.metadata
+            .get("first_move")
+            .and_then(|first| first.parse().ok())
+            .unwrap_or(0)
+    }
+
+    /// Plays `response`, the `move_number`th move of `history`, onto
+    /// `plateau` the way the engine did, every piece of a multi-piece turn
+    /// included
+    pub fn apply(
+        &self,
+        plateau: &mut Plateau,
+        move_number: usize,
+        response: &PlayerResponse,
+    ) -> Result<(), String> {
+        let placements = response.placements();
+        if response.error.is_none() && placements.len() != 1 + response.batch.len() {
+            return Err(String::from(
+                "The turn does not record a placement for every piece",
+            ));
+        }
+        plateau.set_move_number(self.first_move() + move_number);
+        for (piece, point) in placements {
+            plateau
+                .place_piece(piece, &point, response.player)
+                .map_err(|e| e.to_string())?;
+        }
+        self.rules()?
+            .after_move(plateau, self.first_move() + move_number + 1);
+        Ok(())
+    }
+
+    /// Board after the first `moves` moves
+    pub fn plateau_at(&self, moves: usize) -> Result<Plateau, String> {
+        let mut plateau = self.initial_plateau()?;
+        for (move_number, response) in self.history.iter().take(moves).enumerate() {
+            self.apply(&mut plateau, move_number, response)
+                .map_err(|e| format!("Move {}: {}", move_number, e))?;
+        }
+        Ok(plateau)
+    }
+
+    /// Replays the game and returns the first move whose recorded board hash
+    /// does not match the rebuilt board, `None` if every recorded hash agrees
+    pub fn first_divergence(&self) -> Result<Option<usize>, String> {
+        let mut plateau = self.initial_plateau()?;
+        // Slices start mid-game, the hash is of the board the game started on
+        if let (Some(hash), 0) = (self.header["map_hash"].as_str(), self.first_move()) {
+            if hash != hash_hex(&plateau) {
+                return Err(String::from(
+                    "The starting board does not match the map hash in the header",
+                ));
+            }
+        }
+        for (move_number, response) in self.history.iter().enumerate() {
+            if self.apply(&mut plateau, move_number, response).is_err() {
+                return Ok(Some(move_number));
+            }
+            match &response.board_hash {
+                Some(hash) if *hash != hash_hex(&plateau) => return Ok(Some(move_number)),
+                _ => {}
+            }
+        }
+        Ok(None)
+    }
+
+    /// A standalone replay of the moves in `range`, starting from the board
+    /// as it stood before the first of them
+    pub fn slice(&self, range: Range<usize>) -> Result<Replay, String> {
+        if range.start >= range.end || range.end > self.history.len() {
+            return Err(format!(
+                "Moves {}..{} are not within the game's {} moves",
+                range.start,
+                range.end,
+                self.history.len()
+            ));
+        }
+        let start = self.plateau_at(range.start)?;
+
+        let mut metadata = self.metadata.clone();
+        if let Some(game_id) = &self.game_id {
+            metadata.insert(String::from("sliced_from"), game_id.clone());
+        }
+        let first_move = self.first_move() + range.start;
+        metadata.insert(String::from("first_move"), first_move.to_string());
+
+        let started_at = match range.start {
+            0 => self.started_at,
+            n => self.history[n - 1].timestamp,
+        };
+        let (termination, outcome) = match range.end == self.history.len() {
+            true => (self.termination, self.outcome),
+            false => (None, None),
+        };
+
+        Ok(Replay {
+            header: self.header.clone(),
+            game_id: Some(new_game_id()),
+            metadata,
+            started_at,
+            players: self.players.clone(),
+            identities: self.identities.clone(),
+            plateau: ReplayPlateau {
+                rows: Some(start.to_map_rows()),
+                ..self.plateau.clone()
+            },
+            history: self.history[range].to_vec(),
+            termination,
+            outcome,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPLAY: &str = r#"{
+        "started_at": 1000,
+        "players": ["a", "b"],
+        "plateau": {"width": 4, "height": 2,
+            "player1_start": {"x": 0, "y": 0}, "player2_start": {"x": 3, "y": 1}},
+        "history": [
+            {"player": "Player1", "piece": {"width": 2, "height": 1, "cells": [1, 1]},
+             "raw_response": "0 0\n", "placement": {"x": 0, "y": 0}, "placement_count": 1,
+             "error": null, "timestamp": 1010},
+            {"player": "Player2", "piece": {"width": 1, "height": 1, "cells": [1]},
+             "raw_response": "9 9\n", "placement": {"x": 9, "y": 9}, "placement_count": 0,
+             "error": "Piece out of bounds", "timestamp": 1015},
+            {"player": "Player1", "piece": {"width": 2, "height": 1, "cells": [1, 1]},
+             "raw_response": "0 1\n", "placement": {"x": 1, "y": 0}, "placement_count": 2,
+             "error": null, "timestamp": 1030}
+        ],
+        "termination": "ErrorThreshold"
+    }"#;
+
+    #[test]
+    fn reconstructs_boards() {
+        let replay = Replay::from_json(REPLAY).unwrap();
+        assert_eq!(
+            replay.plateau_at(0).unwrap().to_map_rows(),
+            vec!["O...", "...X"]
+        );
+        assert_eq!(
+            replay.plateau_at(3).unwrap().to_map_rows(),
+            vec!["OOO.", "...X"]
+        );
+    }
+
+    #[test]
+    fn slices_start_from_the_reconstructed_board() {
+        let replay = Replay::from_json(REPLAY).unwrap();
+        let slice = replay.slice(1..3).unwrap();
+
+        assert_eq!(slice.history.len(), 2);
+        assert_eq!(slice.started_at, 1010);
+        assert_eq!(slice.termination, Some(Termination::ErrorThreshold));
+        assert_eq!(
+            slice.plateau_at(2).unwrap().to_map_rows(),
+            vec!["OOO.", "...X"]
+        );
+
+        let round_trip = Replay::from_json(&slice.to_json()).unwrap();
+        assert_eq!(round_trip.metadata["first_move"], "1");
+        assert!(replay.slice(2..5).is_err());
+    }
+
+    #[test]
+    fn finds_tampered_moves() {
+        let mut replay = Replay::from_json(REPLAY).unwrap();
+        let mut plateau = replay.initial_plateau().unwrap();
+        for i in 0..replay.history.len() {
+            let response = replay.history[i].clone();
+            replay.apply(&mut plateau, i, &response).unwrap();
+            replay.history[i].board_hash = Some(hash_hex(&plateau));
+        }
+        assert_eq!(replay.first_divergence(), Ok(None));
+
+        replay.history[2].placement = Some(Point::new(2, 0));
+        assert_eq!(replay.first_divergence(), Ok(Some(2)));
+    }
+
+    #[test]
+    fn rejects_headers_it_cannot_honour() {
+        use serde_json::json;
+
+        let mut replay = Replay::from_json(REPLAY).unwrap();
+        replay.header = json!({
+            "engine_version": ENGINE_VERSION,
+            "rules": {"pieces_per_turn": 2, "scoring": null, "fog": [null, null]},
+        });
+        assert_eq!(replay.rules().unwrap().pieces_per_turn, 2);
+
+        replay.header["rules"]["pieces_per_turn"] = json!("two");
+        assert!(replay.rules().is_err());
+        replay.header["rules"] = json!({"gravity": true});
+        assert_eq!(
+            replay.rules(),
+            Err(String::from("Unknown rule in the replay header: gravity"))
+        );
+        replay.header["rules"] = json!({});
+        replay.header["engine_version"] = json!("99.0.0");
+        assert!(replay.plateau_at(1).is_err());
+        replay.header["engine_version"] = json!("nightly");
+        assert!(replay.rules().is_err());
+
+        replay.header["engine_version"] = json!(ENGINE_VERSION);
+        let initial = replay.initial_plateau().unwrap();
+        replay.header["map_hash"] = json!(hash_hex(&initial));
+        assert_eq!(replay.first_divergence(), Ok(None));
+        replay.plateau.player2_start = Point::new(2, 1);
+        assert!(replay.first_divergence().is_err());
+    }
+
+    #[test]
+    fn replays_every_piece_of_a_batch() {
+        use crate::engine::{native_bot, Engine};
+        use crate::models::Player;
+
+        let mut builder = Engine::builder(native_bot::GREEDY);
+        builder.with_player2(native_bot::RANDOM);
+        builder.with_map("tiny").unwrap();
+        builder.with_piecebag(PieceBag::with_seed(3, [3, 4], [3, 4]).with_limit(12));
+        builder.with_pieces_per_turn(2);
+        let mut engine = builder.finish().unwrap();
+        engine.run();
+
+        let replay = Replay::from_json(&engine.replay()).unwrap();
+        let batches = replay
+            .history
+            .iter()
+            .filter(|response| response.placements().len() == 2)
+            .count();
+        assert!(batches > 0);
+        assert_eq!(replay.first_divergence(), Ok(None));
+        let placed: usize = replay
+            .history
+            .iter()
+            .map(|response| response.placements().len())
+            .sum();
+        assert_eq!(
+            placed,
+            engine.placement_count(Player::Player1) + engine.placement_count(Player::Player2)
+        );
+    }
+
+    #[test]
+    fn piece_bag_deals_the_recorded_pieces() {
+        use crate::engine::{native_bot, Engine};
+
+        let mut builder = Engine::builder(native_bot::RANDOM);
+        builder.with_player2(native_bot::RANDOM);
+        builder.with_map("tiny").unwrap();
+        builder.with_piecebag(PieceBag::with_seed(7, [3, 5], [3, 5]).with_limit(6));
+        let mut engine = builder.finish().unwrap();
+        engine.run();
+
+        let replay = Replay::from_json(&engine.replay()).unwrap();
+        let dealt: Vec<String> = replay.piece_bag().unwrap().map(|p| p.to_string()).collect();
+        let recorded: Vec<String> = replay
+            .history
+            .iter()
+            .map(|response| response.piece.to_string())
+            .collect();
+        assert_eq!(dealt, recorded);
+        assert!(Replay::from_json(REPLAY).unwrap().piece_bag().is_none());
+    }
+}
@@ -0,0 +1,162 @@
+use super::{PlayerResponse, Replay};
+use crate::models::Plateau;
+
+/// Moves between the boards a cursor keeps to step back from
+pub const DEFAULT_SNAPSHOT_EVERY: usize = 32;
+
+/// Steps through a replay in either direction. The board is kept after every
+/// `snapshot_every` moves, so stepping back or seeking only replays the moves
+/// since the nearest kept board.
+pub struct ReplayCursor {
+    replay: Replay,
+    snapshot_every: usize,
+    snapshots: Vec<Plateau>,
+    plateau: Plateau,
+    position: usize,
+}
+
+impl ReplayCursor {
+    pub fn new(replay: Replay) -> Result<ReplayCursor, String> {
+        ReplayCursor::with_snapshot_every(replay, DEFAULT_SNAPSHOT_EVERY)
+    }
+
+    pub fn with_snapshot_every(
+        replay: Replay,
+        snapshot_every: usize,
+    ) -> Result<ReplayCursor, String> {
+        let plateau = replay.initial_plateau()?;
+        Ok(ReplayCursor {
+            replay,
+            snapshot_every: snapshot_every.max(1),
+            snapshots: vec![plateau.clone()],
+            plateau,
+            position: 0,
+        })
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    /// Moves played to reach the current board
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn len(&self) -> usize {
+        self.replay.history.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.replay.history.is_empty()
+    }
+
+    pub fn plateau(&self) -> &Plateau {
+        &self.plateau
+    }
+
+    /// The move that led to the current board, `None` at the start
+    pub fn last_move(&self) -> Option<&PlayerResponse> {
+        self.position
+            .checked_sub(1)
+            .and_then(|i| self.replay.history.get(i))
+    }
+
+    /// Plays the next move, returning it or `None` at the end of the game
+    pub fn step_forward(&mut self) -> Result<Option<&PlayerResponse>, String> {
+        let move_number = self.position;
+        let response = match self.replay.history.get(move_number) {
+            Some(response) => response,
+            None => return Ok(None),
+        };
+        self.replay
+            .apply(&mut self.plateau, move_number, response)
+            .map_err(|e| format!("Move {}: {}", move_number, e))?;
+        self.position += 1;
+
+        let kept = self.snapshots.len() * self.snapshot_every;
+        if self.position == kept {
+            self.snapshots.push(self.plateau.clone());
+        }
+        Ok(Some(response))
+    }
+
+    /// Takes back the last move, returning `false` at the start of the game
+    pub fn step_back(&mut self) -> Result<bool, String> {
+        match self.position {
+            0 => Ok(false),
+            position => self.seek(position - 1).map(|_| true),
+        }
+    }
+
+    /// Moves to the board after the first `moves` moves
+    pub fn seek(&mut self, moves: usize) -> Result<(), String> {
+        if moves > self.len() {
+            return Err(format!(
+                "Move {} is past the game's {} moves",
+                moves,
+                self.len()
+            ));
+        }
+        let nearest = (moves / self.snapshot_every).min(self.snapshots.len() - 1);
+        let from = nearest * self.snapshot_every;
+        if moves < self.position || self.position < from {
+            self.plateau = self.snapshots[nearest].clone();
+            self.position = from;
+        }
+        while self.position < moves {
+            self.step_forward()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replay() -> Replay {
+        Replay::from_json(
+            r#"{
+            "players": ["a", "b"],
+            "plateau": {"width": 4, "height": 1,
+                "player1_start": {"x": 0, "y": 0}, "player2_start": {"x": 3, "y": 0}},
+            "history": [
+                {"player": "Player1", "piece": {"width": 2, "height": 1, "cells": [1, 1]},
+                 "raw_response": "0 0\n", "placement": {"x": 0, "y": 0}, "placement_count": 1,
+                 "error": null},
+                {"player": "Player2", "piece": {"width": 1, "height": 1, "cells": [1]},
+                 "raw_response": "0 2\n", "placement": {"x": 2, "y": 0}, "placement_count": 1,
+                 "error": "Piece overlaps opponent"},
+                {"player": "Player1", "piece": {"width": 2, "height": 1, "cells": [1, 1]},
+                 "raw_response": "0 1\n", "placement": {"x": 1, "y": 0}, "placement_count": 2,
+                 "error": null}
+            ],
+            "termination": null
+        }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn stepping_back_matches_replaying_from_the_start() {
+        let replay = replay();
+        let mut cursor = ReplayCursor::with_snapshot_every(replay.clone(), 2).unwrap();
+        while cursor.step_forward().unwrap().is_some() {}
+        assert_eq!(cursor.position(), 3);
+
+        for moves in (0..3).rev() {
+            assert!(cursor.step_back().unwrap());
+            assert_eq!(cursor.position(), moves);
+            assert_eq!(
+                cursor.plateau().to_string(),
+                replay.plateau_at(moves).unwrap().to_string()
+            );
+        }
+        assert!(!cursor.step_back().unwrap());
+
+        cursor.seek(3).unwrap();
+        assert_eq!(cursor.last_move().unwrap().placement_count, 2);
+        assert!(cursor.seek(4).is_err());
+    }
+}
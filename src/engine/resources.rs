@@ -0,0 +1,58 @@
+use serde::Serialize;
+use std::fs;
+
+/// Clock ticks per second used by `/proc/<pid>/stat`, fixed on Linux
+const USER_HZ: u64 = 100;
+
+/// A bot process's resource usage, taken right after one of its moves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ResourceSample {
+    /// Index of the move in the game, counting both players
+    pub move_number: usize,
+    /// Resident memory in kilobytes
+    pub rss_kb: u64,
+    /// User and system CPU time used so far, in milliseconds
+    pub cpu_ms: u64,
+}
+
+/// Reads the usage of process `pid` from procfs. Returns `None` where there
+/// is no procfs or the process has exited.
+pub fn sample(pid: u32, move_number: usize) -> Option<ResourceSample> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    let status = fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    Some(ResourceSample {
+        move_number,
+        rss_kb: parse_rss_kb(&status)?,
+        cpu_ms: parse_cpu_ticks(&stat)? * 1000 / USER_HZ,
+    })
+}
+
+/// `utime` plus `stime` from a stat line. The command name in brackets may
+/// contain spaces, so fields are counted from the closing bracket.
+fn parse_cpu_ticks(stat: &str) -> Option<u64> {
+    let fields: Vec<&str> = stat[stat.rfind(')')? + 1..].split_whitespace().collect();
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    Some(utime + stime)
+}
+
+fn parse_rss_kb(status: &str) -> Option<u64> {
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    line.split_whitespace().nth(1)?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_procfs_fields() {
+        let stat = "42 (my bot) S 1 42 42 0 -1 4194304 105 0 0 0 250 30 0 0 20 0 1";
+        assert_eq!(parse_cpu_ticks(stat), Some(280));
+        assert_eq!(
+            parse_rss_kb("Name:\tbot\nVmRSS:\t    1804 kB\n"),
+            Some(1804)
+        );
+        assert_eq!(parse_rss_kb("Name:\tbot\n"), None);
+    }
+}
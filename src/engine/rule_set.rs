@@ -0,0 +1,144 @@
+use super::preflight::MIN_PIECE_SIDE;
+use super::{Growth, TieBreak};
+use crate::models::{OverlapRule, PieceBag, Plateau, Player};
+use serde::{Deserialize, Serialize};
+
+/// The variant rules a game is played under. The engine takes them from
+/// its builder and records them in the replay header, where `Replay` reads
+/// them back to rebuild the game. Scoring and fog are recorded next to them
+/// but set separately, as scoring is pluggable and fog is per player.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RuleSet {
+    /// Own cells a piece has to cover, see `OverlapRule`
+    pub overlap: OverlapRule,
+    /// See `EngineBuilder::with_decay`
+    pub decay_window: Option<usize>,
+    /// See `EngineBuilder::with_growth`
+    pub growth: Option<Growth>,
+    /// See `EngineBuilder::with_tie_breaks`
+    pub tie_breaks: Vec<TieBreak>,
+    /// See `EngineBuilder::with_pieces_per_turn`
+    pub pieces_per_turn: usize,
+    /// See `EngineBuilder::mirror_pieces`
+    pub mirror_pieces: bool,
+    /// See `EngineBuilder::blind_start`
+    pub blind_start: bool,
+    /// See `EngineBuilder::with_watchdog`
+    pub watchdog: Option<usize>,
+    /// See `EngineBuilder::with_stagnation_limit`
+    pub stagnation_limit: Option<usize>,
+    /// See `EngineBuilder::with_player_piece_range`
+    pub piece_ranges: [Option<PieceRange>; 2],
+}
+
+/// Sides of the random pieces dealt to one player, the upper bounds
+/// exclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PieceRange {
+    pub width: [usize; 2],
+    pub height: [usize; 2],
+}
+
+impl PieceRange {
+    /// Bag of `player`'s pieces in a game whose shared bag follows `seed`
+    pub fn bag(&self, seed: u64, player: Player) -> PieceBag {
+        let seed = seed.wrapping_add(1 + player as u64);
+        PieceBag::with_seed(seed, self.width, self.height)
+    }
+
+    /// Fails unless both ranges hold a side the bag can grow a shape in
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, [min, max]) in [("width", self.width), ("height", self.height)] {
+            if min < MIN_PIECE_SIDE {
+                return Err(format!(
+                    "Piece {} range starts below {}",
+                    name, MIN_PIECE_SIDE
+                ));
+            }
+            if min >= max {
+                return Err(format!("Piece {} range {}..{} is empty", name, min, max));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        RuleSet {
+            overlap: OverlapRule::default(),
+            decay_window: None,
+            growth: None,
+            tie_breaks: vec![],
+            pieces_per_turn: 1,
+            mirror_pieces: false,
+            blind_start: false,
+            watchdog: None,
+            stagnation_limit: None,
+            piece_ranges: [None, None],
+        }
+    }
+}
+
+impl RuleSet {
+    pub fn from_json(json: &str) -> Result<RuleSet, String> {
+        let rules: RuleSet = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        rules.validate()?;
+        Ok(rules)
+    }
+
+    /// Fails on rules no game can be played under
+    pub fn validate(&self) -> Result<(), String> {
+        if self.pieces_per_turn == 0 {
+            return Err(String::from("At least one piece has to be dealt per turn"));
+        }
+        for range in self.piece_ranges.iter().flatten() {
+            range.validate()?;
+        }
+        Ok(())
+    }
+
+    /// A bag for each player dealt pieces of its own range, see
+    /// `EngineBuilder::with_player_piece_range`
+    pub fn player_bags(&self, seed: u64) -> [Option<PieceBag>; 2] {
+        let seats = [Player::Player1, Player::Player2];
+        seats.map(|player| self.piece_ranges[player as usize].map(|range| range.bag(seed, player)))
+    }
+
+    /// Sets up a plateau to be played on under these rules
+    pub fn prepare(&self, plateau: &mut Plateau) {
+        plateau.set_overlap_rule(self.overlap);
+    }
+
+    /// Applies the board rules due once the game has reached `moves_played`
+    /// moves
+    pub fn after_move(&self, plateau: &mut Plateau, moves_played: usize) {
+        if let Some(window) = self.decay_window {
+            plateau.decay(window);
+        }
+        if let Some(growth) = self.growth {
+            growth.apply(plateau, moves_played);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_rules_take_their_defaults() {
+        let rules = RuleSet::from_json(r#"{"decay_window": 4, "fog": [null, null]}"#).unwrap();
+        assert_eq!(rules.decay_window, Some(4));
+        assert_eq!(rules.pieces_per_turn, 1);
+
+        let json = serde_json::to_string(&rules).unwrap();
+        assert_eq!(RuleSet::from_json(&json), Ok(rules));
+        assert!(RuleSet::from_json(r#"{"pieces_per_turn": 0}"#).is_err());
+        let empty = r#"{"piece_ranges": [{"width": [5, 5], "height": [3, 6]}, null]}"#;
+        assert!(RuleSet::from_json(empty).is_err());
+        let thin = r#"{"piece_ranges": [null, {"width": [3, 6], "height": [1, 4]}]}"#;
+        assert!(RuleSet::from_json(thin).is_err());
+    }
+}
@@ -0,0 +1,183 @@
+use super::Interpreters;
+use std::ffi::OsString;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Tool used to give a bot its own namespaces on Linux
+const UNSHARE: &str = "unshare";
+/// Tool used to drop the capabilities a bot gets as root of its namespace
+const SETPRIV: &str = "setpriv";
+/// Remounts the root read-only inside the bot's mount namespace, then runs
+/// the bot passed as `$0` with the arguments after it. The bot is root in
+/// its namespace, so it is stripped of every capability first; otherwise it
+/// could simply remount the root read-write again.
+const READ_ONLY_SCRIPT: &str = "mount -o remount,bind,ro / && \
+    exec setpriv --bounding-set=-all --inh-caps=-all --no-new-privs -- \"$0\" \"$@\"";
+
+/// Restrictions a bot process is started under
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Sandbox {
+    /// Starts the bot in an empty network namespace through `unshare`, so it
+    /// can neither reach the network nor other bots
+    pub isolate_network: bool,
+    /// Directory the bot is started in, the engine's own by default
+    pub cwd: Option<PathBuf>,
+    /// Gives the bot a read-only view of the root filesystem. Filesystems
+    /// mounted separately below it stay as they are.
+    pub read_only: bool,
+    /// Arguments the bot is started with
+    pub args: Vec<String>,
+    /// Interpreters scripts are started with, see `Interpreters`
+    pub interpreters: Interpreters,
+}
+
+impl Sandbox {
+    /// Command that runs the bot at `path` under the sandbox
+    pub fn command(&self, path: &str) -> Command {
+        // A relative path would no longer resolve once the bot moves
+        let path = match &self.cwd {
+            Some(_) => fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path)),
+            None => PathBuf::from(path),
+        };
+        let mut program: Vec<OsString> = match self.interpreters.command_for(&path) {
+            Some(interpreter) => interpreter.iter().map(OsString::from).collect(),
+            None => vec![],
+        };
+        program.push(path.into_os_string());
+
+        let mut command = match self.uses_namespaces() {
+            false => {
+                let mut command = Command::new(&program[0]);
+                command.args(&program[1..]);
+                command
+            }
+            true => {
+                let mut command = Command::new(UNSHARE);
+                command.arg("--map-root-user");
+                if self.isolate_network {
+                    command.arg("--net");
+                }
+                match self.read_only {
+                    true => command.args(["--mount", "--", "sh", "-c", READ_ONLY_SCRIPT]),
+                    false => command.arg("--"),
+                };
+                command.args(&program);
+                command
+            }
+        };
+        command.args(&self.args);
+        if let Some(cwd) = &self.cwd {
+            command.current_dir(cwd);
+        }
+        command
+    }
+
+    fn uses_namespaces(&self) -> bool {
+        self.isolate_network || self.read_only
+    }
+
+    /// Why the sandbox cannot be set up on this host, if it cannot
+    pub fn check(&self) -> Option<String> {
+        if let Some(cwd) = &self.cwd {
+            if !cwd.is_dir() {
+                return Some(format!("Bot directory {} does not exist", cwd.display()));
+            }
+        }
+        if !self.uses_namespaces() || cfg!(target_os = "linux") && has_unshare() {
+            return match self.read_only && !has_setpriv() {
+                true => Some(String::from(
+                    "Read-only filesystems need the setpriv tool to drop the bot's privileges",
+                )),
+                false => None,
+            };
+        }
+        Some(String::from(
+            "Network isolation and read-only filesystems need Linux with the unshare tool",
+        ))
+    }
+}
+
+fn has_unshare() -> bool {
+    Command::new(UNSHARE)
+        .args(["--map-root-user", "--net", "--mount", "--", "true"])
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+fn has_setpriv() -> bool {
+    Command::new(SETPRIV)
+        .args(["--no-new-privs", "--", "true"])
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_sandbox_runs_the_bot_directly() {
+        let command = Sandbox::default().command("./bot.filler");
+        assert_eq!(command.get_program(), "./bot.filler");
+
+        let sandbox = Sandbox {
+            isolate_network: true,
+            ..Sandbox::default()
+        };
+        let command = sandbox.command("./bot.filler");
+        assert_eq!(command.get_program(), UNSHARE);
+        assert_eq!(command.get_args().last().unwrap(), "./bot.filler");
+    }
+
+    #[test]
+    fn read_only_remounts_before_running_the_bot() {
+        let sandbox = Sandbox {
+            read_only: true,
+            cwd: Some(PathBuf::from("/")),
+            ..Sandbox::default()
+        };
+        let command = sandbox.command("/bin/bot");
+        let args: Vec<_> = command.get_args().collect();
+
+        assert!(args.contains(&"--mount".as_ref()));
+        assert!(!args.contains(&"--net".as_ref()));
+        assert_eq!(args.last().unwrap(), &"/bin/bot");
+        assert_eq!(command.get_current_dir().unwrap(), PathBuf::from("/"));
+    }
+
+    #[test]
+    fn read_only_bots_cannot_remount_the_root() {
+        let sandbox = Sandbox {
+            read_only: true,
+            args: vec![
+                String::from("-c"),
+                String::from(
+                    "mount -o remount,bind,rw / 2>/dev/null && echo remounted || echo refused",
+                ),
+            ],
+            ..Sandbox::default()
+        };
+        if sandbox.check().is_some() {
+            return;
+        }
+        let output = sandbox.command("/bin/sh").output().unwrap();
+
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "refused\n");
+    }
+
+    #[test]
+    fn scripts_run_under_their_interpreter() {
+        let sandbox = Sandbox {
+            args: vec![String::from("-v")],
+            ..Sandbox::default()
+        };
+        let command = sandbox.command("bots/bot.py");
+        let args: Vec<_> = command.get_args().collect();
+
+        assert_eq!(command.get_program(), "python3");
+        assert_eq!(args, ["bots/bot.py", "-v"]);
+    }
+}
@@ -0,0 +1,128 @@
+use crate::models::{PlateauView, Player};
+use serde_json::{json, Value};
+use std::str::FromStr;
+
+/// Hook to replace the default score, the number of pieces each player
+/// placed, with one computed from the final board
+pub trait Scoring {
+    fn score(&self, plateau: PlateauView, player: Player) -> usize;
+
+    /// The scoring's name and weights, recorded in the replay header
+    fn rules(&self) -> Value;
+}
+
+/// Scores each owned cell by how recently it was claimed: `full` points for
+/// a cell claimed in the last move, `decay` fewer for every move played
+/// since, but never less than `floor`. Parsed from `FULL,FLOOR,DECAY`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecencyScoring {
+    pub full: usize,
+    pub floor: usize,
+    pub decay: usize,
+}
+
+impl Scoring for RecencyScoring {
+    fn score(&self, plateau: PlateauView, player: Player) -> usize {
+        plateau
+            .cells()
+            .filter(|(_, owner)| *owner == Some(player))
+            .filter_map(|(p, _)| plateau.age(&p))
+            .map(|age| {
+                self.full
+                    .saturating_sub(age.saturating_mul(self.decay))
+                    .max(self.floor)
+            })
+            .sum()
+    }
+
+    fn rules(&self) -> Value {
+        json!({
+            "recency": {
+                "full": self.full,
+                "floor": self.floor,
+                "decay": self.decay,
+            }
+        })
+    }
+}
+
+impl FromStr for RecencyScoring {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let weights: Vec<usize> = s
+            .split(',')
+            .map(|weight| weight.trim().parse())
+            .collect::<Result<_, _>>()
+            .map_err(|_| format!("Invalid recency weights: {}", s))?;
+        match weights.as_slice() {
+            [full, floor, decay] => Ok(RecencyScoring {
+                full: *full,
+                floor: *floor,
+                decay: *decay,
+            }),
+            _ => Err(String::from("Expected FULL,FLOOR,DECAY")),
+        }
+    }
+}
+
+/// Scores one point per owned cell and `value` points per owned hill
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HillScoring {
+    pub value: usize,
+}
+
+impl Scoring for HillScoring {
+    fn score(&self, plateau: PlateauView, player: Player) -> usize {
+        plateau
+            .cells()
+            .filter(|(_, owner)| *owner == Some(player))
+            .map(|(p, _)| match plateau.is_hill(&p) {
+                true => self.value,
+                false => 1,
+            })
+            .sum()
+    }
+
+    fn rules(&self) -> Value {
+        json!({ "hill": { "value": self.value } })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Piece, Plateau, Point};
+
+    #[test]
+    fn recent_cells_score_more() {
+        let mut plateau = Plateau::new(4, 1, &Point::new(0, 0), &Point::new(3, 0)).unwrap();
+        let piece: Piece = "Piece 1 2:\n**\n".parse().unwrap();
+        plateau.set_move_number(1);
+        plateau
+            .place_piece(&piece, &Point::new(0, 0), Player::Player1)
+            .unwrap();
+        let scoring: RecencyScoring = "10,2,3".parse().unwrap();
+
+        // The start cell was overlapped by the piece, so both are fresh
+        assert_eq!(scoring.score(plateau.view(), Player::Player1), 20);
+        // Player2's start cell is one move old
+        assert_eq!(scoring.score(plateau.view(), Player::Player2), 7);
+        assert!("10,2".parse::<RecencyScoring>().is_err());
+    }
+
+    #[test]
+    fn hills_are_worth_their_value() {
+        use std::convert::TryFrom;
+
+        let mut plateau = Plateau::try_from(String::from("O+.\n..X\n")).unwrap();
+        let piece: Piece = "Piece 1 2:\n**\n".parse().unwrap();
+        plateau
+            .place_piece(&piece, &Point::new(0, 0), Player::Player1)
+            .unwrap();
+        let scoring = HillScoring { value: 5 };
+
+        assert_eq!(scoring.score(plateau.view(), Player::Player1), 6);
+        assert_eq!(scoring.score(plateau.view(), Player::Player2), 1);
+    }
+}
@@ -0,0 +1,89 @@
+use super::{OnPlayerResponse, PlayerResponse};
+use crate::models::{Plateau, PlateauView};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+/// Forwards moves to a spectator-facing observer a fixed number of moves late
+/// so a live feed can not be used as an oracle by the bots playing. The moves
+/// still buffered are released when the game ends.
+pub struct SpectatorDelay {
+    inner: Box<dyn OnPlayerResponse>,
+    delay: usize,
+    buffer: RefCell<VecDeque<(Plateau, PlayerResponse)>>,
+}
+
+impl SpectatorDelay {
+    pub fn new(inner: Box<dyn OnPlayerResponse>, delay: usize) -> Self {
+        SpectatorDelay {
+            inner,
+            delay,
+            buffer: RefCell::new(VecDeque::with_capacity(delay + 1)),
+        }
+    }
+}
+
+impl OnPlayerResponse for SpectatorDelay {
+    fn on_player_move(&self, plateau: PlateauView, player_response: &PlayerResponse) {
+        let mut buffer = self.buffer.borrow_mut();
+        buffer.push_back((plateau.to_plateau(), player_response.clone()));
+        if buffer.len() > self.delay {
+            let (plateau, response) = buffer.pop_front().unwrap();
+            self.inner.on_player_move(plateau.view(), &response);
+        }
+    }
+
+    fn on_game_end(&self, plateau: PlateauView) {
+        for (buffered, response) in self.buffer.borrow_mut().drain(..) {
+            self.inner.on_player_move(buffered.view(), &response);
+        }
+        self.inner.on_game_end(plateau);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Piece, Player};
+    use std::rc::Rc;
+
+    struct Recorder(Rc<RefCell<Vec<usize>>>);
+
+    impl OnPlayerResponse for Recorder {
+        fn on_player_move(&self, _: PlateauView, response: &PlayerResponse) {
+            self.0.borrow_mut().push(response.placement_count);
+        }
+    }
+
+    fn response(placement_count: usize) -> PlayerResponse {
+        PlayerResponse {
+            player: Player::Player1,
+            piece: Piece::new_blank(1, 1),
+            batch: vec![],
+            raw_response: None,
+            placement: None,
+            batch_placements: vec![],
+            placement_count,
+            error: None,
+            rejection: None,
+            timestamp: 0,
+            board_hash: None,
+            name: None,
+            notes: vec![],
+        }
+    }
+
+    #[test]
+    fn moves_are_released_late_then_flushed() {
+        let seen = Rc::new(RefCell::new(vec![]));
+        let delay = SpectatorDelay::new(Box::new(Recorder(seen.clone())), 2);
+        let plateau = Plateau::default();
+
+        for i in 0..3 {
+            delay.on_player_move(plateau.view(), &response(i));
+        }
+        assert_eq!(*seen.borrow(), vec![0]);
+
+        delay.on_game_end(plateau.view());
+        assert_eq!(*seen.borrow(), vec![0, 1, 2]);
+    }
+}
@@ -0,0 +1,275 @@
+use super::tournament::GameRecord;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where tournament results are kept. Backends only save and load game
+/// records, standings are rebuilt from them with `Standings::from_games`.
+pub trait Storage {
+    fn save_game(&mut self, game: &GameRecord) -> Result<(), String>;
+    /// Every saved game, in the order it was saved
+    fn games(&self) -> Result<Vec<GameRecord>, String>;
+}
+
+/// Opens the storage described by `spec`: `memory`, `sqlite:PATH` or a
+/// directory of JSON files, optionally written `json:DIR`
+pub fn open(spec: &str) -> Result<Box<dyn Storage>, String> {
+    if spec == "memory" {
+        return Ok(Box::new(MemoryStorage::default()));
+    }
+    if let Some(path) = spec.strip_prefix("sqlite:") {
+        return open_sqlite(Path::new(path));
+    }
+    let dir = spec.strip_prefix("json:").unwrap_or(spec);
+    Ok(Box::new(JsonDirStorage::open(Path::new(dir))?))
+}
+
+#[cfg(feature = "sqlite")]
+fn open_sqlite(path: &Path) -> Result<Box<dyn Storage>, String> {
+    Ok(Box::new(SqliteStorage::open(path)?))
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn open_sqlite(_: &Path) -> Result<Box<dyn Storage>, String> {
+    Err(String::from(
+        "SQLite storage needs a build with the sqlite feature",
+    ))
+}
+
+/// Keeps the games for as long as the process runs
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    games: Vec<GameRecord>,
+}
+
+impl Storage for MemoryStorage {
+    fn save_game(&mut self, game: &GameRecord) -> Result<(), String> {
+        self.games.push(game.clone());
+        Ok(())
+    }
+
+    fn games(&self) -> Result<Vec<GameRecord>, String> {
+        Ok(self.games.clone())
+    }
+}
+
+/// One JSON file per game in a directory, numbered in the order they were
+/// saved
+#[derive(Debug)]
+pub struct JsonDirStorage {
+    dir: PathBuf,
+    saved: usize,
+}
+
+impl JsonDirStorage {
+    /// Creates `dir` if need be, new games are numbered after those already
+    /// in it
+    pub fn open(dir: &Path) -> Result<JsonDirStorage, String> {
+        fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+        let mut storage = JsonDirStorage {
+            dir: dir.to_path_buf(),
+            saved: 0,
+        };
+        storage.saved = storage.files()?.len();
+        Ok(storage)
+    }
+
+    fn files(&self) -> Result<Vec<PathBuf>, String> {
+        let entries =
+            fs::read_dir(&self.dir).map_err(|e| format!("{}: {}", self.dir.display(), e))?;
+        let mut files: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                name.starts_with("game-") && name.ends_with(".json")
+            })
+            .collect();
+        files.sort();
+        Ok(files)
+    }
+}
+
+impl Storage for JsonDirStorage {
+    fn save_game(&mut self, game: &GameRecord) -> Result<(), String> {
+        let path = self.dir.join(format!("game-{:06}.json", self.saved));
+        let json = serde_json::to_string(game).unwrap();
+        fs::write(&path, json).map_err(|e| format!("{}: {}", path.display(), e))?;
+        self.saved += 1;
+        Ok(())
+    }
+
+    fn games(&self) -> Result<Vec<GameRecord>, String> {
+        self.files()?
+            .iter()
+            .map(|path| {
+                fs::read_to_string(path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|json| serde_json::from_str(&json).map_err(|e| e.to_string()))
+                    .map_err(|e| format!("{}: {}", path.display(), e))
+            })
+            .collect()
+    }
+}
+
+/// A `games` table in an SQLite database
+#[cfg(feature = "sqlite")]
+pub struct SqliteStorage {
+    connection: rusqlite::Connection,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteStorage {
+    /// Opens the database at `path`, creating it and its table if need be
+    pub fn open(path: &Path) -> Result<SqliteStorage, String> {
+        let connection =
+            rusqlite::Connection::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        connection
+            .execute(
+                "CREATE TABLE IF NOT EXISTS games (
+                    id INTEGER PRIMARY KEY,
+                    tournament TEXT NOT NULL,
+                    game_id TEXT,
+                    bot1 TEXT NOT NULL,
+                    bot2 TEXT NOT NULL,
+                    player1 INTEGER NOT NULL,
+                    player2 INTEGER NOT NULL,
+                    board INTEGER NOT NULL,
+                    seed INTEGER NOT NULL,
+                    winner INTEGER,
+                    placements1 INTEGER NOT NULL,
+                    placements2 INTEGER NOT NULL,
+                    moves INTEGER NOT NULL,
+                    error TEXT,
+                    metadata TEXT NOT NULL
+                )",
+                rusqlite::NO_PARAMS,
+            )
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(SqliteStorage { connection })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl Storage for SqliteStorage {
+    fn save_game(&mut self, game: &GameRecord) -> Result<(), String> {
+        let pairing = &game.pairing;
+        self.connection
+            .execute(
+                "INSERT INTO games (tournament, game_id, bot1, bot2, player1, player2,
+                    board, seed, winner, placements1, placements2, moves, error, metadata)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+                rusqlite::params![
+                    game.tournament,
+                    game.game_id,
+                    game.bots[0],
+                    game.bots[1],
+                    pairing.player1 as i64,
+                    pairing.player2 as i64,
+                    pairing.board as i64,
+                    // SQLite integers are signed, the seed's bits are kept
+                    pairing.seed as i64,
+                    game.winner.map(|winner| winner as i64),
+                    game.placements[0] as i64,
+                    game.placements[1] as i64,
+                    game.moves as i64,
+                    game.error,
+                    serde_json::to_string(&game.metadata).unwrap(),
+                ],
+            )
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    fn games(&self) -> Result<Vec<GameRecord>, String> {
+        use super::tournament::Pairing;
+
+        let mut statement = self
+            .connection
+            .prepare(
+                "SELECT tournament, game_id, bot1, bot2, player1, player2, board, seed,
+                    winner, placements1, placements2, moves, error, metadata
+                FROM games ORDER BY id",
+            )
+            .map_err(|e| e.to_string())?;
+        let games = statement
+            .query_map(rusqlite::NO_PARAMS, |row| {
+                let metadata: String = row.get(13)?;
+                Ok(GameRecord {
+                    tournament: row.get(0)?,
+                    game_id: row.get(1)?,
+                    bots: [row.get(2)?, row.get(3)?],
+                    pairing: Pairing {
+                        player1: row.get::<_, i64>(4)? as usize,
+                        player2: row.get::<_, i64>(5)? as usize,
+                        board: row.get::<_, i64>(6)? as usize,
+                        seed: row.get::<_, i64>(7)? as u64,
+                    },
+                    metadata: serde_json::from_str(&metadata).unwrap_or_default(),
+                    winner: row.get::<_, Option<i64>>(8)?.map(|winner| winner as usize),
+                    placements: [
+                        row.get::<_, i64>(9)? as usize,
+                        row.get::<_, i64>(10)? as usize,
+                    ],
+                    moves: row.get::<_, i64>(11)? as usize,
+                    error: row.get(12)?,
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        games
+            .collect::<Result<Vec<GameRecord>, _>>()
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::tournament::Pairing;
+    use std::collections::BTreeMap;
+    use std::env;
+
+    fn record(seed: u64, winner: Option<usize>) -> GameRecord {
+        let mut metadata = BTreeMap::new();
+        metadata.insert(String::from("round"), String::from("2"));
+        GameRecord {
+            tournament: String::from("0123456789abcdef"),
+            bots: [String::from("greedy"), String::from("./bot.filler")],
+            game_id: Some(format!("game-{}", seed)),
+            metadata,
+            pairing: Pairing {
+                player1: 0,
+                player2: 1,
+                board: 0,
+                seed,
+            },
+            winner,
+            placements: [4, 3],
+            moves: 7,
+            error: None,
+        }
+    }
+
+    fn round_trip(storage: &mut dyn Storage) {
+        let games = vec![record(u64::MAX, Some(0)), record(2, None)];
+        for game in games.iter() {
+            storage.save_game(game).unwrap();
+        }
+        assert_eq!(storage.games().unwrap(), games);
+    }
+
+    #[test]
+    fn backends_return_the_games_saved() {
+        round_trip(&mut MemoryStorage::default());
+
+        let dir = env::temp_dir().join(format!("filler_storage_{}", std::process::id()));
+        round_trip(&mut *open(&format!("json:{}", dir.display())).unwrap());
+        let mut reopened = JsonDirStorage::open(&dir).unwrap();
+        reopened.save_game(&record(3, Some(1))).unwrap();
+        assert_eq!(reopened.games().unwrap().len(), 3);
+        fs::remove_dir_all(&dir).unwrap();
+
+        #[cfg(feature = "sqlite")]
+        round_trip(&mut *open("sqlite::memory:").unwrap());
+        #[cfg(not(feature = "sqlite"))]
+        assert!(open("sqlite:results.db").is_err());
+    }
+}
@@ -0,0 +1,147 @@
+use super::{OnPlayerResponse, PlayerResponse};
+use crate::models::PlateauView;
+use std::cell::Cell;
+
+/// Which moves a `Subscription` passes on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Events {
+    All,
+    Errors,
+    Placements,
+}
+
+/// Wraps an observer so it only sees a subset of the moves, eg. only every
+/// 10th rejected move. Built by chaining:
+///
+/// `Subscription::new(observer).only_errors().every(10)`
+///
+/// The end of the game is always passed on.
+pub struct Subscription {
+    inner: Box<dyn OnPlayerResponse>,
+    events: Events,
+    every: usize,
+    seen: Cell<usize>,
+}
+
+impl Subscription {
+    pub fn new(inner: Box<dyn OnPlayerResponse>) -> Self {
+        Subscription {
+            inner,
+            events: Events::All,
+            every: 1,
+            seen: Cell::new(0),
+        }
+    }
+
+    /// Passes on rejected moves only
+    pub fn only_errors(mut self) -> Self {
+        self.events = Events::Errors;
+        self
+    }
+
+    /// Passes on successful placements only
+    pub fn only_placements(mut self) -> Self {
+        self.events = Events::Placements;
+        self
+    }
+
+    /// Passes on one in `n` of the selected moves, starting with the `n`th
+    pub fn every(mut self, n: usize) -> Self {
+        self.every = n.max(1);
+        self
+    }
+
+    fn selects(&self, response: &PlayerResponse) -> bool {
+        match self.events {
+            Events::All => true,
+            Events::Errors => response.error.is_some(),
+            Events::Placements => response.error.is_none(),
+        }
+    }
+}
+
+impl OnPlayerResponse for Subscription {
+    fn on_player_move(&self, plateau: PlateauView, player_response: &PlayerResponse) {
+        if !self.selects(player_response) {
+            return;
+        }
+        let seen = self.seen.get() + 1;
+        self.seen.set(seen);
+        if seen.is_multiple_of(self.every) {
+            self.inner.on_player_move(plateau, player_response);
+        }
+    }
+
+    fn on_game_end(&self, plateau: PlateauView) {
+        self.inner.on_game_end(plateau);
+    }
+}
+
+/// Passes every event on to several observers in turn
+pub(crate) struct Fanout(pub Vec<Box<dyn OnPlayerResponse>>);
+
+impl OnPlayerResponse for Fanout {
+    fn on_player_move(&self, plateau: PlateauView, player_response: &PlayerResponse) {
+        for observer in self.0.iter() {
+            observer.on_player_move(plateau, player_response);
+        }
+    }
+
+    fn on_game_end(&self, plateau: PlateauView) {
+        for observer in self.0.iter() {
+            observer.on_game_end(plateau);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Piece, Plateau, Player};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    struct Recorder(Rc<RefCell<Vec<usize>>>);
+
+    impl OnPlayerResponse for Recorder {
+        fn on_player_move(&self, _: PlateauView, response: &PlayerResponse) {
+            self.0.borrow_mut().push(response.placement_count);
+        }
+    }
+
+    fn response(placement_count: usize, error: bool) -> PlayerResponse {
+        PlayerResponse {
+            player: Player::Player1,
+            piece: Piece::new_blank(1, 1),
+            batch: vec![],
+            raw_response: None,
+            placement: None,
+            batch_placements: vec![],
+            placement_count,
+            error: match error {
+                true => Some(String::from("Timed out")),
+                false => None,
+            },
+            rejection: None,
+            timestamp: 0,
+            board_hash: None,
+            name: None,
+            notes: vec![],
+        }
+    }
+
+    #[test]
+    fn filters_then_samples() {
+        let seen = Rc::new(RefCell::new(vec![]));
+        let subscription = Subscription::new(Box::new(Recorder(seen.clone())))
+            .only_errors()
+            .every(2);
+        let plateau = Plateau::default();
+
+        for i in 0..8 {
+            subscription.on_player_move(plateau.view(), &response(i, i % 3 != 0));
+        }
+        // Errors are moves 1, 2, 4, 5 and 7, every second one is passed on
+        assert_eq!(*seen.borrow(), vec![2, 5]);
+    }
+}
@@ -0,0 +1,187 @@
+use std::io;
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Bot path prefix that has the engine wait for the bot to connect over TCP,
+/// as `tcp:PORT` or `tcp:HOST:PORT`
+pub const TCP_PREFIX: &str = "tcp:";
+/// How long the engine waits for a remote bot to connect
+pub const CONNECT_TIMEOUT: Duration = Duration::from_secs(60);
+/// How long a remote bot whose connection dropped has to connect again
+pub const RECONNECT_WINDOW: Duration = Duration::from_secs(10);
+/// Times a remote bot may reconnect before a dropped connection is final
+pub const MAX_RECONNECTS: usize = 3;
+
+/// Address the engine listens on for the bot at `path`, if it is a remote
+/// bot. A bare port listens on every interface.
+pub fn listen_address(path: &str) -> Option<String> {
+    let address = path.strip_prefix(TCP_PREFIX)?;
+    let (host, port) = match address.rsplit_once(':') {
+        Some((host, port)) => (host, port),
+        None => ("0.0.0.0", address),
+    };
+    port.parse::<u16>().ok()?;
+    Some(format!("{}:{}", host, port))
+}
+
+/// Listens on `address` until a bot connects or `timeout` passes. The bot
+/// then speaks the same protocol over the connection as over stdin and
+/// stdout.
+pub fn accept(address: &str, timeout: Duration) -> io::Result<TcpStream> {
+    wait_for_bot(address, timeout, || false)
+}
+
+/// Listens on `address` for a bot that lost its connection, until it
+/// connects again, `RECONNECT_WINDOW` passes or `abandoned` says the engine
+/// stopped waiting on it
+pub fn reconnect(address: &str, abandoned: impl Fn() -> bool) -> io::Result<TcpStream> {
+    wait_for_bot(address, RECONNECT_WINDOW, abandoned)
+}
+
+fn wait_for_bot(
+    address: &str,
+    timeout: Duration,
+    abandoned: impl Fn() -> bool,
+) -> io::Result<TcpStream> {
+    let listener = TcpListener::bind(address)?;
+    listener.set_nonblocking(true)?;
+    let started = Instant::now();
+    loop {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                stream.set_nonblocking(false)?;
+                stream.set_nodelay(true)?;
+                return Ok(stream);
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                if started.elapsed() >= timeout || abandoned() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        format!("no bot connected to {}", address),
+                    ));
+                }
+                thread::sleep(Duration::from_millis(20));
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{Bot, PlayerResponse, Rejection, Sandbox};
+    use crate::models::{Piece, Plateau, Player, Point};
+    use std::io::{BufRead, BufReader, Write};
+
+    #[test]
+    fn parses_listen_addresses() {
+        assert_eq!(listen_address("tcp:4242").unwrap(), "0.0.0.0:4242");
+        assert_eq!(
+            listen_address("tcp:127.0.0.1:4242").unwrap(),
+            "127.0.0.1:4242"
+        );
+        assert!(listen_address("tcp:http").is_none());
+        assert!(listen_address("bots/tcp:4242").is_none());
+    }
+
+    fn free_port() -> u16 {
+        TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    /// Connects to the engine, reads the prelude and the first frame, then
+    /// answers it unless `answer` is `None`
+    fn remote_turn(address: &str, answer: Option<&[u8]>) -> String {
+        let stream = loop {
+            match TcpStream::connect(address) {
+                Ok(stream) => break stream,
+                Err(_) => thread::sleep(Duration::from_millis(10)),
+            }
+        };
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut writer = stream;
+        let mut prelude = String::new();
+        reader.read_line(&mut prelude).unwrap();
+        for line in reader.lines().map(Result::unwrap) {
+            if line.starts_with("Piece") {
+                break;
+            }
+        }
+        if let Some(answer) = answer {
+            writer.write_all(answer).unwrap();
+        }
+        prelude
+    }
+
+    /// Waits for the bot to connect on `path` and asks it for a placement
+    fn remote_move<'a>(path: &'a str, prelude: &str) -> (Bot<'a>, PlayerResponse) {
+        let mut bot = Bot::new(
+            path,
+            String::from("remote"),
+            Duration::from_secs(2),
+            Player::Player1,
+            String::from(prelude),
+            Sandbox::default(),
+        )
+        .unwrap();
+        let mut plateau = Plateau::new(3, 2, &Point::new(0, 0), &Point::new(2, 1)).unwrap();
+        let piece = Piece::new(1, 1, vec![true]);
+        let response = bot.request_placement(&mut plateau, &[piece], 0, &mut []);
+        (bot, response)
+    }
+
+    #[test]
+    fn remote_bot_plays_over_the_connection() {
+        let port = free_port();
+        let address = format!("127.0.0.1:{}", port);
+        let remote = thread::spawn(move || remote_turn(&address, Some(b"0 0\n")));
+
+        let prelude = "$$$ exec p1 : [remote]\n";
+        let (_, response) = remote_move(&format!("tcp:127.0.0.1:{}", port), prelude);
+
+        assert_eq!(response.error, None);
+        assert_eq!(response.placement, Some(Point::new(0, 0)));
+        assert_eq!(remote.join().unwrap(), prelude);
+    }
+
+    #[test]
+    fn dropped_bots_may_reconnect_and_answer() {
+        let port = free_port();
+        let address = format!("127.0.0.1:{}", port);
+        let remote = thread::spawn(move || {
+            remote_turn(&address, None);
+            remote_turn(&address, Some(b"0 0\n"))
+        });
+
+        let prelude = "$$$ exec p1 : [remote]\n";
+        let path = format!("tcp:127.0.0.1:{}", port);
+        let (bot, response) = remote_move(&path, prelude);
+
+        assert_eq!(response.error, None);
+        assert_eq!(response.placement, Some(Point::new(0, 0)));
+        assert_eq!(remote.join().unwrap(), prelude);
+        assert_eq!(bot.report().reconnects, 1);
+    }
+
+    #[test]
+    fn the_engine_stops_waiting_for_a_bot_at_the_end_of_the_game() {
+        let port = free_port();
+        let address = format!("127.0.0.1:{}", port);
+        let remote = thread::spawn(move || remote_turn(&address, None));
+
+        let path = format!("tcp:127.0.0.1:{}", port);
+        let (mut bot, response) = remote_move(&path, "");
+        assert_eq!(response.rejection, Some(Rejection::Timeout));
+        remote.join().unwrap();
+
+        let started = Instant::now();
+        bot.end_game(&[], false);
+        assert!(started.elapsed() < RECONNECT_WINDOW);
+        assert_eq!(bot.report().reconnects, 0);
+    }
+}
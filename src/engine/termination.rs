@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Why a game ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Termination {
+    /// Too many errors in a row
+    ErrorThreshold,
+    /// The watchdog saw no successful placement from any player for too long
+    Stalled,
+    /// A finite piece bag ran out of pieces
+    PiecesExhausted,
+    /// Every player's last turn failed with a piece that fit nowhere
+    NoMovesLeft,
+    /// The territory balance came back to where it was a round earlier for
+    /// too many moves in a row
+    Stagnation,
+}
+
+impl fmt::Display for Termination {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match self {
+            Termination::ErrorThreshold => "error threshold reached",
+            Termination::Stalled => "game stalled",
+            Termination::PiecesExhausted => "piece bag exhausted",
+            Termination::NoMovesLeft => "no player can place",
+            Termination::Stagnation => "territory balance stagnated",
+        };
+        write!(f, "{}", reason)
+    }
+}
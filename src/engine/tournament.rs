@@ -0,0 +1,629 @@
+use super::{Engine, Outcome, Storage};
+use crate::models::piece::RANGE_DEFAULT;
+use crate::models::{PieceBag, Plateau, Player};
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+/// Points for a won game, a draw is worth half
+const WIN_POINTS: usize = 2;
+
+/// Number of buckets `Standings::by_seed_bucket` splits games into, a
+/// game's bucket being its seed modulo this
+pub const SEED_BUCKETS: u64 = 4;
+
+/// One game of a tournament. Bots are given by their index in the
+/// tournament's list, boards by their index in its boards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pairing {
+    pub player1: usize,
+    pub player2: usize,
+    pub board: usize,
+    /// Seed of the game's pieces, shared by both colours of a pairing
+    pub seed: u64,
+}
+
+/// How one game of a tournament ended
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameRecord {
+    /// `Tournament::id` of the tournament the game belongs to
+    #[serde(default)]
+    pub tournament: String,
+    pub pairing: Pairing,
+    /// Names of the bots in Player1's and Player2's seats
+    #[serde(default)]
+    pub bots: [String; 2],
+    /// `None` for a game that could not be played
+    #[serde(default)]
+    pub game_id: Option<String>,
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+    /// Index of the winning bot, `None` for a draw
+    pub winner: Option<usize>,
+    /// Pieces placed by Player1 and Player2
+    pub placements: [usize; 2],
+    pub moves: usize,
+    /// Why the game could not be played, such as an engine panic. Such
+    /// games count towards no one's standing.
+    pub error: Option<String>,
+}
+
+impl GameRecord {
+    fn engine_error(tournament: &Tournament, pairing: Pairing, error: String) -> GameRecord {
+        GameRecord {
+            tournament: tournament.id(),
+            pairing,
+            bots: tournament.seat_names(&pairing),
+            game_id: None,
+            metadata: BTreeMap::new(),
+            winner: None,
+            placements: [0, 0],
+            moves: 0,
+            error: Some(error),
+        }
+    }
+}
+
+/// A bot's record over a tournament
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Standing {
+    pub bot: String,
+    pub games: usize,
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+    pub placements: usize,
+    pub points: usize,
+}
+
+/// Bots ranked by points, then by pieces placed, with the games behind it
+#[derive(Debug, Clone, Serialize)]
+pub struct Standings {
+    pub table: Vec<Standing>,
+    /// The same table over each board's games alone, by board index
+    pub by_board: BTreeMap<usize, Vec<Standing>>,
+    /// The same table over each bucket of seeds alone, see `SEED_BUCKETS`
+    pub by_seed_bucket: BTreeMap<u64, Vec<Standing>>,
+    pub games: Vec<GameRecord>,
+}
+
+impl Standings {
+    pub fn from_games(bots: &[String], games: Vec<GameRecord>) -> Standings {
+        let played: Vec<&GameRecord> = games.iter().filter(|game| game.error.is_none()).collect();
+        let boards: BTreeSet<usize> = played.iter().map(|game| game.pairing.board).collect();
+        let by_board = boards
+            .into_iter()
+            .map(|board| {
+                let games = played.iter().filter(|game| game.pairing.board == board);
+                (board, Standings::table(bots, games.copied()))
+            })
+            .collect();
+        let buckets: BTreeSet<u64> = played
+            .iter()
+            .map(|game| game.pairing.seed % SEED_BUCKETS)
+            .collect();
+        let by_seed_bucket = buckets
+            .into_iter()
+            .map(|bucket| {
+                let games = played
+                    .iter()
+                    .filter(|game| game.pairing.seed % SEED_BUCKETS == bucket);
+                (bucket, Standings::table(bots, games.copied()))
+            })
+            .collect();
+        let table = Standings::table(bots, played.into_iter());
+        Standings {
+            table,
+            by_board,
+            by_seed_bucket,
+            games,
+        }
+    }
+
+    /// Ranks the bots over the given played games
+    fn table<'a>(bots: &[String], games: impl Iterator<Item = &'a GameRecord>) -> Vec<Standing> {
+        let mut table: Vec<Standing> = bots
+            .iter()
+            .map(|bot| Standing {
+                bot: bot.clone(),
+                ..Standing::default()
+            })
+            .collect();
+        for game in games {
+            let seats = [game.pairing.player1, game.pairing.player2];
+            for (seat, bot) in seats.iter().enumerate() {
+                let standing = &mut table[*bot];
+                standing.games += 1;
+                standing.placements += game.placements[seat];
+                match game.winner {
+                    Some(winner) if winner == *bot => standing.wins += 1,
+                    Some(_) => standing.losses += 1,
+                    None => standing.draws += 1,
+                }
+            }
+        }
+        for standing in table.iter_mut() {
+            standing.points = standing.wins * WIN_POINTS + standing.draws * WIN_POINTS / 2;
+        }
+        table.sort_by_key(|standing| (Reverse(standing.points), Reverse(standing.placements)));
+        table
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+impl fmt::Display for Standings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>4}  {:<24} {:>5} {:>5} {:>5} {:>5} {:>10} {:>6}",
+            "Rank", "Bot", "Games", "Wins", "Draws", "Lost", "Placements", "Points"
+        )?;
+        for (rank, standing) in self.table.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>4}  {:<24} {:>5} {:>5} {:>5} {:>5} {:>10} {:>6}",
+                rank + 1,
+                standing.bot,
+                standing.games,
+                standing.wins,
+                standing.draws,
+                standing.losses,
+                standing.placements,
+                standing.points
+            )?;
+        }
+        for game in self.games.iter() {
+            if let Some(error) = &game.error {
+                writeln!(f, "Failed game: {}", error)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Round-robin between bots: every pair of bots meets once on every board,
+/// or twice with the colours exchanged
+pub struct Tournament {
+    bots: Vec<String>,
+    boards: Vec<Plateau>,
+    both_colors: bool,
+    seed: u64,
+    threads: usize,
+}
+
+impl Tournament {
+    pub fn new(bots: Vec<String>) -> Tournament {
+        Tournament {
+            bots,
+            boards: vec![Plateau::default()],
+            both_colors: false,
+            seed: thread_rng().gen(),
+            threads: 1,
+        }
+    }
+
+    /// Boards every pairing is played on, the default plateau otherwise
+    pub fn with_boards(&mut self, boards: Vec<Plateau>) -> &Self {
+        assert!(!boards.is_empty(), "A tournament needs at least one board");
+        self.boards = boards;
+        self
+    }
+
+    /// Plays every pairing a second time with the bots' seats exchanged
+    pub fn both_colors(&mut self) -> &Self {
+        self.both_colors = true;
+        self
+    }
+
+    /// Seed the pieces of every game follow from, so a tournament can be
+    /// played again with the same pieces
+    pub fn with_seed(&mut self, seed: u64) -> &Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Plays up to `threads` games at once. Each game has its own board and
+    /// piece bag, and its seed is fixed by the schedule, so the results do
+    /// not depend on the number of threads.
+    pub fn with_threads(&mut self, threads: usize) -> &Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    pub fn bots(&self) -> &[String] {
+        &self.bots
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Tells the tournament's games apart from those of other tournaments in
+    /// the same storage. It follows from the bots, boards and seed, so the
+    /// same tournament run again has the same id.
+    pub fn id(&self) -> String {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let boards = self
+            .boards
+            .iter()
+            .map(|board| board.content_hash().to_string());
+        let parts = self
+            .bots
+            .iter()
+            .cloned()
+            .chain(boards)
+            .chain(vec![self.both_colors.to_string(), self.seed.to_string()]);
+        for byte in parts.flat_map(|part| part.into_bytes().into_iter().chain(Some(0))) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+        format!("{:016x}", hash)
+    }
+
+    /// `A vs B on board N`, for error messages
+    fn describe(&self, pairing: &Pairing) -> String {
+        let bot = |index: usize| self.bots.get(index).map_or("?", String::as_str);
+        format!(
+            "{} vs {} on board {}",
+            bot(pairing.player1),
+            bot(pairing.player2),
+            pairing.board
+        )
+    }
+
+    fn seat_names(&self, pairing: &Pairing) -> [String; 2] {
+        let bot = |index: usize| self.bots.get(index).cloned().unwrap_or_default();
+        [bot(pairing.player1), bot(pairing.player2)]
+    }
+
+    /// Every game of the tournament, in the order `run` plays them
+    pub fn schedule(&self) -> Vec<Pairing> {
+        let mut schedule = vec![];
+        let mut seed = self.seed;
+        for player1 in 0..self.bots.len() {
+            for player2 in player1 + 1..self.bots.len() {
+                for board in 0..self.boards.len() {
+                    let pairing = Pairing {
+                        player1,
+                        player2,
+                        board,
+                        seed,
+                    };
+                    schedule.push(pairing);
+                    if self.both_colors {
+                        schedule.push(Pairing {
+                            player1: player2,
+                            player2: player1,
+                            ..pairing
+                        });
+                    }
+                    seed = seed.wrapping_add(1);
+                }
+            }
+        }
+        schedule
+    }
+
+    pub fn play_game(&self, pairing: &Pairing) -> GameRecord {
+        let mut builder = Engine::builder(&self.bots[pairing.player1]);
+        builder.with_player2(&self.bots[pairing.player2]);
+        builder.with_plateau(self.boards[pairing.board].clone());
+        builder.with_piecebag(PieceBag::with_seed(
+            pairing.seed,
+            RANGE_DEFAULT,
+            RANGE_DEFAULT,
+        ));
+        builder.with_metadata("tournament", &self.id());
+        builder.with_metadata("board", &pairing.board.to_string());
+        let result = match builder.finish() {
+            Ok(mut engine) => engine.run(),
+            Err(e) => {
+                let error = format!("{}: {}", self.describe(pairing), e);
+                return GameRecord::engine_error(self, *pairing, error);
+            }
+        };
+
+        let placements = |player| {
+            result
+                .placement_counts
+                .iter()
+                .find(|(seat, _)| *seat == player)
+                .map_or(0, |(_, count)| *count)
+        };
+        GameRecord {
+            tournament: self.id(),
+            pairing: *pairing,
+            bots: self.seat_names(pairing),
+            game_id: Some(result.game_id.clone()),
+            metadata: result.metadata.clone(),
+            winner: result.winner().map(|winner| match winner {
+                Player::Player1 => pairing.player1,
+                Player::Player2 => pairing.player2,
+            }),
+            placements: [placements(Player::Player1), placements(Player::Player2)],
+            moves: result.moves,
+            error: match result.outcome {
+                Outcome::NoContest => Some(format!(
+                    "{}: no contest, neither bot placed a piece",
+                    self.describe(pairing)
+                )),
+                _ => None,
+            },
+        }
+    }
+
+    /// Plays a game, recording an engine panic as a failed game so it does
+    /// not abort the rest of the tournament
+    pub fn play_isolated(&self, pairing: &Pairing) -> GameRecord {
+        let game = panic::catch_unwind(AssertUnwindSafe(|| self.play_game(pairing)));
+        game.unwrap_or_else(|payload| {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| String::from(*message))
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| String::from("unknown panic"));
+            let error = format!("{}: engine panicked: {}", self.describe(pairing), message);
+            GameRecord::engine_error(self, *pairing, error)
+        })
+    }
+
+    /// Plays the tournament like `run`, saving every game to `storage` as
+    /// soon as it is over. Games of the schedule already in `storage` are not
+    /// played again, so an interrupted tournament picks up where it stopped
+    /// when run again with the same seed. Games of other tournaments in
+    /// `storage` are left alone.
+    pub fn run_into(&self, storage: &mut dyn Storage) -> Result<Standings, String> {
+        let schedule = self.schedule();
+        let id = self.id();
+        let mut games: Vec<GameRecord> = storage
+            .games()?
+            .into_iter()
+            .filter(|game| game.tournament == id && schedule.contains(&game.pairing))
+            .collect();
+        let pending: Vec<Pairing> = schedule
+            .iter()
+            .filter(|pairing| !games.iter().any(|game| game.pairing == **pairing))
+            .copied()
+            .collect();
+        self.play_all(&pending, |_, game| {
+            storage.save_game(&game)?;
+            games.push(game);
+            Ok(())
+        })?;
+
+        let games = schedule
+            .iter()
+            .filter_map(|pairing| games.iter().find(|game| game.pairing == *pairing))
+            .cloned()
+            .collect();
+        Ok(Standings::from_games(&self.bots, games))
+    }
+
+    /// Plays every game of the schedule, on as many threads as configured.
+    /// The games are recorded in schedule order.
+    pub fn run(&self) -> Standings {
+        let schedule = self.schedule();
+        let mut records = vec![None; schedule.len()];
+        let played = self.play_all(&schedule, |index, game| {
+            records[index] = Some(game);
+            Ok(())
+        });
+        played.unwrap();
+        Standings::from_games(&self.bots, records.into_iter().flatten().collect())
+    }
+
+    /// Plays `pairings` on up to `threads` threads, handing each game with
+    /// its index to `done` on the calling thread as it finishes. No further
+    /// games are started once `done` fails.
+    fn play_all<F>(&self, pairings: &[Pairing], mut done: F) -> Result<(), String>
+    where
+        F: FnMut(usize, GameRecord) -> Result<(), String>,
+    {
+        let next = AtomicUsize::new(0);
+        let (sender, receiver) = mpsc::channel();
+        thread::scope(|scope| {
+            for _ in 0..self.threads.min(pairings.len()) {
+                let sender = sender.clone();
+                let next = &next;
+                scope.spawn(move || loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let pairing = match pairings.get(index) {
+                        Some(pairing) => pairing,
+                        None => break,
+                    };
+                    if sender.send((index, self.play_isolated(pairing))).is_err() {
+                        break;
+                    }
+                });
+            }
+            drop(sender);
+            for (index, game) in receiver {
+                if let Err(e) = done(index, game) {
+                    next.store(pairings.len(), Ordering::Relaxed);
+                    return Err(e);
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::native_bot::{GREEDY, RANDOM};
+    use crate::engine::storage::MemoryStorage;
+    use crate::models::maps;
+
+    #[test]
+    fn schedules_every_pairing() {
+        let bots = vec![String::from("a"), String::from("b"), String::from("c")];
+        let mut tournament = Tournament::new(bots);
+        tournament.with_boards(vec![Plateau::default(), Plateau::default()]);
+        tournament.both_colors();
+        tournament.with_seed(10);
+        let schedule = tournament.schedule();
+
+        assert_eq!(schedule.len(), 12);
+        assert_eq!((schedule[1].player1, schedule[1].player2), (1, 0));
+        assert_eq!(schedule[0].seed, schedule[1].seed);
+        assert_eq!(schedule[11].seed, 15);
+    }
+
+    #[test]
+    fn ranks_bots_by_points() {
+        let bots = vec![String::from(RANDOM), String::from(GREEDY)];
+        let mut tournament = Tournament::new(bots);
+        tournament.with_boards(vec![maps::get("tiny").unwrap()]);
+        tournament.both_colors();
+        let standings = tournament.run();
+
+        assert_eq!(standings.games.len(), 2);
+        let games: usize = standings.table.iter().map(|standing| standing.games).sum();
+        assert_eq!(games, 4);
+        assert!(standings.table[0].points >= standings.table[1].points);
+        assert!(standings.to_json().contains("\"table\""));
+    }
+
+    #[test]
+    fn parallel_games_match_serial_ones() {
+        let bots = vec![String::from(GREEDY), String::from(GREEDY)];
+        let mut tournament = Tournament::new(bots);
+        tournament.with_boards(vec![maps::get("tiny").unwrap(), maps::get("tiny").unwrap()]);
+        tournament.both_colors();
+        tournament.with_seed(3);
+        let serial = tournament.run();
+        tournament.with_threads(4);
+        let parallel = tournament.run();
+
+        // Every game gets a fresh id, the rest has to match
+        let without_ids = |standings: &Standings| -> Vec<GameRecord> {
+            let games = standings.games.iter().cloned();
+            games
+                .map(|game| GameRecord {
+                    game_id: None,
+                    ..game
+                })
+                .collect()
+        };
+        assert_eq!(without_ids(&parallel), without_ids(&serial));
+        assert_eq!(parallel.table, serial.table);
+    }
+
+    #[test]
+    fn resumes_from_the_games_in_storage() {
+        let bots = vec![String::from(RANDOM), String::from(GREEDY)];
+        let mut tournament = Tournament::new(bots);
+        tournament.with_boards(vec![maps::get("tiny").unwrap()]);
+        tournament.both_colors();
+        tournament.with_threads(2);
+        let schedule = tournament.schedule();
+        let mut storage = MemoryStorage::default();
+        let stored = GameRecord {
+            tournament: tournament.id(),
+            pairing: schedule[0],
+            bots: [String::from(RANDOM), String::from(GREEDY)],
+            game_id: None,
+            metadata: BTreeMap::new(),
+            winner: Some(1),
+            placements: [1, 2],
+            moves: 3,
+            error: None,
+        };
+        storage.save_game(&stored).unwrap();
+        let other = GameRecord {
+            tournament: String::from("another"),
+            pairing: schedule[1],
+            ..stored.clone()
+        };
+        storage.save_game(&other).unwrap();
+        let standings = tournament.run_into(&mut storage).unwrap();
+
+        assert_eq!(standings.games.len(), 2);
+        assert_eq!(standings.games[0], stored);
+        assert_eq!(standings.games[1].pairing, schedule[1]);
+        assert_eq!(
+            standings.games[1].bots,
+            [String::from(GREEDY), String::from(RANDOM)]
+        );
+        assert_eq!(standings.games[1].metadata["tournament"], tournament.id());
+        assert!(standings.games[1].game_id.is_some());
+        assert_eq!(storage.games().unwrap().len(), 3);
+        assert_eq!(
+            tournament.run_into(&mut storage).unwrap().games,
+            standings.games
+        );
+        assert_eq!(storage.games().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn records_a_panicking_game_as_failed() {
+        let bots = vec![String::from(RANDOM), String::from(GREEDY)];
+        let mut tournament = Tournament::new(bots);
+        tournament.with_boards(vec![maps::get("tiny").unwrap()]);
+        let broken = Pairing {
+            player1: 0,
+            player2: 1,
+            board: 7,
+            seed: 0,
+        };
+        let record = tournament.play_isolated(&broken);
+
+        let error = record.error.clone().unwrap();
+        assert!(error.starts_with("random vs greedy on board 7: engine panicked"));
+        let mut games = vec![record];
+        games.push(tournament.play_isolated(&tournament.schedule()[0]));
+        let standings = Standings::from_games(tournament.bots(), games);
+        let played: usize = standings.table.iter().map(|standing| standing.games).sum();
+        assert_eq!(played, 2);
+        assert!(standings
+            .to_string()
+            .contains("Failed game: random vs greedy"));
+    }
+
+    #[test]
+    fn breaks_standings_down_by_board_and_seed_bucket() {
+        let bots = vec![String::from(RANDOM), String::from(GREEDY)];
+        let mut tournament = Tournament::new(bots);
+        tournament.with_boards(vec![maps::get("tiny").unwrap(), maps::get("tiny").unwrap()]);
+        tournament.both_colors();
+        tournament.with_seed(5);
+        let standings = tournament.run();
+
+        assert_eq!(standings.by_board.keys().collect::<Vec<_>>(), vec![&0, &1]);
+        for table in standings.by_board.values() {
+            let games: usize = table.iter().map(|standing| standing.games).sum();
+            assert_eq!(games, 4);
+        }
+        let buckets = standings
+            .games
+            .iter()
+            .map(|game| game.pairing.seed % SEED_BUCKETS);
+        assert_eq!(
+            standings
+                .by_seed_bucket
+                .keys()
+                .copied()
+                .collect::<BTreeSet<_>>(),
+            buckets.collect()
+        );
+        let games: usize = standings
+            .by_seed_bucket
+            .values()
+            .flatten()
+            .map(|standing| standing.games)
+            .sum();
+        assert_eq!(games, 8);
+        let json = standings.to_json();
+        assert!(json.contains("\"by_board\":{\"0\":[") && json.contains("\"by_seed_bucket\""));
+    }
+}
@@ -0,0 +1,178 @@
+use crate::models::{PlateauView, Player, Point};
+use std::collections::VecDeque;
+
+/// Number of 8-way steps from a player's territory to every cell of a
+/// plateau. Paths only cross empty cells; opponent cells get a distance but
+/// are not crossed, and obstacles are never reached.
+///
+/// After a placement the field can be brought up to date with `update`,
+/// which only revisits the cells whose distance can have changed. Rules
+/// that empty cells or resize the board (decay, growth) need a new field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DistanceField {
+    player: Player,
+    width: usize,
+    height: usize,
+    distances: Vec<Option<u32>>,
+}
+
+impl DistanceField {
+    pub fn new(plateau: PlateauView, player: Player) -> DistanceField {
+        let (width, height) = (plateau.width(), plateau.height());
+        let mut field = DistanceField {
+            player,
+            width,
+            height,
+            distances: vec![None; width * height],
+        };
+
+        let mut queue = VecDeque::new();
+        for (p, owner) in plateau.cells() {
+            if owner == Some(player) {
+                field.set(&p, 0);
+                queue.push_back(p);
+            }
+        }
+        field.spread(plateau, queue);
+        field
+    }
+
+    /// Brings the field up to date after `player` claimed the `claimed`
+    /// cells, given the plateau as it is after the placement
+    pub fn update(&mut self, plateau: PlateauView, player: Player, claimed: &[Point]) {
+        let mut queue = VecDeque::new();
+        if player == self.player {
+            // New territory only shortens paths, so spread from it alone
+            for p in claimed {
+                self.set(p, 0);
+                queue.push_back(*p);
+            }
+        } else {
+            // New opponent cells can only lengthen paths through them. Cells
+            // no further away than the nearest of them keep their distance,
+            // the rest are searched again from the edge of that area.
+            let nearest = match claimed.iter().filter_map(|p| self.get(p)).min() {
+                Some(nearest) => nearest,
+                None => return,
+            };
+            for distance in self.distances.iter_mut() {
+                if distance.is_some_and(|d| d > nearest) {
+                    *distance = None;
+                }
+            }
+            for (p, owner) in plateau.cells() {
+                if owner.is_none() && self.get(&p) == Some(nearest) {
+                    queue.push_back(p);
+                }
+            }
+        }
+        self.spread(plateau, queue);
+    }
+
+    pub fn player(&self) -> Player {
+        self.player
+    }
+
+    /// Steps from the player's territory to `p`, `None` if it can not be
+    /// reached or lies off the plateau
+    pub fn get(&self, p: &Point) -> Option<u32> {
+        match p.is_within(self.width, self.height) {
+            true => self.distances[self.index(p)],
+            false => None,
+        }
+    }
+
+    /// The nearest opponent cell's distance, `None` if none can be reached
+    pub fn to_opponent(&self, plateau: PlateauView) -> Option<u32> {
+        plateau
+            .cells()
+            .filter(|(_, owner)| *owner == Some(self.player.opponent()))
+            .filter_map(|(p, _)| self.get(&p))
+            .min()
+    }
+
+    /// Breadth-first search outwards from the cells in `queue`, which
+    /// already hold their distances and are all equally far away. Cells
+    /// that are already as close are left alone.
+    fn spread(&mut self, plateau: PlateauView, mut queue: VecDeque<Point>) {
+        while let Some(p) = queue.pop_front() {
+            let next = self.distances[self.index(&p)].unwrap() + 1;
+            for n in p.neighbors8() {
+                if !plateau.is_in_bounds(&n)
+                    || plateau.is_blocked(&n)
+                    || self.get(&n).is_some_and(|d| d <= next)
+                {
+                    continue;
+                }
+                self.set(&n, next);
+                if plateau.get(&n).is_none() {
+                    queue.push_back(n);
+                }
+            }
+        }
+    }
+
+    fn set(&mut self, p: &Point, distance: u32) {
+        let i = self.index(p);
+        self.distances[i] = Some(distance);
+    }
+
+    fn index(&self, p: &Point) -> usize {
+        self.width * p.y as usize + p.x as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{PieceBag, Plateau};
+    use crate::sim::Game;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn walks_around_obstacles() {
+        let plateau = Plateau::try_from(String::from("O#..\n.#..\n...X\n")).unwrap();
+        let field = DistanceField::new(plateau.view(), Player::Player1);
+
+        assert_eq!(field.get(&Point::new(0, 0)), Some(0));
+        assert_eq!(field.get(&Point::new(1, 0)), None);
+        assert_eq!(field.get(&Point::new(2, 0)), Some(4));
+        assert_eq!(field.get(&Point::new(9, 9)), None);
+        assert_eq!(field.to_opponent(plateau.view()), Some(4));
+    }
+
+    #[test]
+    fn updates_match_a_full_search() {
+        let plateau = Plateau::new(12, 12, &Point::new(2, 2), &Point::new(9, 9)).unwrap();
+        let mut game = Game::new(plateau, PieceBag::new([3, 5], [3, 5]).with_limit(40));
+        let mut fields = [
+            DistanceField::new(game.plateau(), Player::Player1),
+            DistanceField::new(game.plateau(), Player::Player2),
+        ];
+
+        while let Some(piece) = game.piece().cloned() {
+            let player = game.to_move();
+            let before = game.plateau().to_plateau();
+            let placed = (0..12 * 12)
+                .map(|i| Point::new(i % 12 - 1, i / 12 - 1))
+                .find(|p| game.play(player, *p).is_ok());
+            let placement = match placed {
+                Some(placement) => placement,
+                None => {
+                    game.pass(player).unwrap();
+                    continue;
+                }
+            };
+            let claimed: Vec<Point> = piece
+                .filled_points()
+                .map(|p| p + placement)
+                .filter(|p| before.view().get(p).is_none())
+                .collect();
+
+            for field in fields.iter_mut() {
+                field.update(game.plateau(), player, &claimed);
+                assert_eq!(*field, DistanceField::new(game.plateau(), field.player()));
+            }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+//! Measures of a position shared by heuristic bots and analysis tools
+
+mod distance_field;
+pub use distance_field::DistanceField;
+
+use crate::models::{PlateauView, Player};
+
+/// Cells `player` holds minus the cells its opponent holds
+pub fn territory_difference(plateau: PlateauView, player: Player) -> i64 {
+    plateau.territory(player) as i64 - plateau.territory(player.opponent()) as i64
+}
+
+/// Cells of `player` that touch an empty cell, the edge it can still grow
+/// from
+pub fn frontier_length(plateau: PlateauView, player: Player) -> usize {
+    let is_open =
+        |p| plateau.is_in_bounds(&p) && plateau.get(&p).is_none() && !plateau.is_blocked(&p);
+    plateau
+        .cells()
+        .filter(|(p, owner)| *owner == Some(player) && p.neighbors8().any(is_open))
+        .count()
+}
+
+/// Largest share of the contested cells one player may be closer to beyond
+/// its opponent's before a layout counts as unfair
+const FAIRNESS_TOLERANCE: f64 = 0.1;
+
+/// Checks that a starting layout favours neither player: each must be able
+/// to reach the other, and the empty cells one of them reaches first must
+/// be split about evenly
+pub fn check_fairness(plateau: PlateauView) -> Result<(), String> {
+    let player1 = DistanceField::new(plateau, Player::Player1);
+    let player2 = DistanceField::new(plateau, Player::Player2);
+    if player1
+        .get(&plateau.player_start(Player::Player2))
+        .is_none()
+    {
+        return Err(String::from("Obstacles separate the players"));
+    }
+
+    let (mut closer1, mut closer2) = (0, 0);
+    for (p, owner) in plateau.cells() {
+        if owner.is_some() || plateau.is_blocked(&p) {
+            continue;
+        }
+        match (player1.get(&p), player2.get(&p)) {
+            (Some(d1), Some(d2)) if d1 < d2 => closer1 += 1,
+            (Some(d1), Some(d2)) if d2 < d1 => closer2 += 1,
+            (Some(_), None) => closer1 += 1,
+            (None, Some(_)) => closer2 += 1,
+            _ => {}
+        }
+    }
+    let contested = (closer1 + closer2).max(1) as f64;
+    match (closer1 as f64 - closer2 as f64).abs() / contested {
+        imbalance if imbalance > FAIRNESS_TOLERANCE => Err(format!(
+            "Player1 reaches {} cells first, Player2 {}",
+            closer1, closer2
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Plateau;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn measures_territory_and_frontier() {
+        let plateau = Plateau::try_from(String::from("OO#\nOO#\n..X\n")).unwrap();
+
+        assert_eq!(territory_difference(plateau.view(), Player::Player1), 3);
+        assert_eq!(territory_difference(plateau.view(), Player::Player2), -3);
+        assert_eq!(frontier_length(plateau.view(), Player::Player1), 2);
+        assert_eq!(frontier_length(plateau.view(), Player::Player2), 1);
+    }
+
+    #[test]
+    fn rejects_lopsided_layouts() {
+        let fair = Plateau::try_from(String::from("O...\n.##.\n...X\n")).unwrap();
+        assert!(check_fairness(fair.view()).is_ok());
+
+        let walled = Plateau::try_from(String::from("O#..\n##..\n...X\n")).unwrap();
+        assert!(check_fairness(walled.view()).is_err());
+
+        let cornered = Plateau::try_from(String::from("O.....\n......\n....X.\n")).unwrap();
+        assert!(check_fairness(cornered.view()).is_err());
+    }
+}
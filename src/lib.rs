@@ -1,2 +1,4 @@
 pub mod engine;
+pub mod eval;
 pub mod models;
+pub mod sim;
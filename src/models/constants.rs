@@ -3,5 +3,8 @@ pub const PLAYER1_NEW: char = 'o';
 pub const PLAYER2: char = 'X';
 pub const PLAYER2_NEW: char = 'x';
 pub const EMPTY: char = '.';
+pub const BLOCKED: char = '#';
+/// Empty cell worth extra points under hill scoring
+pub const HILL: char = '+';
 
 pub const PLAYER_TOKENS: [char; 2] = [PLAYER1, PLAYER2];
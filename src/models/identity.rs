@@ -0,0 +1,27 @@
+use super::Player;
+use serde::{Deserialize, Serialize};
+
+/// Colours used for each seat unless another is chosen
+pub const DEFAULT_COLORS: [&str; 2] = ["#d33", "#33d"];
+
+/// Who plays from a seat. `Player` only names the seat, which decides the
+/// move order and the token on the board; the identity carries how the
+/// player is shown.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Identity {
+    pub seat: Player,
+    pub name: String,
+    /// CSS colour the player is drawn in
+    pub color: String,
+}
+
+impl Identity {
+    /// Identity in the seat's default colour
+    pub fn new(seat: Player, name: &str) -> Identity {
+        Identity {
+            seat,
+            name: String::from(name),
+            color: String::from(DEFAULT_COLORS[seat as usize]),
+        }
+    }
+}
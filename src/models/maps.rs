@@ -0,0 +1,86 @@
+use super::constants::{BLOCKED, EMPTY};
+use super::Plateau;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use std::convert::TryFrom;
+
+const MAPS: &[(&str, &str)] = &[
+    ("tiny", include_str!("../../resources/maps/tiny")),
+    ("classic", include_str!("../../resources/maps/classic")),
+    ("huge", include_str!("../../resources/maps/huge")),
+    ("pillars", include_str!("../../resources/maps/pillars")),
+    ("wall", include_str!("../../resources/maps/wall")),
+    ("hills", include_str!("../../resources/maps/hills")),
+    ("map00", include_str!("../../resources/maps/map00")),
+    ("map01", include_str!("../../resources/maps/map01")),
+    ("map02", include_str!("../../resources/maps/map02")),
+];
+
+/// Names of the maps bundled with the engine
+pub fn names() -> impl Iterator<Item = &'static str> {
+    MAPS.iter().map(|(name, _)| *name)
+}
+
+/// Builds the bundled map with the given name
+pub fn get(name: &str) -> Option<Plateau> {
+    MAPS.iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, map)| Plateau::try_from(String::from(*map)).expect("bundled map is invalid"))
+}
+
+/// Generates a map with mirrored starts and about `obstacles` of its empty
+/// cells blocked. Obstacles come in pairs mirrored through the centre, like
+/// the starts, so the layout favours neither player. The same seed always
+/// generates the same map.
+pub fn generate(width: usize, height: usize, obstacles: f64, seed: u64) -> Result<Plateau, String> {
+    if !(0.0..1.0).contains(&obstacles) {
+        return Err(String::from(
+            "Obstacle density must be at least 0 and below 1",
+        ));
+    }
+    let mut rng = StdRng::seed_from_u64(seed);
+    let plateau = Plateau::with_random_starts_from(width, height, &mut rng)?;
+
+    let mut cells: Vec<char> = plateau.to_map_rows().concat().chars().collect();
+    // Visiting the first half decides every mirrored pair once, the centre
+    // cell of an odd board mirrors itself
+    for i in 0..cells.len().div_ceil(2) {
+        let mirror = cells.len() - 1 - i;
+        if cells[i] == EMPTY && cells[mirror] == EMPTY && rng.gen_bool(obstacles) {
+            cells[i] = BLOCKED;
+            cells[mirror] = BLOCKED;
+        }
+    }
+    let rows: Vec<String> = cells
+        .chunks(width)
+        .map(|row| row.iter().collect())
+        .collect();
+    Plateau::try_from(rows.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_maps_parse() {
+        for name in names() {
+            assert!(get(name).is_some(), "{}", name);
+        }
+        assert!(get("nowhere").is_none());
+    }
+
+    #[test]
+    fn generated_maps_are_mirrored_and_repeatable() {
+        let map = generate(9, 7, 0.3, 7).unwrap();
+        let cells: Vec<char> = map.to_map_rows().concat().chars().collect();
+        let blocked = |i: usize| cells[i] == BLOCKED;
+        assert!((0..cells.len()).any(blocked));
+        assert!((0..cells.len()).all(|i| blocked(i) == blocked(cells.len() - 1 - i)));
+        assert_eq!(
+            generate(9, 7, 0.3, 7).unwrap().to_map_rows(),
+            map.to_map_rows()
+        );
+        assert!(generate(9, 7, 1.0, 7).is_err());
+    }
+}
@@ -1,9 +1,13 @@
 pub mod piece;
 pub use piece::Piece;
 pub use piece::PieceBag;
+pub use piece::PieceBagState;
 
 pub mod plateau;
-pub use plateau::Plateau;
+pub use plateau::{OverlapRule, PlacementError, Plateau, PlateauState, PlateauView, StartLayout};
+
+pub mod identity;
+pub use identity::Identity;
 
 pub mod player;
 pub use player::Player;
@@ -12,3 +16,5 @@ pub mod point;
 pub use point::Point;
 
 pub mod constants;
+
+pub mod maps;
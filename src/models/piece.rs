@@ -1,15 +1,20 @@
 use super::point::Point;
 
 use rand::prelude::*;
+use rand::rngs::StdRng;
+use serde::de::{self, Deserializer};
 use serde::ser::{Serialize, SerializeStruct, Serializer};
+use serde::{Deserialize, Serialize as DeriveSerialize};
 use std::fmt;
+use std::str::FromStr;
 
 const EMPTY: char = '.';
 const OCCUPIED: char = '*';
 
-const RANGE_DEFAULT: [usize; 2] = [3, 8];
+/// Sides random pieces are drawn from, the upper bound exclusive
+pub const RANGE_DEFAULT: [usize; 2] = [3, 8];
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Piece {
     width: usize,
     height: usize,
@@ -21,13 +26,12 @@ impl Piece {
     pub fn new(width: usize, height: usize, cells: Vec<bool>) -> Self {
         assert_eq!(width * height, cells.len());
 
-        let p = Piece {
+        Piece {
             width,
             height,
             cells,
             density: 1,
-        };
-        p
+        }
     }
 
     pub fn new_blank(width: usize, height: usize) -> Self {
@@ -42,9 +46,7 @@ impl Piece {
     }
 
     // This fn should act only as a placeholder till better function is made
-    fn mutate(&mut self, x: usize, y: usize) -> &mut Self {
-        let mut rng = thread_rng();
-
+    fn mutate<R: Rng>(&mut self, x: usize, y: usize, rng: &mut R) -> &mut Self {
         if x == 0 || y == 0 || x >= self.width - 1 || y >= self.height - 1 {
             return self;
         }
@@ -59,16 +61,24 @@ impl Piece {
             let dy = rng.gen_range(-1, 2);
             let x = (x as i32 + dx) as usize;
             let y = (y as i32 + dy) as usize;
-            if self.cells[(y * self.width + x) as usize] == true {
+            if self.cells[y * self.width + x] {
                 continue;
             }
-            self.cells[(y * self.width + x) as usize] = true;
+            self.cells[y * self.width + x] = true;
             self.density += 1;
-            self.mutate(x, y);
+            self.mutate(x, y, rng);
         }
         self
     }
 
+    /// The piece turned half a turn, as seen from the opposite start point
+    /// of a plateau mirrored through its centre
+    pub fn rotated_half_turn(&self) -> Piece {
+        let mut cells = self.cells.clone();
+        cells.reverse();
+        Piece::new(self.width, self.height, cells)
+    }
+
     pub fn width(&self) -> usize {
         self.width
     }
@@ -76,6 +86,22 @@ impl Piece {
     pub fn height(&self) -> usize {
         self.height
     }
+
+    /// The piece's filled cells, relative to its top left corner, in
+    /// reading order
+    pub fn filled_points(&self) -> impl Iterator<Item = Point> + '_ {
+        let width = self.width;
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(|(_, filled)| **filled)
+            .map(move |(i, _)| Point::new((i % width) as i32, (i / width) as i32))
+    }
+
+    /// Number of filled cells
+    pub fn cell_count(&self) -> usize {
+        self.cells.iter().filter(|filled| **filled).count()
+    }
 }
 
 impl fmt::Display for Piece {
@@ -83,14 +109,14 @@ impl fmt::Display for Piece {
         writeln!(f, "Piece {} {}:", self.height, self.width)?;
         for y in 0..(self.height) {
             for x in 0..(self.width) {
-                let c = self.cells[(y * self.width + x) as usize];
+                let c = self.cells[y * self.width + x];
                 let c = match c {
                     true => OCCUPIED,
                     false => EMPTY,
                 };
                 write!(f, "{}", c)?;
             }
-            writeln!(f, "")?;
+            writeln!(f)?;
         }
         Ok(())
     }
@@ -115,31 +141,209 @@ impl Serialize for Piece {
     }
 }
 
+/// Reads a piece in the `Piece H W:` format `Display` writes
+impl FromStr for Piece {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let mut lines = s.lines();
+        let header = lines.next().unwrap_or("");
+        let mut size = header
+            .strip_prefix("Piece ")
+            .and_then(|size| size.strip_suffix(':'))
+            .ok_or_else(|| format!("Bad piece header: {}", header))?
+            .split(' ')
+            .map(str::parse::<usize>);
+        let (height, width) = match (size.next(), size.next(), size.next()) {
+            (Some(Ok(height)), Some(Ok(width)), None) => (height, width),
+            _ => return Err(format!("Bad piece header: {}", header)),
+        };
+
+        let mut cells = Vec::with_capacity(width * height);
+        for row in lines.take(height) {
+            if row.len() != width {
+                return Err(format!("Piece row '{}' is not {} wide", row, width));
+            }
+            for c in row.chars() {
+                match c {
+                    OCCUPIED => cells.push(true),
+                    EMPTY => cells.push(false),
+                    _ => return Err(format!("Unknown piece cell '{}'", c)),
+                }
+            }
+        }
+        if cells.len() != width * height {
+            return Err(format!("Piece is missing rows: {}", header));
+        }
+        Ok(Piece::new(width, height, cells))
+    }
+}
+
+/// Reads the layout written by `Serialize`
+impl<'de> Deserialize<'de> for Piece {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Layout {
+            width: usize,
+            height: usize,
+            cells: Vec<u8>,
+        }
+
+        let layout = Layout::deserialize(deserializer)?;
+        if layout.cells.len() != layout.width * layout.height {
+            return Err(de::Error::custom("piece cells do not match its size"));
+        }
+        let cells = layout.cells.iter().map(|cell| *cell != 0).collect();
+        Ok(Piece::new(layout.width, layout.height, cells))
+    }
+}
+
 pub struct PieceBag {
     width_range: [usize; 2],
     height_range: [usize; 2],
+    seed: u64,
+    draws: usize,
+    rng: StdRng,
+    /// Number of pieces the bag holds, `None` for an endless bag
+    limit: Option<usize>,
+    /// Pieces dealt in order instead of random ones
+    fixed: Option<Vec<Piece>>,
 }
 
-impl PieceBag {
-    pub fn default() -> PieceBag {
-        PieceBag {
-            width_range: RANGE_DEFAULT,
-            height_range: RANGE_DEFAULT,
-        }
+/// Everything needed to rebuild a PieceBag at the same point in its sequence
+#[derive(Debug, Clone, PartialEq, Eq, DeriveSerialize, Deserialize)]
+pub struct PieceBagState {
+    pub width_range: [usize; 2],
+    pub height_range: [usize; 2],
+    pub seed: u64,
+    pub draws: usize,
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// Pieces of a finite bag, see `PieceBag::finite`
+    #[serde(default)]
+    pub pieces: Option<Vec<Piece>>,
+}
+
+impl Default for PieceBag {
+    fn default() -> PieceBag {
+        PieceBag::new(RANGE_DEFAULT, RANGE_DEFAULT)
     }
+}
 
+impl PieceBag {
     pub fn new(width_range: [usize; 2], height_range: [usize; 2]) -> PieceBag {
         let mut p = PieceBag {
             width_range,
             height_range,
+            seed: 0,
+            draws: 0,
+            rng: StdRng::seed_from_u64(0),
+            limit: None,
+            fixed: None,
         };
         p.width_range.sort();
         p.height_range.sort();
+        p.reseed(thread_rng().gen());
+        p
+    }
+
+    /// Bag whose random pieces follow from `seed`, so the same seed deals
+    /// the same pieces on every run
+    pub fn with_seed(seed: u64, width_range: [usize; 2], height_range: [usize; 2]) -> PieceBag {
+        let mut p = PieceBag::new(width_range, height_range);
+        p.reseed(seed);
+        p
+    }
+
+    /// Bag that deals exactly `pieces`, in order, and is then exhausted
+    pub fn finite(pieces: Vec<Piece>) -> PieceBag {
+        let range = |side: fn(&Piece) -> usize| {
+            let min = pieces.iter().map(side).min().unwrap_or(0);
+            let max = pieces.iter().map(side).max().unwrap_or(0);
+            [min, max + 1]
+        };
+        let mut p = PieceBag::new(range(Piece::width), range(Piece::height));
+        p.limit = Some(pieces.len());
+        p.fixed = Some(pieces);
+        p
+    }
+
+    /// Caps the number of random pieces the bag deals
+    pub fn with_limit(mut self, limit: usize) -> PieceBag {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Pieces left to deal, `None` if the bag never runs out
+    pub fn remaining(&self) -> Option<usize> {
+        self.limit.map(|limit| limit.saturating_sub(self.draws))
+    }
+
+    /// Seed the random pieces of the current game follow from
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn is_fixed(&self) -> bool {
+        self.fixed.is_some()
+    }
+
+    /// Starts the bag over for a new game, with a new seed for random pieces
+    pub fn restart(&mut self) {
+        self.reseed(thread_rng().gen());
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.draws = 0;
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    pub fn state(&self) -> PieceBagState {
+        PieceBagState {
+            width_range: self.width_range,
+            height_range: self.height_range,
+            seed: self.seed,
+            draws: self.draws,
+            limit: self.limit,
+            pieces: self.fixed.clone(),
+        }
+    }
+
+    /// An independent bag that deals the same pieces as this one from here
+    /// on, so mirrored games can each draw from their own copy, on separate
+    /// threads if need be
+    pub fn fork(&self) -> PieceBag {
+        PieceBag {
+            width_range: self.width_range,
+            height_range: self.height_range,
+            seed: self.seed,
+            draws: self.draws,
+            rng: self.rng.clone(),
+            limit: self.limit,
+            fixed: self.fixed.clone(),
+        }
+    }
+
+    /// Rebuilds a bag from its seed and replays its draws so the next piece is
+    /// the one the original bag would have dealt. A finite bag carries its
+    /// pieces in its state and picks up after the ones already dealt.
+    pub fn from_state(state: &PieceBagState) -> PieceBag {
+        let mut p = PieceBag::new(state.width_range, state.height_range);
+        p.reseed(state.seed);
+        if state.pieces.is_none() {
+            for _ in 0..state.draws {
+                p.random_piece();
+            }
+        }
+        p.draws = state.draws;
+        p.limit = state.limit;
+        p.fixed = state.pieces.clone();
         p
     }
 
-    pub fn next(&self) -> Piece {
-        let mut rng = thread_rng();
+    fn random_piece(&mut self) -> Piece {
+        let rng = &mut self.rng;
 
         let width = rng.gen_range(self.width_range[0], self.width_range[1]);
         let height = rng.gen_range(self.height_range[0], self.height_range[1]);
@@ -148,9 +352,124 @@ impl PieceBag {
 
         let x = rng.gen_range(1, p.width - 1);
         let y = rng.gen_range(1, p.height - 1);
-        p.cells[(y * p.width + x) as usize] = true;
-        p.mutate(x, y);
+        p.cells[y * p.width + x] = true;
+        p.mutate(x, y, rng);
 
         p
     }
 }
+
+impl Iterator for PieceBag {
+    type Item = Piece;
+
+    fn next(&mut self) -> Option<Piece> {
+        if self.remaining() == Some(0) {
+            return None;
+        }
+        let piece = match &self.fixed {
+            Some(pieces) => pieces[self.draws].clone(),
+            None => self.random_piece(),
+        };
+        self.draws += 1;
+        Some(piece)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bag_resumes_from_state() {
+        let mut bag = PieceBag::default();
+        for _ in 0..5 {
+            bag.next();
+        }
+        let mut resumed = PieceBag::from_state(&bag.state());
+        assert_eq!(resumed.state(), bag.state());
+
+        for _ in 0..5 {
+            assert_eq!(
+                resumed.next().unwrap().to_string(),
+                bag.next().unwrap().to_string()
+            );
+        }
+    }
+
+    #[test]
+    fn same_seed_deals_same_pieces() {
+        let deal = |seed| {
+            PieceBag::with_seed(seed, RANGE_DEFAULT, [3, 5])
+                .with_limit(10)
+                .map(|p| p.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(deal(42), deal(42));
+        assert_ne!(deal(42), deal(43));
+        assert_eq!(PieceBag::with_seed(42, [3, 4], [3, 4]).seed(), 42);
+    }
+
+    #[test]
+    fn forks_deal_the_same_pieces() {
+        let mut bag = PieceBag::default().with_limit(8);
+        bag.next();
+        let fork = bag.fork();
+        let forked = std::thread::spawn(move || fork.map(|p| p.to_string()).collect::<Vec<_>>());
+        let dealt: Vec<String> = bag.map(|p| p.to_string()).collect();
+
+        assert_eq!(dealt.len(), 7);
+        assert_eq!(forked.join().unwrap(), dealt);
+    }
+
+    #[test]
+    fn half_turn_reverses_cells() {
+        let piece = Piece::new(3, 2, vec![true, true, false, false, false, true]);
+        assert_eq!(
+            piece.rotated_half_turn().to_string(),
+            "Piece 2 3:\n*..\n.**\n"
+        );
+    }
+
+    #[test]
+    fn piece_json_round_trips() {
+        let piece = Piece::new(2, 2, vec![true, false, false, true]);
+        let json = serde_json::to_string(&piece).unwrap();
+        let read: Piece = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(read.to_string(), piece.to_string());
+        assert!(serde_json::from_str::<Piece>(r#"{"width":2,"height":2,"cells":[1]}"#).is_err());
+    }
+
+    #[test]
+    fn piece_text_round_trips() {
+        let piece: Piece = "Piece 2 3:\n.*.\n**.\n".parse().unwrap();
+        assert_eq!(piece.to_string(), "Piece 2 3:\n.*.\n**.\n");
+        assert!("Piece 2 3:\n.*.\n".parse::<Piece>().is_err());
+        assert!("Plateau 2 3:\n".parse::<Piece>().is_err());
+    }
+
+    #[test]
+    fn filled_points_follow_the_cells() {
+        let piece: Piece = "Piece 2 3:\n.*.\n**.\n".parse().unwrap();
+        let points: Vec<Point> = piece.filled_points().collect();
+
+        assert_eq!(piece.cell_count(), 3);
+        assert_eq!(
+            points,
+            vec![Point::new(1, 0), Point::new(0, 1), Point::new(1, 1)]
+        );
+        assert!(points.iter().all(|p| piece.get(*p)));
+    }
+
+    #[test]
+    fn finite_bags_run_out() {
+        let pieces = vec![Piece::new_blank(1, 2), Piece::new_blank(3, 1)];
+        let mut bag = PieceBag::finite(pieces);
+        assert_eq!(bag.remaining(), Some(2));
+        assert_eq!(bag.next().map(|p| p.width()), Some(1));
+        assert_eq!(bag.next().map(|p| p.width()), Some(3));
+        assert!(bag.next().is_none());
+
+        assert_eq!(PieceBag::default().with_limit(3).count(), 3);
+    }
+}
@@ -0,0 +1,46 @@
+use super::{Plateau, Player, Point};
+use crate::eval::DistanceField;
+
+impl Plateau {
+    /// Steps from every cell to the nearest cell of `player`'s opponent,
+    /// counted like `eval::DistanceField`: in the eight directions, across
+    /// empty cells and around obstacles. Row-major like `to_owner_grid`.
+    /// Opponent cells are 0, obstacles and cells the opponent can not reach
+    /// are `u32::MAX`. The usual filler heuristic places pieces on the
+    /// lowest values.
+    pub fn heat_map(&self, player: Player) -> Vec<u32> {
+        let field = DistanceField::new(self.view(), player.opponent());
+        let width = self.width;
+        (0..self.width * self.height)
+            .map(|i| Point::new((i % width) as i32, (i / width) as i32))
+            .map(|p| field.get(&p).unwrap_or(u32::MAX))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn heat_grows_away_from_the_opponent() {
+        let plateau = Plateau::try_from(String::from("O..\n.#.\n..X\n")).unwrap();
+
+        assert_eq!(
+            plateau.heat_map(Player::Player1),
+            vec![3, 2, 2, 2, u32::MAX, 1, 2, 1, 0]
+        );
+        assert_eq!(plateau.heat_map(Player::Player2)[8], 3);
+    }
+
+    #[test]
+    fn heat_goes_around_obstacles() {
+        let plateau = Plateau::try_from(String::from("O#..\n.#..\n...X\n")).unwrap();
+        let heat = plateau.heat_map(Player::Player2);
+
+        assert_eq!(heat[1], u32::MAX);
+        assert_eq!(heat[2], 4);
+        assert_eq!(heat[11], 4);
+    }
+}
@@ -1,36 +1,52 @@
+mod heat_map;
+mod overlap_rule;
+mod owner_grid;
 mod parser;
+pub use overlap_rule::OverlapRule;
+pub use owner_grid::{OWNER_BLOCKED, OWNER_EMPTY, OWNER_PLAYER1, OWNER_PLAYER2};
+mod placement_error;
+pub use placement_error::PlacementError;
+mod snapshot;
+pub use snapshot::PlateauState;
+mod start_layout;
+pub use start_layout::StartLayout;
+mod view;
+pub use view::PlateauView;
 
 use super::{constants, Piece, Player, Point};
 
 use constants::*;
 
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::fmt;
 
 const DEFAULT_SIZE: usize = 50;
 const DEFAULT_P1_START: Point = Point { x: 5, y: 5 };
 const DEFAULT_P2_START: Point = Point { x: 44, y: 44 };
+/// Attempts made at finding fair random start points before giving up
+const RANDOM_START_ATTEMPTS: usize = 100;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 enum Cell {
     Player1(bool),
     Player2(bool),
     Empty,
+    /// Obstacle that no piece may cover
+    Blocked,
 }
 
 impl PartialEq for Cell {
     fn eq(&self, other: &Self) -> bool {
         use Cell::*;
-        match self {
-            Player1(_) => match other {
-                Player1(_) => true,
-                _ => false,
-            },
-            Player2(_) => match other {
-                Player2(_) => true,
-                _ => false,
-            },
-            _ => self == other,
-        }
+        matches!(
+            (self, other),
+            (Player1(_), Player1(_))
+                | (Player2(_), Player2(_))
+                | (Empty, Empty)
+                | (Blocked, Blocked)
+        )
     }
 }
 
@@ -40,23 +56,40 @@ impl Cell {
         match self {
             Player1(_) => Player1(false),
             Player2(_) => Player2(false),
-            Empty => Empty,
+            cell => *cell,
         }
     }
+
+    fn is_claimed(&self) -> bool {
+        matches!(self, Cell::Player1(_) | Cell::Player2(_))
+    }
 }
 
-#[derive(Debug)]
+/// Serializes with everything but the undo stack, for checkpoints
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Plateau {
     player1_start: Point,
     player2_start: Point,
     width: usize,
     height: usize,
     cells: Vec<Cell>,
+    /// Move number at which each cell was last claimed, start cells are stamped 0
+    stamps: Vec<usize>,
+    /// High-value cells, see `HILL`
+    hills: Vec<bool>,
+    /// Move of the game in progress, see `set_move_number`
+    move_number: usize,
     last_piece: Option<(Point, Piece)>,
+    overlap_rule: OverlapRule,
+    /// States before the latest placements, see `set_undo_depth`
+    #[serde(skip)]
+    undo: VecDeque<PlateauState>,
+    #[serde(skip)]
+    undo_depth: usize,
 }
 
-impl Plateau {
-    pub fn default() -> Self {
+impl Default for Plateau {
+    fn default() -> Self {
         Plateau::new(
             DEFAULT_SIZE,
             DEFAULT_SIZE,
@@ -65,7 +98,40 @@ impl Plateau {
         )
         .unwrap()
     }
+}
 
+/// Start points for both players where `free` allows them. Player2 mirrors
+/// Player1 through the centre and the two are kept at least a quarter of the
+/// board's perimeter apart.
+fn random_starts<R: Rng>(
+    width: usize,
+    height: usize,
+    rng: &mut R,
+    free: impl Fn(&Point) -> bool,
+) -> Result<(Point, Point), String> {
+    let min_distance = ((width + height) / 2) as i32;
+
+    for _ in 0..RANDOM_START_ATTEMPTS {
+        if width == 0 || height == 0 {
+            break;
+        }
+        let player1 = Point::new(
+            rng.gen_range(0, width as i32),
+            rng.gen_range(0, height as i32),
+        );
+        let player2 = Point::new(width as i32 - 1 - player1.x, height as i32 - 1 - player1.y);
+        let distance = player1.manhattan(&player2) as i32;
+        if distance >= min_distance && distance > 0 && free(&player1) && free(&player2) {
+            return Ok((player1, player2));
+        }
+    }
+    Err(format!(
+        "Could not find fair start points on a {}x{} plateau",
+        width, height
+    ))
+}
+
+impl Plateau {
     pub fn new(
         width: usize,
         height: usize,
@@ -73,12 +139,18 @@ impl Plateau {
         player2: &Point,
     ) -> Result<Plateau, String> {
         let mut plateau = Plateau {
-            player1_start: player1.clone(),
-            player2_start: player2.clone(),
+            player1_start: *player1,
+            player2_start: *player2,
             width,
             height,
-            cells: vec![Cell::Empty; (width * height) as usize],
+            cells: vec![Cell::Empty; width * height],
+            stamps: vec![0; width * height],
+            hills: vec![false; width * height],
+            move_number: 0,
             last_piece: None,
+            overlap_rule: OverlapRule::default(),
+            undo: VecDeque::new(),
+            undo_depth: 0,
         };
 
         match plateau.is_in_bounds(player1) {
@@ -91,16 +163,102 @@ impl Plateau {
             false => return Err(String::from("Player2 out of bounds")),
         };
 
+        if player1 == player2 {
+            return Err(String::from("Start points collide"));
+        }
+
         Ok(plateau)
     }
 
+    pub fn with_layout(
+        width: usize,
+        height: usize,
+        layout: &StartLayout,
+    ) -> Result<Plateau, String> {
+        let (player1, player2) = layout.points(width, height)?;
+        Plateau::new(width, height, &player1, &player2)
+    }
+
+    /// Creates a plateau with start points picked at random. Player2 always
+    /// mirrors Player1 through the centre of the board and the two are kept at
+    /// least a quarter of the board's perimeter apart.
+    pub fn with_random_starts(width: usize, height: usize) -> Result<Plateau, String> {
+        Plateau::with_random_starts_from(width, height, &mut thread_rng())
+    }
+
+    /// `with_random_starts` drawing from `rng`, so seeded generators can
+    /// repeat a layout
+    pub(crate) fn with_random_starts_from<R: Rng>(
+        width: usize,
+        height: usize,
+        rng: &mut R,
+    ) -> Result<Plateau, String> {
+        let (player1, player2) = random_starts(width, height, rng, |_| true)?;
+        Plateau::new(width, height, &player1, &player2)
+    }
+
+    /// The same board with the players' cells cleared and new start points
+    /// picked at random among its empty cells, the way `with_random_starts`
+    /// picks them. Obstacles and hills stay where they are.
+    pub fn with_starts_moved(&self) -> Result<Plateau, String> {
+        let cleared = self.without_players();
+        let free = |point: &Point| {
+            let index = point.y as usize * cleared.width + point.x as usize;
+            cleared.cells[index] == Cell::Empty && !cleared.hills[index]
+        };
+        let (player1, player2) = random_starts(self.width, self.height, &mut thread_rng(), free)?;
+        cleared.with_starts_at(&player1, &player2)
+    }
+
+    /// The same board with the players' cells cleared and the players
+    /// starting at the given points instead. Obstacles and hills stay where
+    /// they are, and a start point on an obstacle is refused.
+    pub fn with_starts_at(&self, player1: &Point, player2: &Point) -> Result<Plateau, String> {
+        let mut plateau = self.without_players();
+        for (point, player) in [(player1, Player::Player1), (player2, Player::Player2)] {
+            if !plateau.is_in_bounds(point) {
+                return Err(format!("{:?} out of bounds", player));
+            }
+            if plateau.get(point) == Cell::Blocked {
+                return Err(format!("{:?} starts on an obstacle", player));
+            }
+        }
+        if player1 == player2 {
+            return Err(String::from("Start points collide"));
+        }
+        plateau.set(player1, Cell::Player1(false));
+        plateau.set(player2, Cell::Player2(false));
+        plateau.player1_start = *player1;
+        plateau.player2_start = *player2;
+        Ok(plateau)
+    }
+
+    fn without_players(&self) -> Plateau {
+        let mut plateau = self.clone();
+        for cell in plateau.cells.iter_mut() {
+            if let Cell::Player1(_) | Cell::Player2(_) = cell {
+                *cell = Cell::Empty;
+            }
+        }
+        plateau
+    }
+
+    /// How many own cells a piece has to cover, see `OverlapRule`
+    pub fn overlap_rule(&self) -> OverlapRule {
+        self.overlap_rule
+    }
+
+    pub fn set_overlap_rule(&mut self, rule: OverlapRule) {
+        self.overlap_rule = rule;
+    }
+
     pub fn is_in_bounds(&self, p: &Point) -> bool {
-        p.x >= 0 && p.x < self.width as i32 && p.y >= 0 && p.y < self.height as i32
+        p.is_within(self.width, self.height)
     }
 
     fn get(&self, p: &Point) -> Cell {
         match self.cells.get((self.width as i32 * p.y + p.x) as usize) {
-            Some(c) => c.clone(),
+            Some(c) => *c,
             None => panic!("Cells incorrectly initialized"),
         }
     }
@@ -114,46 +272,64 @@ impl Plateau {
         piece: &Piece,
         placement: &Point,
         owner: &Cell,
-    ) -> Result<(), String> {
-        let mut overlap = false;
+    ) -> Result<(), PlacementError> {
+        let mut overlap = 0;
+        let max_overlap = self.overlap_rule.max();
 
-        for y in 0..(piece.height()) as i32 {
-            for x in 0..(piece.width()) as i32 {
-                use Cell::{Empty, Player1, Player2};
-                if !piece.get(Point { x, y }) {
-                    continue;
-                }
+        for point in piece.filled_points() {
+            use Cell::{Blocked, Empty, Player1, Player2};
 
-                let offset = &Point { x, y } + &placement;
-                if !self.is_in_bounds(&offset) {
-                    return Err(String::from("Piece out of bounds"));
-                }
+            let offset = &point + placement;
+            if !self.is_in_bounds(&offset) {
+                return Err(PlacementError::OutOfBounds);
+            }
 
-                let plat_cell = self.get(&offset);
-                match plat_cell {
-                    Empty => continue,
-                    Player1(_) | Player2(_) if plat_cell == *owner => match overlap {
-                        true => return Err(String::from("Overlap greater than one")),
-                        false => overlap = true,
-                    },
-                    Player1(_) | Player2(_) => return Err(String::from("Overlap on other player")),
+            let plat_cell = self.get(&offset);
+            match plat_cell {
+                Empty => continue,
+                Blocked => return Err(PlacementError::Blocked),
+                Player1(_) | Player2(_) if plat_cell == *owner => {
+                    overlap += 1;
+                    if max_overlap.is_some_and(|max| overlap > max) {
+                        return Err(PlacementError::DoubleOverlap);
+                    }
                 }
+                Player1(_) | Player2(_) => return Err(PlacementError::OpponentOverlap),
             }
         }
 
-        if !overlap {
-            return Err(String::from("No Overlap"));
+        if overlap < self.overlap_rule.min() {
+            return Err(PlacementError::NoOverlap);
         }
 
         Ok(())
     }
 
+    /// Whether `player` has any legal placement for `piece`
+    pub fn can_place(&self, piece: &Piece, player: Player) -> bool {
+        let owner = match player {
+            Player::Player1 => Cell::Player1(false),
+            Player::Player2 => Cell::Player2(false),
+        };
+        let (width, height) = (self.width as i32, self.height as i32);
+        (1 - piece.height() as i32..height).any(|y| {
+            (1 - piece.width() as i32..width).any(|x| {
+                self.is_valid_placement(piece, &Point::new(x, y), &owner)
+                    .is_ok()
+            })
+        })
+    }
+
     pub fn place_piece(
         &mut self,
         piece: &Piece,
         placement: &Point,
         player: Player,
-    ) -> Result<(), String> {
+    ) -> Result<(), PlacementError> {
+        let before = match self.undo_depth {
+            0 => None,
+            _ => Some(self.snapshot()),
+        };
         self.age_placement();
         let owner = match player {
             Player::Player1 => Cell::Player1(true),
@@ -162,32 +338,138 @@ impl Plateau {
 
         self.is_valid_placement(piece, placement, &owner)?;
 
-        for y in 0..(piece.height()) as i32 {
-            for x in 0..(piece.width()) as i32 {
-                if !piece.get(Point { x, y }) {
+        for point in piece.filled_points() {
+            let offset = &point + placement;
+            self.set(&offset, owner);
+            self.stamps[self.width * offset.y as usize + offset.x as usize] = self.move_number;
+        }
+        self.last_piece = Some((*placement, piece.clone()));
+        if let Some(before) = before {
+            self.push_undo(before);
+        }
+
+        Ok(())
+    }
+
+    /// Reverts every claimed cell that was not placed within the last `window`
+    /// moves, counting the current one, and does not touch (8-way) a cell
+    /// that was. Returns the number of cells cleared.
+    pub fn decay(&mut self, window: usize) -> usize {
+        let is_fresh =
+            |i: usize| self.cells[i].is_claimed() && self.stamps[i] + window > self.move_number;
+        let fresh: Vec<bool> = (0..self.cells.len()).map(is_fresh).collect();
+
+        let mut cleared = 0;
+        for y in 0..self.height as i32 {
+            for x in 0..self.width as i32 {
+                let p = Point { x, y };
+                if !self.get(&p).is_claimed() || self.touches(&fresh, &p) {
                     continue;
                 }
+                self.set(&p, Cell::Empty);
+                cleared += 1;
+            }
+        }
+        cleared
+    }
+
+    /// Surrounds the plateau with a ring of empty cells. Every existing cell,
+    /// start point and the last placement move one down and one right. Moves
+    /// before the growth can no longer be undone.
+    pub fn grow_ring(&mut self) {
+        let width = self.width + 2;
+        let height = self.height + 2;
+        let mut cells = vec![Cell::Empty; width * height];
+        let mut stamps = vec![0; width * height];
+        let mut hills = vec![false; width * height];
+        for (i, cell) in self.cells.iter().enumerate() {
+            let j = width * (i / self.width + 1) + i % self.width + 1;
+            cells[j] = *cell;
+            stamps[j] = self.stamps[i];
+            hills[j] = self.hills[i];
+        }
+
+        let shift = Point::new(1, 1);
+        self.width = width;
+        self.height = height;
+        self.cells = cells;
+        self.stamps = stamps;
+        self.hills = hills;
+        self.player1_start = self.player1_start + shift;
+        self.player2_start = self.player2_start + shift;
+        if let Some((placement, _)) = self.last_piece.as_mut() {
+            *placement = *placement + shift;
+        }
+        // Earlier states no longer fit the plateau
+        self.undo.clear();
+    }
+
+    /// The plateau with the players' cells and start points exchanged
+    pub fn with_players_swapped(&self) -> Plateau {
+        let mut swapped = self.clone();
+        for cell in swapped.cells.iter_mut() {
+            *cell = match *cell {
+                Cell::Player1(is_new) => Cell::Player2(is_new),
+                Cell::Player2(is_new) => Cell::Player1(is_new),
+                other => other,
+            };
+        }
+        swapped.player1_start = self.player2_start;
+        swapped.player2_start = self.player1_start;
+        swapped
+    }
+
+    /// Copy of the plateau as seen by `player` when it can only see `radius`
+    /// cells (8-way) around its own territory. Opponent cells in the fog are
+    /// shown as empty.
+    pub fn fogged(&self, player: Player, radius: usize) -> Plateau {
+        let own = match player {
+            Player::Player1 => Cell::Player1(false),
+            Player::Player2 => Cell::Player2(false),
+        };
+        let mut visible: Vec<bool> = self.cells.iter().map(|c| *c == own).collect();
+        for _ in 0..radius {
+            visible = (0..self.cells.len())
+                .map(|i| {
+                    let p = Point::new((i % self.width) as i32, (i / self.width) as i32);
+                    visible[i] || self.touches(&visible, &p)
+                })
+                .collect();
+        }
 
-                let offset = &Point { x, y } + &placement;
-                self.set(&offset, owner);
+        let mut fogged = self.clone();
+        for (i, cell) in fogged.cells.iter_mut().enumerate() {
+            if cell.is_claimed() && !visible[i] {
+                *cell = Cell::Empty;
             }
         }
-        self.last_piece = Some((*placement, piece.clone()));
+        fogged
+    }
 
-        Ok(())
+    fn touches(&self, mask: &[bool], p: &Point) -> bool {
+        let i = self.width * p.y as usize + p.x as usize;
+        mask[i]
+            || p.neighbors8()
+                .any(|n| self.is_in_bounds(&n) && mask[self.width * n.y as usize + n.x as usize])
+    }
+
+    /// Move of the game in progress, see `set_move_number`
+    pub fn move_number(&self) -> usize {
+        self.move_number
+    }
+
+    /// Starts move `move_number` of the game, counted from 0 with failed
+    /// turns included. Cells claimed until the next move are stamped with it.
+    pub fn set_move_number(&mut self, move_number: usize) {
+        self.move_number = move_number;
     }
 
     fn age_placement(&mut self) {
         if let Some((placement, piece)) = self.last_piece.take() {
-            for y in 0..(piece.height()) as i32 {
-                for x in 0..(piece.width()) as i32 {
-                    if !piece.get(Point { x, y }) {
-                        continue;
-                    }
-                    let offset = &Point { x, y } + &placement;
-                    let owner = self.get(&offset);
-                    self.set(&offset, owner.age());
-                }
+            for point in piece.filled_points() {
+                let offset = point + placement;
+                let owner = self.get(&offset);
+                self.set(&offset, owner.age());
             }
         }
     }
@@ -200,6 +482,78 @@ impl Plateau {
         self.height
     }
 
+    /// The plateau as sent to bots: the size header followed by the bare rows.
+    /// Hills are not part of the protocol and are sent as empty cells.
+    pub fn render_protocol(&self) -> String {
+        let mut out = format!("Plateau {} {}:\n", self.height, self.width);
+        for row in self.cells.chunks(self.width) {
+            out.extend(row.iter().map(|cell| cell.to_string()));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// FNV-1a hash of the plateau's size and map rows. Stable across builds,
+    /// so it can be stored in replays.
+    pub fn content_hash(&self) -> u64 {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let header = format!("{} {}\n", self.width, self.height);
+        let rows = self.to_map_rows().concat();
+        for byte in header.bytes().chain(rows.bytes()) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+        hash
+    }
+
+    /// The plateau's rows in map file format, as read by `Plateau::try_from`.
+    /// Hills only show while they are unclaimed.
+    pub fn to_map_rows(&self) -> Vec<String> {
+        (0..self.height)
+            .map(|y| {
+                (y * self.width..(y + 1) * self.width)
+                    .map(|i| match self.cells[i] {
+                        Cell::Player1(_) => PLAYER1,
+                        Cell::Player2(_) => PLAYER2,
+                        Cell::Empty if self.hills[i] => HILL,
+                        Cell::Empty => EMPTY,
+                        Cell::Blocked => BLOCKED,
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Whether the cell at `p` is a hill, whoever owns it
+    pub fn is_hill(&self, p: &Point) -> bool {
+        self.is_in_bounds(p) && self.hills[self.width * p.y as usize + p.x as usize]
+    }
+
+    /// The plateau with column and row numbers, for terminals and logs
+    pub fn render_human(&self) -> String {
+        let mut out = format!("Plateau {} {}:\n    ", self.height, self.width);
+        out.extend((0..self.width).map(|x| (x % 10).to_string()));
+        out.push('\n');
+        for (y, row) in self.cells.chunks(self.width).enumerate() {
+            out += &format!("{:03} ", y);
+            let hills = &self.hills[y * self.width..(y + 1) * self.width];
+            out.extend(
+                row.iter()
+                    .zip(hills)
+                    .map(|(cell, hill)| match (cell, hill) {
+                        (Cell::Empty, true) => HILL.to_string(),
+                        _ => cell.to_string(),
+                    }),
+            );
+            out.push('\n');
+        }
+        out
+    }
+
+    pub fn view(&self) -> PlateauView<'_> {
+        PlateauView::new(self)
+    }
+
     pub fn player_start(&self, player: Player) -> Point {
         match player {
             Player::Player1 => self.player1_start,
@@ -220,6 +574,7 @@ impl fmt::Display for Cell {
                 false => PLAYER2,
             },
             Cell::Empty => EMPTY,
+            Cell::Blocked => BLOCKED,
         };
         write!(f, "{}", cell)
     }
@@ -227,28 +582,14 @@ impl fmt::Display for Cell {
 
 impl fmt::Display for Plateau {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        writeln!(f, "Plateau {} {}:", self.height, self.width)?;
-        write!(f, "    ")?;
-        for y in 0..(self.width) {
-            write!(f, "{}", y % 10)?;
-        }
-        writeln!(f, "")?;
-
-        for y in 0..(self.height) as i32 {
-            write!(f, "{:03} ", y)?;
-            for x in 0..(self.width) as i32 {
-                let cell = self.get(&Point { x, y });
-                write!(f, "{}", cell)?;
-            }
-            writeln!(f, "")?;
-        }
-        Ok(())
+        write!(f, "{}", self.render_human())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::convert::TryFrom;
 
     fn piece_horizontal() -> Piece {
         let cells = vec![false, true, true, false];
@@ -296,6 +637,115 @@ mod tests {
         );
     }
 
+    #[test]
+    fn overlap_rule_sets_the_cells_to_cover() {
+        let mut plateau = Plateau::try_from(String::from("OO..\n...X\n")).unwrap();
+        let piece = Piece::new(2, 1, vec![true, true]);
+        let owner = Cell::Player1(false);
+
+        assert_eq!(
+            plateau.is_valid_placement(&piece, &Point::new(0, 0), &owner),
+            Err(PlacementError::DoubleOverlap)
+        );
+        plateau.set_overlap_rule(OverlapRule::AtLeast(2));
+        assert_eq!(
+            plateau.is_valid_placement(&piece, &Point::new(0, 0), &owner),
+            Ok(())
+        );
+        assert_eq!(
+            plateau.is_valid_placement(&piece, &Point::new(1, 0), &owner),
+            Err(PlacementError::NoOverlap)
+        );
+    }
+
+    #[test]
+    fn can_place_looks_for_any_legal_placement() {
+        let plateau = Plateau::try_from(String::from("O#..\n#..X\n")).unwrap();
+        let piece = Piece::new(2, 1, vec![true, true]);
+
+        assert!(!plateau.can_place(&piece, Player::Player1));
+        assert!(plateau.can_place(&piece, Player::Player2));
+    }
+
+    #[test]
+    fn fog_hides_distant_opponent_cells() {
+        let plateau = Plateau::try_from(String::from("O...X\n....X\n")).unwrap();
+
+        assert_eq!(
+            plateau.fogged(Player::Player1, 3).to_map_rows(),
+            vec!["O....", "....."]
+        );
+        assert_eq!(
+            plateau.fogged(Player::Player1, 4).to_map_rows(),
+            vec!["O...X", "....X"]
+        );
+        assert_eq!(
+            plateau.fogged(Player::Player2, 0).to_map_rows(),
+            vec!["....X", "....X"]
+        );
+    }
+
+    #[test]
+    fn grow_ring_keeps_cells_in_place() {
+        let mut plateau = Plateau::try_from(String::from("O.\n#X\n")).unwrap();
+        plateau.grow_ring();
+
+        assert_eq!(plateau.to_map_rows(), vec!["....", ".O..", ".#X.", "...."]);
+        assert_eq!(plateau.player_start(Player::Player2), Point::new(2, 2));
+    }
+
+    #[test]
+    fn hills_survive_claims_and_growth() {
+        let mut plateau = Plateau::try_from(String::from("O+.\n..X\n")).unwrap();
+        assert_eq!(plateau.to_map_rows(), vec!["O+.", "..X"]);
+        assert!(plateau.render_human().contains("O+."));
+        assert!(!plateau.render_protocol().contains(HILL));
+
+        plateau
+            .place_piece(&piece_horizontal(), &Point::new(-1, 0), Player::Player1)
+            .unwrap();
+        plateau.grow_ring();
+        assert!(plateau.is_hill(&Point::new(2, 1)));
+        assert!(!plateau.is_hill(&Point::new(1, 1)));
+    }
+
+    #[test]
+    fn swapped_players_trade_places() {
+        let plateau = Plateau::try_from(String::from("O#.\n.+X\n")).unwrap();
+        let swapped = plateau.with_players_swapped();
+
+        assert_eq!(swapped.to_map_rows(), vec!["X#.", ".+O"]);
+        assert_eq!(swapped.player_start(Player::Player1), Point::new(2, 1));
+        assert_eq!(swapped.player_start(Player::Player2), Point::new(0, 0));
+    }
+
+    #[test]
+    fn content_hash_follows_cells() {
+        let plateau = Plateau::try_from(String::from("O..\n..X\n")).unwrap();
+        let same = Plateau::try_from(String::from("O..\n..X\n")).unwrap();
+        let moved = Plateau::try_from(String::from(".O.\n..X\n")).unwrap();
+        let reshaped = Plateau::try_from(String::from("O.\n.X\n..\n")).unwrap();
+
+        assert_eq!(plateau.content_hash(), same.content_hash());
+        assert_ne!(plateau.content_hash(), moved.content_hash());
+        assert_ne!(plateau.content_hash(), reshaped.content_hash());
+    }
+
+    #[test]
+    fn placement_on_obstacle() {
+        let plateau = Plateau::try_from(String::from("...\n#O.\n..X\n")).unwrap();
+        let piece = piece_horizontal();
+
+        assert_eq!(
+            plateau.is_valid_placement(&piece, &Point::new(-1, 1), &Cell::Player1(false)),
+            Err(PlacementError::Blocked)
+        );
+        assert_eq!(
+            plateau.is_valid_placement(&piece, &Point::new(0, 1), &Cell::Player1(false)),
+            Ok(())
+        );
+    }
+
     #[test]
     fn good_placement_vertical_with_overlap() {
         let player_1_start = Point::new(1, 1);
@@ -337,6 +787,83 @@ mod tests {
         );
     }
 
+    #[test]
+    fn decay_clears_stale_cells_only() {
+        let mut plateau = Plateau::new(6, 1, &Point::new(0, 0), &Point::new(5, 0)).unwrap();
+        let piece = Piece::new(2, 1, vec![true, true]);
+
+        plateau.set_move_number(1);
+        plateau
+            .place_piece(&piece, &Point::new(0, 0), Player::Player1)
+            .unwrap();
+        plateau.set_move_number(2);
+        plateau
+            .place_piece(&piece, &Point::new(1, 0), Player::Player1)
+            .unwrap();
+
+        // Only the second placement ((1,0) and (2,0)) is fresh, (0,0) is adjacent
+        // to it and the lone Player2 start at (5,0) is not
+        assert_eq!(plateau.decay(1), 1);
+        assert_eq!(plateau.get(&Point::new(0, 0)), Cell::Player1(false));
+        assert_eq!(plateau.get(&Point::new(5, 0)), Cell::Empty);
+
+        // Failed turns age cells as much as placements
+        plateau.set_move_number(4);
+        assert_eq!(plateau.decay(2), 3);
+    }
+
+    #[test]
+    fn random_starts_are_mirrored() {
+        for _ in 0..20 {
+            let plateau = Plateau::with_random_starts(17, 15).unwrap();
+            let p1 = plateau.player_start(Player::Player1);
+            let p2 = plateau.player_start(Player::Player2);
+            assert_eq!((p1.x + p2.x, p1.y + p2.y), (16, 14));
+            assert_eq!(plateau.get(&p1), Cell::Player1(false));
+            assert_eq!(plateau.get(&p2), Cell::Player2(false));
+        }
+        assert!(Plateau::with_random_starts(1, 1).is_err());
+    }
+
+    #[test]
+    fn moved_starts_keep_the_map() {
+        let map = "O.#....\n.+...#.\n.#...+.\n....#.X\n";
+        let plateau = Plateau::try_from(String::from(map)).unwrap();
+        for _ in 0..20 {
+            let moved = plateau.with_starts_moved().unwrap();
+            let p1 = moved.player_start(Player::Player1);
+            let p2 = moved.player_start(Player::Player2);
+            assert_eq!((p1.x + p2.x, p1.y + p2.y), (6, 3));
+            let rows: String = moved.to_map_rows().concat();
+            let cleared: String = rows.replace(['O', 'X'], ".");
+            assert_eq!(cleared, map.replace('\n', "").replace(['O', 'X'], "."));
+            assert_eq!(rows.matches('O').count() + rows.matches('X').count(), 2);
+        }
+        let walled = Plateau::try_from(String::from("O..#\n#X.#\n")).unwrap();
+        assert!(walled.with_starts_moved().is_err());
+    }
+
+    #[test]
+    fn protocol_rendering_is_bare() {
+        let mut plateau = Plateau::new(3, 2, &Point::new(0, 0), &Point::new(2, 1)).unwrap();
+        plateau
+            .place_piece(&piece_horizontal(), &Point::new(-1, 0), Player::Player1)
+            .unwrap();
+
+        assert_eq!(plateau.render_protocol(), "Plateau 2 3:\noo.\n..X\n");
+    }
+
+    #[test]
+    fn human_rendering_has_headers() {
+        let plateau = Plateau::new(3, 2, &Point::new(0, 0), &Point::new(2, 1)).unwrap();
+
+        assert_eq!(
+            plateau.render_human(),
+            "Plateau 2 3:\n    012\n000 O..\n001 ..X\n"
+        );
+        assert_eq!(plateau.to_string(), plateau.render_human());
+    }
+
     #[test]
     fn default_should_not_panic() {
         Plateau::default();
@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// How many of its own cells a piece has to cover to be placed. The
+/// standard game requires exactly one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OverlapRule {
+    Exactly(usize),
+    AtLeast(usize),
+    /// Inclusive on both ends
+    Between(usize, usize),
+}
+
+impl Default for OverlapRule {
+    fn default() -> Self {
+        OverlapRule::Exactly(1)
+    }
+}
+
+impl OverlapRule {
+    pub fn min(&self) -> usize {
+        match *self {
+            OverlapRule::Exactly(n) | OverlapRule::AtLeast(n) | OverlapRule::Between(n, _) => n,
+        }
+    }
+
+    /// Largest overlap allowed, `None` if there is no limit
+    pub fn max(&self) -> Option<usize> {
+        match *self {
+            OverlapRule::Exactly(n) | OverlapRule::Between(_, n) => Some(n),
+            OverlapRule::AtLeast(_) => None,
+        }
+    }
+
+    pub fn allows(&self, overlap: usize) -> bool {
+        overlap >= self.min() && self.max().is_none_or(|max| overlap <= max)
+    }
+}
+
+/// Parses `N` for exactly N, `N+` for at least N or `N-M` for N to M
+impl FromStr for OverlapRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let count = |n: &str| {
+            n.parse::<usize>()
+                .map_err(|_| format!("Bad overlap rule '{}', expected N, N+ or N-M", s))
+        };
+        let rule = match (s.strip_suffix('+'), s.split_once('-')) {
+            (Some(min), _) => OverlapRule::AtLeast(count(min)?),
+            (None, Some((min, max))) => OverlapRule::Between(count(min)?, count(max)?),
+            (None, None) => OverlapRule::Exactly(count(s)?),
+        };
+        if let OverlapRule::Between(min, max) = rule {
+            if min > max {
+                return Err(format!("Overlap rule '{}' has its bounds reversed", s));
+            }
+        }
+        Ok(rule)
+    }
+}
+
+impl fmt::Display for OverlapRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OverlapRule::Exactly(n) => write!(f, "{}", n),
+            OverlapRule::AtLeast(n) => write!(f, "{}+", n),
+            OverlapRule::Between(min, max) => write!(f, "{}-{}", min, max),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_allows() {
+        let rules: Vec<OverlapRule> = ["1", "2+", "1-3"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        assert_eq!(
+            rules,
+            vec![
+                OverlapRule::Exactly(1),
+                OverlapRule::AtLeast(2),
+                OverlapRule::Between(1, 3)
+            ]
+        );
+        assert_eq!(rules[2].to_string(), "1-3");
+
+        assert!(!rules[0].allows(2));
+        assert!(rules[1].allows(9));
+        assert!(!rules[2].allows(0));
+        assert!("3-1".parse::<OverlapRule>().is_err());
+        assert!("x".parse::<OverlapRule>().is_err());
+    }
+}
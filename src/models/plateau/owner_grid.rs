@@ -0,0 +1,78 @@
+use super::{Cell, Plateau, BLOCKED, EMPTY, PLAYER1, PLAYER2};
+use std::convert::TryFrom;
+
+/// Values of `Plateau::to_owner_grid`
+pub const OWNER_EMPTY: u8 = 0;
+pub const OWNER_PLAYER1: u8 = 1;
+pub const OWNER_PLAYER2: u8 = 2;
+pub const OWNER_BLOCKED: u8 = 255;
+
+impl Plateau {
+    /// Who owns each cell, one byte per cell in row-major order: 0 empty,
+    /// 1 Player1, 2 Player2 and 255 blocked. The buffer can be handed to
+    /// NumPy as a `(height, width)` uint8 array. Hills are not included.
+    pub fn to_owner_grid(&self) -> Vec<u8> {
+        self.cells
+            .iter()
+            .map(|cell| match cell {
+                Cell::Empty => OWNER_EMPTY,
+                Cell::Player1(_) => OWNER_PLAYER1,
+                Cell::Player2(_) => OWNER_PLAYER2,
+                Cell::Blocked => OWNER_BLOCKED,
+            })
+            .collect()
+    }
+
+    /// Reads a grid written by `to_owner_grid`. As with map files, each
+    /// player's first cell becomes its start point.
+    pub fn from_owner_grid(width: usize, height: usize, grid: &[u8]) -> Result<Plateau, String> {
+        if width == 0 || grid.len() != width * height {
+            return Err(format!(
+                "Grid of {} cells does not fit {}x{}",
+                grid.len(),
+                width,
+                height
+            ));
+        }
+        let mut map = String::with_capacity((width + 1) * height);
+        for (i, owner) in grid.iter().enumerate() {
+            map.push(match *owner {
+                OWNER_EMPTY => EMPTY,
+                OWNER_PLAYER1 => PLAYER1,
+                OWNER_PLAYER2 => PLAYER2,
+                OWNER_BLOCKED => BLOCKED,
+                _ => {
+                    return Err(format!(
+                        "Unknown owner {} found at [{}, {}]",
+                        owner,
+                        i % width,
+                        i / width
+                    ))
+                }
+            });
+            if (i + 1) % width == 0 {
+                map.push('\n');
+            }
+        }
+        Plateau::try_from(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Player;
+
+    #[test]
+    fn owner_grid_round_trips() {
+        let plateau = Plateau::try_from(String::from("O.#\n+.X\n")).unwrap();
+        let grid = plateau.to_owner_grid();
+
+        assert_eq!(grid, vec![1, 0, 255, 0, 0, 2]);
+        let read = Plateau::from_owner_grid(3, 2, &grid).unwrap();
+        assert_eq!(read.to_owner_grid(), grid);
+        assert_eq!(read.player_start(Player::Player2).x, 2);
+        assert!(Plateau::from_owner_grid(3, 2, &[1, 0, 7, 0, 0, 2]).is_err());
+        assert!(Plateau::from_owner_grid(4, 2, &grid).is_err());
+    }
+}
@@ -1,4 +1,5 @@
-use super::{Cell, Plateau, Point, EMPTY, PLAYER1, PLAYER2};
+use super::{Cell, OverlapRule, Plateau, Point, BLOCKED, EMPTY, HILL, PLAYER1, PLAYER2};
+use std::collections::VecDeque;
 use std::convert::TryFrom;
 
 impl TryFrom<String> for Plateau {
@@ -8,7 +9,7 @@ impl TryFrom<String> for Plateau {
         if map.is_empty() {
             return Err(String::from("Map is empty"));
         }
-        let width = match map.find(|c| c == '\n') {
+        let width = match map.find('\n') {
             Some(w) => w,
             None => map.len(),
         };
@@ -18,6 +19,7 @@ impl TryFrom<String> for Plateau {
 
         let mut height = 0;
         let mut cells = Vec::new();
+        let mut hills = Vec::new();
         for (y, row) in map.lines().enumerate() {
             if row.len() != width {
                 return Err(String::from("Rows sizes are inconsistent"));
@@ -44,18 +46,20 @@ impl TryFrom<String> for Plateau {
                         }
                         Player2(false)
                     }
-                    EMPTY => Empty,
+                    EMPTY | HILL => Empty,
+                    BLOCKED => Blocked,
                     _ => return Err(format!("Unknown cell '{}' found at [{}, {}]", c, x, y)),
                 };
                 cells.push(cell);
+                hills.push(c == HILL);
             }
             height += 1;
         }
 
-        if let None = player1_start {
+        if player1_start.is_none() {
             return Err(String::from("Player1 not found"));
         }
-        if let None = player2_start {
+        if player2_start.is_none() {
             return Err(String::from("Player2 not found"));
         }
 
@@ -64,8 +68,14 @@ impl TryFrom<String> for Plateau {
             player2_start: player2_start.unwrap(),
             width,
             height,
+            stamps: vec![0; cells.len()],
             cells,
+            hills,
+            move_number: 0,
             last_piece: None,
+            overlap_rule: OverlapRule::default(),
+            undo: VecDeque::new(),
+            undo_depth: 0,
         };
 
         Ok(p)
@@ -120,4 +130,12 @@ mod tests {
 
         assert_eq!(plat.cells, cell_map, "Cell map incorrect");
     }
+
+    #[test]
+    fn map_rows_round_trip() {
+        let string_map = String::from("OX.\nXO.\n");
+        let plat = Plateau::try_from(string_map.clone()).unwrap();
+
+        assert_eq!(plat.to_map_rows().join("\n") + "\n", string_map);
+    }
 }
@@ -0,0 +1,27 @@
+use serde::Serialize;
+use std::fmt;
+
+/// Reasons a piece can not be placed on a plateau
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum PlacementError {
+    OutOfBounds,
+    /// Fewer own cells covered than the overlap rule requires
+    NoOverlap,
+    /// More own cells covered than the overlap rule allows
+    DoubleOverlap,
+    OpponentOverlap,
+    Blocked,
+}
+
+impl fmt::Display for PlacementError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg = match self {
+            PlacementError::OutOfBounds => "Piece out of bounds",
+            PlacementError::NoOverlap => "Not enough overlap",
+            PlacementError::DoubleOverlap => "Too much overlap",
+            PlacementError::OpponentOverlap => "Overlap on other player",
+            PlacementError::Blocked => "Overlap on obstacle",
+        };
+        write!(f, "{}", msg)
+    }
+}
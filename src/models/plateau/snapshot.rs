@@ -0,0 +1,103 @@
+use super::{Cell, Piece, Plateau, Point};
+
+/// The part of a plateau that changes as pieces are placed. Its size, start
+/// points, hills and overlap rule are left out, they stay with the plateau.
+#[derive(Debug, Clone)]
+pub struct PlateauState {
+    width: usize,
+    height: usize,
+    cells: Vec<Cell>,
+    stamps: Vec<usize>,
+    move_number: usize,
+    last_piece: Option<(Point, Piece)>,
+}
+
+impl Plateau {
+    pub fn snapshot(&self) -> PlateauState {
+        PlateauState {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.clone(),
+            stamps: self.stamps.clone(),
+            move_number: self.move_number,
+            last_piece: self.last_piece.clone(),
+        }
+    }
+
+    /// Puts the cells back as they were at `state`. Fails if the plateau
+    /// has grown since.
+    pub fn restore(&mut self, state: &PlateauState) -> Result<(), String> {
+        if (state.width, state.height) != (self.width, self.height) {
+            return Err(format!(
+                "Snapshot of a {}x{} plateau does not fit {}x{}",
+                state.width, state.height, self.width, self.height
+            ));
+        }
+        self.cells = state.cells.clone();
+        self.stamps = state.stamps.clone();
+        self.move_number = state.move_number;
+        self.last_piece = state.last_piece.clone();
+        Ok(())
+    }
+
+    /// Keeps the state before each of the last `depth` placements so they
+    /// can be taken back with `undo`. A depth of 0, the default, keeps none.
+    pub fn set_undo_depth(&mut self, depth: usize) {
+        self.undo_depth = depth;
+        while self.undo.len() > depth {
+            self.undo.pop_front();
+        }
+    }
+
+    /// Takes back the last placement still on the undo stack. Returns
+    /// whether there was one.
+    pub fn undo(&mut self) -> bool {
+        match self.undo.pop_back() {
+            Some(state) => self.restore(&state).is_ok(),
+            None => false,
+        }
+    }
+
+    pub(super) fn push_undo(&mut self, state: PlateauState) {
+        if self.undo_depth == 0 {
+            return;
+        }
+        if self.undo.len() == self.undo_depth {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Player;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn restores_snapshots_and_undoes_moves() {
+        let mut plateau = Plateau::try_from(String::from("O...\n...X\n")).unwrap();
+        let piece = Piece::new(2, 1, vec![true, true]);
+        let start = plateau.snapshot();
+        plateau.set_undo_depth(1);
+
+        plateau
+            .place_piece(&piece, &Point::new(0, 0), Player::Player1)
+            .unwrap();
+        let after_one = plateau.to_map_rows();
+        plateau.set_move_number(1);
+        plateau
+            .place_piece(&piece, &Point::new(1, 0), Player::Player1)
+            .unwrap();
+        assert!(plateau.undo());
+        assert_eq!(plateau.to_map_rows(), after_one);
+        assert_eq!(plateau.move_number(), 1);
+        assert!(!plateau.undo());
+
+        plateau.restore(&start).unwrap();
+        assert_eq!(plateau.to_map_rows(), vec!["O...", "...X"]);
+        plateau.grow_ring();
+        assert!(plateau.restore(&start).is_err());
+    }
+}
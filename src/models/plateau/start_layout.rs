@@ -0,0 +1,138 @@
+use crate::models::Point;
+use std::str::FromStr;
+
+/// Common ways of placing the two start points on a plateau
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StartLayout {
+    /// Opposite corners, inset a tenth of the shorter side
+    Corners,
+    /// Either side of the centre cell
+    Centered,
+    /// Player1 at the given point, Player2 mirrored through the centre
+    Mirrored(Point),
+    Custom(Point, Point),
+}
+
+impl StartLayout {
+    /// Resolves the layout to start points for a plateau of the given size
+    pub fn points(&self, width: usize, height: usize) -> Result<(Point, Point), String> {
+        let (w, h) = (width as i32, height as i32);
+        let mirror = |p: Point| Point::new(w - 1 - p.x, h - 1 - p.y);
+
+        let (player1, player2) = match *self {
+            StartLayout::Corners => {
+                let inset = w.min(h) / 10;
+                let player1 = Point::new(inset, inset);
+                (player1, mirror(player1))
+            }
+            StartLayout::Centered => {
+                let player1 = Point::new((w - 1) / 2 - 1, (h - 1) / 2);
+                (player1, mirror(player1))
+            }
+            StartLayout::Mirrored(player1) => (player1, mirror(player1)),
+            StartLayout::Custom(player1, player2) => (player1, player2),
+        };
+
+        let in_bounds = |p: &Point| p.x >= 0 && p.x < w && p.y >= 0 && p.y < h;
+        if !in_bounds(&player1) {
+            return Err(String::from("Player1 out of bounds"));
+        }
+        if !in_bounds(&player2) {
+            return Err(String::from("Player2 out of bounds"));
+        }
+        if player1 == player2 {
+            return Err(String::from("Start points collide"));
+        }
+        Ok((player1, player2))
+    }
+}
+
+/// Parses `corners`, `centered`, `mirrored:X,Y` or `custom:X,Y:X,Y`
+impl FromStr for StartLayout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let mut parts = s.split(':');
+        let layout = match (parts.next(), parts.next(), parts.next()) {
+            (Some("corners"), None, None) => StartLayout::Corners,
+            (Some("centered"), None, None) => StartLayout::Centered,
+            (Some("mirrored"), Some(p), None) => StartLayout::Mirrored(parse_point(p)?),
+            (Some("custom"), Some(p1), Some(p2)) => {
+                StartLayout::Custom(parse_point(p1)?, parse_point(p2)?)
+            }
+            _ => return Err(format!("Unknown start layout: {}", s)),
+        };
+        match parts.next() {
+            Some(_) => Err(format!("Unknown start layout: {}", s)),
+            None => Ok(layout),
+        }
+    }
+}
+
+fn parse_point(s: &str) -> Result<Point, String> {
+    let mut coordinates = s.split(',').map(|c| c.trim().parse::<i32>());
+    match (coordinates.next(), coordinates.next(), coordinates.next()) {
+        (Some(Ok(x)), Some(Ok(y)), None) => Ok(Point::new(x, y)),
+        _ => Err(format!("Bad point: {}", s)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Plateau;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn presets_are_mirrored() {
+        assert_eq!(
+            StartLayout::Corners.points(50, 50),
+            Ok((Point::new(5, 5), Point::new(44, 44)))
+        );
+        assert_eq!(
+            StartLayout::Centered.points(15, 15),
+            Ok((Point::new(6, 7), Point::new(8, 7)))
+        );
+        assert_eq!(
+            StartLayout::Mirrored(Point::new(1, 2)).points(10, 10),
+            Ok((Point::new(1, 2), Point::new(8, 7)))
+        );
+    }
+
+    #[test]
+    fn layouts_keep_the_map() {
+        let plateau = Plateau::try_from(String::from("O..#\n.#..\n...X\n")).unwrap();
+        let (player1, player2) = StartLayout::Custom(Point::new(1, 0), Point::new(2, 2))
+            .points(4, 3)
+            .unwrap();
+        let moved = plateau.with_starts_at(&player1, &player2).unwrap();
+
+        assert_eq!(moved.to_map_rows(), vec![".O.#", ".#..", "..X."]);
+        assert_eq!(
+            plateau.with_starts_at(&Point::new(1, 1), &player2).err(),
+            Some(String::from("Player1 starts on an obstacle"))
+        );
+    }
+
+    #[test]
+    fn collisions_are_rejected() {
+        assert!(StartLayout::Mirrored(Point::new(1, 1))
+            .points(3, 3)
+            .is_err());
+        assert!(StartLayout::Custom(Point::new(0, 0), Point::new(0, 0))
+            .points(3, 3)
+            .is_err());
+        assert!(StartLayout::Centered.points(1, 1).is_err());
+    }
+
+    #[test]
+    fn parses_layouts() {
+        assert_eq!("corners".parse(), Ok(StartLayout::Corners));
+        assert_eq!(
+            "custom:1,2:3,4".parse(),
+            Ok(StartLayout::Custom(Point::new(1, 2), Point::new(3, 4)))
+        );
+        assert!("mirrored:1".parse::<StartLayout>().is_err());
+        assert!("corners:1,1".parse::<StartLayout>().is_err());
+    }
+}
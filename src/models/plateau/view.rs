@@ -0,0 +1,122 @@
+use super::{Cell, Plateau, Player, Point};
+use std::fmt;
+
+/// Read-only access to a plateau, handed out to observers so they can inspect
+/// the board without reaching into the engine
+#[derive(Clone, Copy)]
+pub struct PlateauView<'a> {
+    plateau: &'a Plateau,
+}
+
+impl<'a> PlateauView<'a> {
+    pub fn new(plateau: &'a Plateau) -> Self {
+        PlateauView { plateau }
+    }
+
+    pub fn width(&self) -> usize {
+        self.plateau.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.plateau.height
+    }
+
+    pub fn is_in_bounds(&self, p: &Point) -> bool {
+        self.plateau.is_in_bounds(p)
+    }
+
+    /// Owner of the cell at `p`, `None` when empty or out of bounds
+    pub fn get(&self, p: &Point) -> Option<Player> {
+        if !self.is_in_bounds(p) {
+            return None;
+        }
+        owner(self.plateau.get(p))
+    }
+
+    pub fn is_blocked(&self, p: &Point) -> bool {
+        self.is_in_bounds(p) && self.plateau.get(p) == Cell::Blocked
+    }
+
+    pub fn is_hill(&self, p: &Point) -> bool {
+        self.plateau.is_hill(p)
+    }
+
+    /// Whether the cell at `p` was claimed by the last piece placed
+    pub fn is_new(&self, p: &Point) -> bool {
+        self.is_in_bounds(p)
+            && matches!(
+                self.plateau.get(p),
+                Cell::Player1(true) | Cell::Player2(true)
+            )
+    }
+
+    /// Moves played since the cell at `p` was claimed, `None` unless it is owned
+    pub fn age(&self, p: &Point) -> Option<usize> {
+        self.get(p)?;
+        let stamp = self.plateau.stamps[self.plateau.width * p.y as usize + p.x as usize];
+        Some(self.plateau.move_number - stamp)
+    }
+
+    pub fn player_start(&self, player: Player) -> Point {
+        self.plateau.player_start(player)
+    }
+
+    /// Every cell in row-major order along with its owner
+    pub fn cells(&self) -> impl Iterator<Item = (Point, Option<Player>)> + 'a {
+        let width = self.plateau.width;
+        self.plateau.cells.iter().enumerate().map(move |(i, cell)| {
+            let p = Point::new((i % width) as i32, (i / width) as i32);
+            (p, owner(*cell))
+        })
+    }
+
+    /// An owned copy of the plateau, for observers that need to keep it
+    pub fn to_plateau(&self) -> Plateau {
+        self.plateau.clone()
+    }
+
+    /// Number of cells owned by `player`
+    pub fn territory(&self, player: Player) -> usize {
+        let target = match player {
+            Player::Player1 => Cell::Player1(false),
+            Player::Player2 => Cell::Player2(false),
+        };
+        self.plateau
+            .cells
+            .iter()
+            .filter(|cell| **cell == target)
+            .count()
+    }
+}
+
+fn owner(cell: Cell) -> Option<Player> {
+    match cell {
+        Cell::Player1(_) => Some(Player::Player1),
+        Cell::Player2(_) => Some(Player::Player2),
+        Cell::Empty | Cell::Blocked => None,
+    }
+}
+
+impl fmt::Display for PlateauView<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.plateau)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn view_reports_owners_and_territory() {
+        let plateau = Plateau::try_from(String::from("OO.\n..X\n")).unwrap();
+        let view = plateau.view();
+
+        assert_eq!(view.territory(Player::Player1), 2);
+        assert_eq!(view.territory(Player::Player2), 1);
+        assert!(view.get(&Point::new(2, 0)).is_none());
+        assert!(view.get(&Point::new(5, 5)).is_none());
+        assert_eq!(view.cells().filter(|(_, o)| o.is_some()).count(), 3);
+    }
+}
@@ -1,13 +1,22 @@
 use super::constants::*;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter, Result};
 
-#[derive(Debug, Copy, Clone, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Player {
     Player1,
     Player2,
 }
 
+impl Player {
+    pub fn opponent(self) -> Player {
+        match self {
+            Player::Player1 => Player::Player2,
+            Player::Player2 => Player::Player1,
+        }
+    }
+}
+
 impl Display for Player {
     fn fmt(&self, f: &mut Formatter) -> Result {
         use Player::{Player1, Player2};
@@ -1,50 +1,117 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 pub use std::convert::TryFrom;
-use std::ops::Add;
+use std::ops::{Add, Mul, Sub};
 
-#[derive(Debug, Clone, Copy, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Point {
     pub x: i32,
     pub y: i32,
 }
 
-impl Point {
-    pub fn default() -> Self {
+impl Default for Point {
+    fn default() -> Self {
         Point::new(0, 0)
     }
+}
+
+/// Offsets to the four edge-sharing neighbours of a point
+const OFFSETS4: [(i32, i32); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+/// Offsets to the eight neighbours of a point, diagonals included
+const OFFSETS8: [(i32, i32); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+impl Point {
     pub fn new(x: i32, y: i32) -> Self {
         Point { x, y }
     }
+
+    /// Steps between two points moving only along rows and columns
+    pub fn manhattan(&self, other: &Point) -> u32 {
+        ((self.x - other.x).abs() + (self.y - other.y).abs()) as u32
+    }
+
+    /// Steps between two points when diagonal moves are allowed
+    pub fn chebyshev(&self, other: &Point) -> u32 {
+        (self.x - other.x).abs().max((self.y - other.y).abs()) as u32
+    }
+
+    /// The points above, left, right and below, in that order
+    pub fn neighbors4(self) -> impl Iterator<Item = Point> {
+        OFFSETS4
+            .iter()
+            .map(move |&(dx, dy)| Point::new(self.x + dx, self.y + dy))
+    }
+
+    /// The eight surrounding points in reading order
+    pub fn neighbors8(self) -> impl Iterator<Item = Point> {
+        OFFSETS8
+            .iter()
+            .map(move |&(dx, dy)| Point::new(self.x + dx, self.y + dy))
+    }
+
+    /// Whether the point lies on a `width` by `height` grid
+    pub fn is_within(&self, width: usize, height: usize) -> bool {
+        self.x >= 0 && self.y >= 0 && (self.x as usize) < width && (self.y as usize) < height
+    }
+
+    /// The nearest point on a `width` by `height` grid, which must not be empty
+    pub fn clamped(&self, width: usize, height: usize) -> Point {
+        Point::new(
+            self.x.clamp(0, width as i32 - 1),
+            self.y.clamp(0, height as i32 - 1),
+        )
+    }
 }
 
-impl TryFrom<&String> for Point {
+impl TryFrom<&str> for Point {
     type Error = String;
 
-    /// '1 2' -> Point {x: 1, y: 2}
-    fn try_from(s: &String) -> Result<Self, String> {
-        let coordinates: Vec<&str> = s.trim().split(" ").collect();
+    /// '2 1' -> Point {x: 1, y: 2}, the protocol gives the row first.
+    /// Any amount of whitespace may surround and separate the coordinates.
+    fn try_from(s: &str) -> Result<Self, String> {
+        let mut fields = s.split_whitespace();
 
-        let cy = match coordinates.get(0) {
-            Some(s) => s,
-            None => return Err(format!("Bad input: {}", s)),
+        let cy = match fields.next() {
+            Some(field) => field,
+            None => return Err(format!("Missing coordinates: {:?}", s)),
         };
-        let cx = match coordinates.get(1) {
-            Some(s) => s,
-            None => return Err(format!("Bad input: {}", s)),
+        let cx = match fields.next() {
+            Some(field) => field,
+            None => return Err(format!("Missing x coordinate: {:?}", s)),
         };
-        let x = match cx.parse::<i32>() {
+        if let Some(extra) = fields.next() {
+            return Err(format!("Unexpected trailing input {:?}: {:?}", extra, s));
+        }
+        let y = match cy.parse::<i32>() {
             Ok(i) => i,
-            Err(_) => return Err(format!("Invalid x coordinate: {}", s)),
+            Err(_) => return Err(format!("Invalid y coordinate {:?}: {:?}", cy, s)),
         };
-        let y = match cy.parse::<i32>() {
+        let x = match cx.parse::<i32>() {
             Ok(i) => i,
-            Err(_) => return Err(format!("Invalid y coordinate: {}", s)),
+            Err(_) => return Err(format!("Invalid x coordinate {:?}: {:?}", cx, s)),
         };
 
         Ok(Point { x, y })
     }
 }
 
+impl TryFrom<&String> for Point {
+    type Error = String;
+
+    fn try_from(s: &String) -> Result<Self, String> {
+        Point::try_from(s.as_str())
+    }
+}
+
 impl Add for Point {
     type Output = Self;
 
@@ -66,3 +133,78 @@ impl Add for &Point {
         }
     }
 }
+
+impl Sub for Point {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Point {
+            x: self.x - other.x,
+            y: self.y - other.y,
+        }
+    }
+}
+
+impl Sub for &Point {
+    type Output = Point;
+
+    fn sub(self, other: Self) -> Point {
+        Point {
+            x: self.x - other.x,
+            y: self.y - other.y,
+        }
+    }
+}
+
+impl Mul<i32> for Point {
+    type Output = Self;
+
+    fn mul(self, scale: i32) -> Self {
+        Point {
+            x: self.x * scale,
+            y: self.y * scale,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distances_and_arithmetic() {
+        let a = Point::new(1, 2);
+        let b = Point::new(4, -2);
+
+        assert_eq!(a.manhattan(&b), 7);
+        assert_eq!(a.chebyshev(&b), 4);
+        assert_eq!(b - a, Point::new(3, -4));
+        assert_eq!(a + (b - a), b);
+        assert_eq!(a * 3, Point::new(3, 6));
+    }
+
+    #[test]
+    fn parses_row_then_column() {
+        assert_eq!(Point::try_from("2 1\n"), Ok(Point::new(1, 2)));
+        assert_eq!(Point::try_from("  -3 \t 4  "), Ok(Point::new(4, -3)));
+
+        assert!(Point::try_from("")
+            .unwrap_err()
+            .contains("Missing coordinates"));
+        assert!(Point::try_from("2").unwrap_err().contains("Missing x"));
+        assert!(Point::try_from("2 a").unwrap_err().contains("Invalid x"));
+        assert!(Point::try_from("b 1").unwrap_err().contains("Invalid y"));
+        assert!(Point::try_from("2 1 0").unwrap_err().contains("trailing"));
+    }
+
+    #[test]
+    fn neighbours_and_bounds() {
+        let origin = Point::default();
+
+        let inside: Vec<Point> = origin.neighbors4().filter(|n| n.is_within(3, 3)).collect();
+        assert_eq!(inside, vec![Point::new(1, 0), Point::new(0, 1)]);
+        assert_eq!(origin.neighbors8().count(), 8);
+        assert!(origin.neighbors8().all(|n| origin.chebyshev(&n) == 1));
+        assert_eq!(Point::new(-4, 9).clamped(5, 5), Point::new(0, 4));
+    }
+}
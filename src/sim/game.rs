@@ -0,0 +1,179 @@
+use crate::engine::{Growth, Placement, RuleSet};
+use crate::models::{Piece, PieceBag, PlacementError, Plateau, PlateauView, Player, Point};
+use std::fmt;
+
+/// Why a move was refused by a `Game`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimError {
+    /// No pieces are left to play
+    GameOver,
+    /// It is the other player's turn, the one given here
+    NotYourTurn(Player),
+    Placement(PlacementError),
+}
+
+impl fmt::Display for SimError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SimError::GameOver => write!(f, "The game is over"),
+            SimError::NotYourTurn(player) => write!(f, "It is <{}>'s turn", player),
+            SimError::Placement(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+/// A game driven move by move from code, without any bot processes. It
+/// deals pieces, keeps the turn order and applies the same placement, decay
+/// and growth rules as the engine. Refused moves leave the game unchanged.
+pub struct Game {
+    plateau: Plateau,
+    piece_bag: PieceBag,
+    piece: Option<Piece>,
+    to_move: Player,
+    move_count: usize,
+    rules: RuleSet,
+    history: Vec<Placement>,
+}
+
+impl Game {
+    pub fn new(plateau: Plateau, mut piece_bag: PieceBag) -> Game {
+        Game {
+            plateau,
+            piece: piece_bag.next(),
+            piece_bag,
+            to_move: Player::Player1,
+            move_count: 0,
+            rules: RuleSet::default(),
+            history: vec![],
+        }
+    }
+
+    /// Plays under the board rules of `rules`, its turn rules are not used
+    pub fn with_rules(mut self, rules: RuleSet) -> Game {
+        rules.prepare(&mut self.plateau);
+        self.rules = rules;
+        self
+    }
+
+    /// See `EngineBuilder::with_decay`
+    pub fn with_decay(mut self, window: usize) -> Game {
+        self.rules.decay_window = Some(window);
+        self
+    }
+
+    /// See `EngineBuilder::with_growth`
+    pub fn with_growth(mut self, growth: Growth) -> Game {
+        self.rules.growth = Some(growth);
+        self
+    }
+
+    pub fn first_to_move(mut self, player: Player) -> Game {
+        self.to_move = player;
+        self
+    }
+
+    /// The piece the player to move has to place, `None` once the game is over
+    pub fn piece(&self) -> Option<&Piece> {
+        self.piece.as_ref()
+    }
+
+    pub fn to_move(&self) -> Player {
+        self.to_move
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.piece.is_none()
+    }
+
+    pub fn plateau(&self) -> PlateauView<'_> {
+        self.plateau.view()
+    }
+
+    /// Moves played so far, including passed turns
+    pub fn move_count(&self) -> usize {
+        self.move_count
+    }
+
+    /// Successful placements, in the order they were made
+    pub fn history(&self) -> &[Placement] {
+        &self.history
+    }
+
+    /// Places the current piece for `player` with its top left corner at
+    /// `point`, then hands the turn over
+    pub fn play(&mut self, player: Player, point: Point) -> Result<(), SimError> {
+        self.check_turn(player)?;
+        let piece = self.piece.as_ref().ok_or(SimError::GameOver)?;
+        self.plateau
+            .place_piece(piece, &point, player)
+            .map_err(SimError::Placement)?;
+        self.history.push(Placement {
+            player,
+            move_number: self.move_count,
+            point,
+        });
+        self.end_turn();
+        Ok(())
+    }
+
+    /// Gives up the turn without placing, as the engine does for a rejected move
+    pub fn pass(&mut self, player: Player) -> Result<(), SimError> {
+        self.check_turn(player)?;
+        self.end_turn();
+        Ok(())
+    }
+
+    fn check_turn(&self, player: Player) -> Result<(), SimError> {
+        if self.is_over() {
+            return Err(SimError::GameOver);
+        }
+        match player == self.to_move {
+            true => Ok(()),
+            false => Err(SimError::NotYourTurn(self.to_move)),
+        }
+    }
+
+    fn end_turn(&mut self) {
+        self.move_count += 1;
+        self.rules.after_move(&mut self.plateau, self.move_count);
+        self.to_move = self.to_move.opponent();
+        self.piece = self.piece_bag.next();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    fn dot() -> Piece {
+        "Piece 1 2:\n**\n".parse().unwrap()
+    }
+
+    #[test]
+    fn enforces_turns_and_placements() {
+        let plateau = Plateau::try_from(String::from("O...\n...X\n")).unwrap();
+        let mut game = Game::new(plateau, PieceBag::finite(vec![dot(), dot(), dot()]));
+
+        assert_eq!(
+            game.play(Player::Player2, Point::new(2, 1)),
+            Err(SimError::NotYourTurn(Player::Player1))
+        );
+        assert_eq!(
+            game.play(Player::Player1, Point::new(2, 0)),
+            Err(SimError::Placement(PlacementError::NoOverlap))
+        );
+        game.play(Player::Player1, Point::new(0, 0)).unwrap();
+        game.pass(Player::Player2).unwrap();
+        game.play(Player::Player1, Point::new(1, 0)).unwrap();
+
+        assert!(game.is_over());
+        assert_eq!(
+            game.play(Player::Player2, Point::new(2, 1)),
+            Err(SimError::GameOver)
+        );
+        assert_eq!(game.plateau().territory(Player::Player1), 3);
+        assert_eq!(game.history().len(), 2);
+        assert_eq!(game.move_count(), 3);
+    }
+}
@@ -0,0 +1,2 @@
+mod game;
+pub use game::{Game, SimError};